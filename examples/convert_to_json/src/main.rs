@@ -1,4 +1,5 @@
 //! Simple (and dirty) way to dump a subset of the data from the .root files to json files
+//! For dumping arbitrary branches of any tree, see `root-ls dump`
 
 extern crate serde;
 #[macro_use]
@@ -41,11 +42,14 @@ fn main() {
         .flat_map(|ev| to_json(&ev))
         .take(10_000)
         .for_each(|json| {
+            // Separate the elements; a trailing comma is not valid JSON
+            if event_counter > 0 {
+                f.write_all(b",\n").unwrap();
+            }
             serde_json::to_writer(&mut f, &json).unwrap();
-            f.write_all(b",\n").unwrap();
             event_counter += 1;
         });
-    f.write_all(b"]").unwrap();
+    f.write_all(b"\n]\n").unwrap();
     println!("Wrote {} events to events.json", event_counter);
 }
 
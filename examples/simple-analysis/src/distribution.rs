@@ -4,7 +4,7 @@
 ///    interaction point along beam axis
 
 use std::f64::consts::PI;
-use gnuplot::{Figure, AxesCommon, PlotOption, Tick, AutoOption};
+use std::fs::File;
 use malice::Event;
 use malice::default_track_filter;
 use failure::Error;
@@ -27,16 +27,21 @@ impl SimpleAnalysis {
         SimpleAnalysis {
             single_particles: HistogramBuilder::<[usize; 3]>::new()
                 .add_equal_width_axis(neta, -0.8, 0.8)
+                .with_label("eta")
                 .add_equal_width_axis(nphi, 0., 2. * PI)
+                .with_label("phi")
                 .add_equal_width_axis(nzvtx, zmin, zmax)
+                .with_label("z_vtx")
                 .build()
                 .expect("Error building histogram"),
             z_vertex: HistogramBuilder::<[usize; 1]>::new()
                 .add_equal_width_axis(nzvtx, zmin, zmax)
+                .with_label("z_vtx")
                 .build()
                 .expect("Error building histogram"),
             multiplicity: HistogramBuilder::<[usize; 1]>::new()
                 .add_equal_width_axis(nmult, 0.0, nmult as f64)
+                .with_label("multiplicity")
                 .build()
                 .expect("Error building histogram"),            
         }
@@ -44,8 +49,50 @@ impl SimpleAnalysis {
 }
 
 
-impl SimpleAnalysis {
-    pub fn process_event(mut self, event: &Event) -> Self
+/// The histograms of a `SimpleAnalysis` shared between the threads
+/// processing the events
+pub struct SharedAnalysis {
+    single_particles: SharedHistogram<f32, [usize; 3]>,
+    z_vertex: SharedHistogram<i32, [usize; 1]>,
+    multiplicity: SharedHistogram<f32, [usize; 1]>,
+}
+
+/// A thread's share of the analysis. Its fills are added to the
+/// `SharedAnalysis` when it is dropped
+pub struct LocalAnalysis {
+    single_particles: LocalHistogram<f32, [usize; 3]>,
+    z_vertex: LocalHistogram<i32, [usize; 1]>,
+    multiplicity: LocalHistogram<f32, [usize; 1]>,
+}
+
+impl SharedAnalysis {
+    pub fn new(analysis: SimpleAnalysis) -> SharedAnalysis {
+        SharedAnalysis {
+            single_particles: SharedHistogram::new(analysis.single_particles),
+            z_vertex: SharedHistogram::new(analysis.z_vertex),
+            multiplicity: SharedHistogram::new(analysis.multiplicity),
+        }
+    }
+    /// Histograms to be filled by the current thread
+    pub fn local(&self) -> LocalAnalysis {
+        LocalAnalysis {
+            single_particles: self.single_particles.local(),
+            z_vertex: self.z_vertex.local(),
+            multiplicity: self.multiplicity.local(),
+        }
+    }
+    /// The filled analysis once all `LocalAnalysis`es are dropped
+    pub fn into_inner(self) -> Result<SimpleAnalysis, Error> {
+        Ok(SimpleAnalysis {
+            single_particles: self.single_particles.into_inner()?,
+            z_vertex: self.z_vertex.into_inner()?,
+            multiplicity: self.multiplicity.into_inner()?,
+        })
+    }
+}
+
+impl LocalAnalysis {
+    pub fn process_event(&mut self, event: &Event)
     {
         // Fill only if we have a valid primary vertex
         if let Some(prime_vtx) = event.primary_vertex() {
@@ -60,110 +107,86 @@ impl SimpleAnalysis {
                                      .filter(|tr| default_track_filter(&tr, &prime_vtx))
                                      .count() as f64]);
         };
-        self
     }
+}
+
+impl SimpleAnalysis {
     pub fn write_to_disc(&self) -> Result<(), Error> {
-        self.single_particles.dump_to_file("hybrid")?;
-        self.z_vertex.dump_to_file("z_pos")?;
+        self.single_particles.write_npz(File::create("hybrid.npz")?)?;
+        self.z_vertex.write_npz(File::create("z_pos.npz")?)?;
+        // Mergeable with `merge-histograms` across several runs
+        self.single_particles.save_to_file("hybrid.hist")?;
+        self.z_vertex.save_to_file("z_pos.hist")?;
+        self.multiplicity.save_to_file("multiplicity.hist")?;
         Ok(())
     }    
 }
 
 impl SimpleAnalysis {
-    /// Visualized the data using gnuplot-rs
-    pub fn visualize(&self) {
-        use std::f64::consts::PI;
-        let mut fg = Figure::new();
-        let eta_bin_width = self.single_particles.widths(0)[0] as f32;
-        let plot_options = [PlotOption::Color("#d95f02"), PlotOption::FillAlpha(0.8)];
-        fg.axes2d()
-            .set_pos_grid(2, 2, 0)
-            .set_title("η track distribution", &[])
-            .set_x_label("η", &[])
-            .set_y_label("⟨dN_{ch} / dη ⟩_{event}", &[])
-            .boxes(&self.single_particles.centers(0),
-                   // Sum over phi and z
-                   (&self.single_particles
-                    .counts
-                    .sum_axis(Axis(1))
-                    .sum_axis(Axis(1))
-                    / self.z_vertex.counts.scalar_sum() as f32
-                    / eta_bin_width
-                   ).view(), 
-                   &plot_options);
-
-        let phi_bin_width = self.single_particles.widths(1)[0] as f32;
-        let x_ticks = vec![Tick::Major(0.0, AutoOption::Fix("0".to_owned())),
-                           Tick::Major(0.5 * PI, AutoOption::Fix("0.5 π".to_owned())),
-                           Tick::Major(PI, AutoOption::Fix("π".to_owned())),
-                           Tick::Major(1.5 * PI, AutoOption::Fix("1.5π".to_owned())),
-                           Tick::Major(2.0 * PI, AutoOption::Fix("2π".to_owned()))];
-                           
-        fg.axes2d()
-            .set_pos_grid(2, 2, 1)
-            .set_title("φ track distribution", &[])
-            .set_x_label("φ [rad]", &[])
-            .set_y_label("⟨dN_{ch} / dφ ⟩_{event}", &[])
-            .set_x_range(AutoOption::Fix(0.0), AutoOption::Fix(2.0 * PI))
-            .set_x_ticks_custom(x_ticks, &[], &[])
-            .boxes(&self.single_particles.centers(1),
-                   // Sum over eta and z
-                   (&self.single_particles
-                    .counts
-                    .sum_axis(Axis(2))
-                    .sum_axis(Axis(0))
-                    / self.z_vertex.counts.scalar_sum() as f32
-                    / phi_bin_width
-                   ).view(), 
-                   &plot_options);
-
-        fg.axes2d()
-            .set_pos_grid(2, 2, 2)
-            .set_title("Primary vertex position", &[])
-            .set_x_label("z [cm]", &[])
-            .set_y_label("# events", &[])
-            .boxes(&self.z_vertex.centers(0),
-                   &self.z_vertex.counts,
-                   &plot_options);
-
-        fg.axes2d()
-            .set_pos_grid(2, 2, 3)
-            .set_title("N_{ch} distribution", &[])
-            .set_x_label("N_{ch}", &[])
-            .set_y_label("# events", &[])
-            // .set_x_log(Some(10.0))
-            .set_y_log(Some(10.0))
-            .boxes(&self.multiplicity.centers(0),
-                   &self.multiplicity.counts,
-                   &plot_options);
-        fg.show();
+    /// Plot the distributions to `simple-analysis.svg`
+    pub fn visualize(&self) -> Result<(), Error> {
+        let mut fig = Figure::new(2);
+        let n_events = self.z_vertex.counts.scalar_sum() as f32;
+        let bar_color = "#d95f02";
+        let error_bar_color = "black";
+
+        let mut eta = self.single_particles.project::<[usize; 1]>(&[0])?;
+        let eta_bin_width = eta.widths(0)[0] as f32;
+        eta.scale(1.0 / n_events / eta_bin_width);
+        fig.plot()
+            .set_title("η track distribution")
+            .set_x_label("η")
+            .set_y_label("⟨dN_ch / dη⟩_event")
+            .bars(&eta, bar_color);
+
+        let mut phi = self.single_particles.project::<[usize; 1]>(&[1])?;
+        let phi_bin_width = phi.widths(0)[0] as f32;
+        phi.scale(1.0 / n_events / phi_bin_width);
+        fig.plot()
+            .set_title("φ track distribution")
+            .set_x_label("φ [rad]")
+            .set_y_label("⟨dN_ch / dφ⟩_event")
+            .set_x_range(0.0, 2.0 * PI)
+            .set_x_ticks(&[(0.0, "0"),
+                           (0.5 * PI, "0.5π"),
+                           (PI, "π"),
+                           (1.5 * PI, "1.5π"),
+                           (2.0 * PI, "2π")])
+            .bars(&phi, bar_color);
+
+        fig.plot()
+            .set_title("Primary vertex position")
+            .set_x_label("z [cm]")
+            .set_y_label("# events")
+            .bars(&self.z_vertex, bar_color)
+            .error_bars(&self.z_vertex, error_bar_color);
+
+        fig.plot()
+            .set_title("N_ch distribution")
+            .set_x_label("N_ch")
+            .set_y_label("# events")
+            .set_y_log()
+            .bars(&self.multiplicity, bar_color)
+            .error_bars(&self.multiplicity, error_bar_color);
+
+        let eta_phi = self.single_particles.project::<[usize; 2]>(&[0, 1])?;
+        fig.plot()
+            .set_title("η-φ track distribution")
+            .set_x_label("η")
+            .set_y_label("φ [rad]")
+            .heatmap(&eta_phi);
 
+        fig.save_svg("simple-analysis.svg")
     }
     /// Compute the centrality edges based on the N_ch/Event distribution
     pub fn compute_centrality_edges(&self) {
-        let tot = self.multiplicity.counts.scalar_sum();
-        let cum: Vec<_> = self.multiplicity.counts
-            .iter()
-            .scan(0.0, |state, el| {
-                *state += el;
-                Some(*state)
-            })
-            // convert to %; 100% is first bin
-            .map(|v| (1.0 - v / tot) * 100.0)
-            // Bin width is one track, so we just enumerate to have the number of tracks
-            .enumerate()
-            .collect::<Vec<_>>();
-        let percent_edges = (1..11)
-            .rev()
-            .filter_map(|v| {
-                let want_this_percent = (v * 10) as f32;
-                cum.iter()
-                    .find(|bin_percent| bin_percent.1 <= want_this_percent)
-            })
-            .collect::<Vec<_>>();
         println!("Number of valid tracks | less than %");
-        for cent_edge in percent_edges {
-            println!("{:4} | {:3}%", cent_edge.0, cent_edge.1);
+        for percent in (1..11).rev().map(|v| v * 10) {
+            // The given percentage of events has more valid tracks
+            let quantile = 1.0 - f64::from(percent) / 100.0;
+            if let Some(n_tracks) = self.multiplicity.quantile(0, quantile) {
+                println!("{:6.1} | {:3}%", n_tracks, percent);
+            }
         }
     }
 }
@@ -1,19 +1,18 @@
 extern crate alice_open_data;
 extern crate failure;
-extern crate gnuplot;
 extern crate histogram;
 extern crate malice;
+extern crate rayon;
 extern crate root_io;
 
-use std::sync::mpsc;
-use std::thread::spawn;
+use rayon::prelude::*;
 
 use malice::DatasetIntoIter as DsIntoIter;
 use malice::default_event_filter;
 use root_io::RootFile;
 
 mod distribution;
-use distribution::SimpleAnalysis;
+use distribution::{SharedAnalysis, SimpleAnalysis};
 
 fn main() {
     let files: Vec<_> = alice_open_data::all_files_10h()
@@ -24,26 +23,24 @@ fn main() {
     if files.is_empty() {
         panic!("Somehow no files were found! Something is fishy!");
     }
-    // Create a channel to transfer items from the worker thread.
-    let (tx, rx) = mpsc::sync_channel(10);
-    // Move this iterator to a new worker thread and run it there.
-    spawn(move || {
-        files
-            .into_iter()
-            .map(|path| RootFile::new_from_file(&path).expect("Failed to open file"))
-            .map(|rf| rf.items()[0].as_tree().unwrap())
-            .flat_map(|tree| match DsIntoIter::new(&tree) {
-                Ok(s) => s,
+    // Each file is processed in its own thread, which fills its own
+    // copy of the histograms. The copies are added to the shared
+    // histograms once the thread is done
+    let shared = SharedAnalysis::new(SimpleAnalysis::new());
+    files
+        .par_iter()
+        .for_each_init(|| shared.local(), |local, path| {
+            let rf = RootFile::new_from_file(path).expect("Failed to open file");
+            let tree = rf.items()[0].as_tree().unwrap();
+            match DsIntoIter::new(&tree) {
+                Ok(events) => events
+                    .filter(default_event_filter)
+                    .for_each(|ev| local.process_event(&ev)),
                 Err(err) => panic!("An error occured! Message: {}", err),
-            })
-            .for_each(|ev| { tx.send(ev).unwrap(); })
-    });
-    
-    let analysis: SimpleAnalysis = rx
-        .into_iter()
-        .filter(default_event_filter)
-        .fold(SimpleAnalysis::new(), |analysis, ev| { analysis.process_event(&ev) });
+            }
+        });
+    let analysis = shared.into_inner().unwrap();
     analysis.write_to_disc().unwrap();
     analysis.compute_centrality_edges();
-    analysis.visualize();
+    analysis.visualize().unwrap();
 }
@@ -0,0 +1,22 @@
+//! Add up histograms saved with `Histogram::save_to_file`, e.g. the
+//! partial outputs of distributed jobs:
+//! ```text
+//! merge-histograms merged.hist job-*/output.hist
+//! ```
+extern crate histogram;
+
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("Usage: merge-histograms OUTPUT INPUT...");
+        process::exit(1);
+    }
+    let inputs: Vec<&str> = args[1..].iter().map(|s| s.as_str()).collect();
+    if let Err(e) = histogram::merge_files(&inputs, &args[0]) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
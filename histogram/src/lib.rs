@@ -1,33 +1,345 @@
-extern crate bincode;
+#[macro_use]
 extern crate failure;
 extern crate itertools;
 extern crate ndarray;
+extern crate npy_writer;
 extern crate num_traits as libnum;
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate bincode;
 
 use ndarray as nd;
-use serde::Serialize;
-use bincode::serialize;
 use failure::Error;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 use std::cmp::Ordering;
 use std::marker::PhantomData;
-use std::ops::{Add, AddAssign};
+use std::ops::{Add, AddAssign, Mul, Range, SubAssign};
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{BufReader, BufWriter, Read, Write};
+
+mod plot;
+mod profile;
+mod shared;
+pub use plot::{Figure, Plot};
+pub use profile::Profile;
+pub use shared::{LocalHistogram, SharedHistogram};
 
 // Re-export some ndarray things
 pub use nd::Dimension;
 pub use nd::Axis;
 pub use nd::IxDyn;
+/// Bounds on the element type for generic code, e.g. for converting
+/// histograms from other formats. These are kept out of the crate
+/// root since `NumCast::from` clashes with `From::from` after a glob
+/// import of this crate
+pub mod num {
+    pub use libnum::{Num, NumCast, ToPrimitive};
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Histogram<A, D> {
     edges: Vec<Vec<BinEdges>>,
+    /// How the bin of a value is found along each axis
+    kinds: Vec<AxisKind>,
+    /// Label of each axis; empty if not set
+    labels: Vec<String>,
+    /// Whether each axis has an under- and an overflow bin
+    flow: Vec<bool>,
+    /// The counts in each bin. Along axes with under- and overflow
+    /// bins, the first element is the underflow and the last one the
+    /// overflow bin; see `in_range` for the regular bins only
     pub counts: nd::Array<A, IxDyn>,
+    /// Sum of the squared weights in each bin; `None` unless enabled
+    /// with `enable_sumw2` or by a weighted fill
+    sumw2: Option<nd::Array<A, IxDyn>>,
+    /// Number of fills, including those outside of the range
+    entries: u64,
     dim: PhantomData<D>,
 }
 
+/// Bytes at the start of files written by `Histogram::save_to_file`
+const FILE_MAGIC: &[u8] = b"alice-rs histogram";
+/// Version of the layout of files written by `Histogram::save_to_file`
+const FILE_VERSION: u32 = 1;
+
+/// Header preceding each histogram written by `Histogram::save_to_file`
+#[derive(Serialize, Deserialize)]
+struct FileHeader {
+    version: u32,
+    /// The NumPy type description of the elements, e.g. `<f8`
+    element: String,
+    ndim: u64,
+}
+
+impl FileHeader {
+    /// Read and check the header at the start of the file `name`
+    fn read<R: Read>(r: &mut R, name: &str) -> Result<FileHeader, Error> {
+        let mut magic = vec![0; FILE_MAGIC.len()];
+        if r.read_exact(&mut magic).is_err() || magic != FILE_MAGIC {
+            return Err(format_err!("{} is not a histogram file", name));
+        }
+        let header: FileHeader = bincode::deserialize_from(r)?;
+        if header.version != FILE_VERSION {
+            return Err(format_err!("{} has unsupported format version {}", name, header.version));
+        }
+        Ok(header)
+    }
+}
+
+impl<A, D> Histogram<A, D> {
+    /// The bin edges along `axis`, including the upper edge of the last bin
+    pub fn edges(&self, axis: usize) -> Vec<f64> {
+        let bins = &self.edges[axis];
+        bins.iter()
+            .map(|bin| bin.lower)
+            .chain(bins.last().map(|bin| bin.upper))
+            .collect()
+    }
+
+    /// The number of axes
+    pub fn ndim(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// The label of `axis`; empty if it was not set
+    pub fn label(&self, axis: usize) -> &str {
+        &self.labels[axis]
+    }
+
+    /// Set the label of `axis`
+    pub fn set_label(&mut self, axis: usize, label: &str) {
+        self.labels[axis] = label.to_string();
+    }
+
+    /// Number of times this histogram was filled, including values
+    /// which were out of range
+    pub fn entries(&self) -> u64 {
+        self.entries
+    }
+
+    /// Set the number of times this histogram was filled, e.g. when
+    /// reading it from another format
+    pub fn set_entries(&mut self, entries: u64) {
+        self.entries = entries;
+    }
+
+    /// The categories of the bins along `axis` if it is categorical
+    pub fn categories(&self, axis: usize) -> Option<Vec<i64>> {
+        match self.kinds[axis] {
+            AxisKind::Categories(ref lookup) => {
+                let mut categories = vec![0; lookup.len()];
+                for &(category, bin) in lookup {
+                    categories[bin] = category;
+                }
+                Some(categories)
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether values outside of the range of `axis` are counted in
+    /// under- and overflow bins
+    pub fn has_flow_bins(&self, axis: usize) -> bool {
+        self.flow[axis]
+    }
+
+    /// The counts of values below the range of `axis`; `None` if
+    /// `axis` has no under- and overflow bins
+    pub fn underflow(&self, axis: usize) -> Option<nd::ArrayView<'_, A, IxDyn>> {
+        if self.flow[axis] {
+            Some(self.counts.subview(Axis(axis), 0))
+        } else {
+            None
+        }
+    }
+
+    /// The counts of values above the range of `axis`; `None` if
+    /// `axis` has no under- and overflow bins
+    pub fn overflow(&self, axis: usize) -> Option<nd::ArrayView<'_, A, IxDyn>> {
+        if self.flow[axis] {
+            let last = self.counts.len_of(Axis(axis)) - 1;
+            Some(self.counts.subview(Axis(axis), last))
+        } else {
+            None
+        }
+    }
+
+    /// The counts of the regular bins, i.e. without under- and
+    /// overflow bins
+    pub fn in_range(&self) -> nd::ArrayView<'_, A, IxDyn> {
+        let mut view = self.counts.view();
+        for (axis, &flow) in self.flow.iter().enumerate() {
+            if flow {
+                let n = view.len_of(Axis(axis));
+                view.slice_axis_inplace(Axis(axis), nd::Slice::from(1..n - 1));
+            }
+        }
+        view
+    }
+
+    /// Keep track of the sum of the squared weights in each bin from
+    /// now on. Previous fills are assumed to be unweighted.
+    pub fn enable_sumw2(&mut self)
+    where A: Clone,
+    {
+        if self.sumw2.is_none() {
+            self.sumw2 = Some(self.counts.clone());
+        }
+    }
+
+    /// The sum of the squared weights in each bin if it is tracked
+    pub fn sumw2(&self) -> Option<&nd::Array<A, IxDyn>> {
+        self.sumw2.as_ref()
+    }
+
+    /// Mutable access to the sum of the squared weights in each bin
+    /// if it is tracked
+    pub fn sumw2_mut(&mut self) -> Option<&mut nd::Array<A, IxDyn>> {
+        self.sumw2.as_mut()
+    }
+
+    /// The statistical error of each bin; the square root of the sum
+    /// of the squared weights if it is tracked, otherwise the square
+    /// root of the counts
+    pub fn errors(&self) -> nd::Array<f64, IxDyn>
+    where A: Clone + libnum::ToPrimitive,
+    {
+        self.sumw2.as_ref()
+            .unwrap_or(&self.counts)
+            .mapv(|v| v.to_f64().map_or(f64::NAN, f64::sqrt))
+    }
+
+    /// Fail unless `other` has the same bin edges and the same under-
+    /// and overflow bins as this histogram
+    fn check_binning(&self, other: &Histogram<A, D>) -> Result<(), Error> {
+        if self.edges != other.edges {
+            return Err(format_err!("Histograms have different bin edges"));
+        }
+        if self.kinds != other.kinds {
+            return Err(format_err!("Histograms have different axis types"));
+        }
+        if self.flow != other.flow {
+            return Err(format_err!("Histograms have different under- and overflow bins"));
+        }
+        Ok(())
+    }
+
+    /// Find the bin index containing `value` on `axis`
+    /// Return None if the the value is not in range
+    pub fn find_bin_index_axis(&self, axis: usize, value: f64) -> Option<usize>{
+        self.search_bin(axis, value).ok()
+    }
+
+    /// Find the index along `axis` of `counts` for `value`;
+    /// that may be the under- or overflow bin
+    fn find_count_index_axis(&self, axis: usize, value: f64) -> Option<usize> {
+        match self.search_bin(axis, value) {
+            Ok(idx) => Some(idx + self.flow[axis] as usize),
+            Err(_) if !self.flow[axis] || value.is_nan() => None,
+            Err(0) => Some(0),
+            Err(_) => Some(self.edges[axis].len() + 1),
+        }
+    }
+
+    /// Find the indices into `counts` of `values` along each axis;
+    /// returns false if a value is out of range along an axis
+    /// without flow bins
+    fn find_count_indices(&self, values: &[f64], idxs: &mut [usize]) -> bool {
+        for (dim, (&value, idx)) in values.iter().zip(idxs.iter_mut()).enumerate() {
+            match self.find_count_index_axis(dim, value) {
+                Some(i) => *idx = i,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Search the regular bin along `axis` containing `value`. Like
+    /// `binary_search`, the error is 0 for values below the range and
+    /// the number of bins for values above it. Values which are not a
+    /// category of a categorical axis count as above the range.
+    fn search_bin(&self, axis: usize, value: f64) -> Result<usize, usize> {
+        let bins = &self.edges[axis];
+        let n = bins.len();
+        let first = bins[0];
+        let guess = match self.kinds[axis] {
+            AxisKind::Variable => return bins.binary_search_by(|bin| bin.cmp_with(value)),
+            AxisKind::Categories(ref lookup) => {
+                if value.fract() != 0.0 {
+                    return Err(n);
+                }
+                return lookup.binary_search_by_key(&(value as i64), |&(category, _)| category)
+                    .map(|i| lookup[i].1)
+                    .map_err(|_| n);
+            },
+            AxisKind::Uniform => (value - first.lower) / first.width(),
+            AxisKind::Log => (value / first.lower).ln() / (first.upper / first.lower).ln(),
+        };
+        if guess.is_nan() {
+            return bins.binary_search_by(|bin| bin.cmp_with(value));
+        }
+        let idx = if guess <= 0.0 {
+            0
+        } else if guess >= n as f64 {
+            n - 1
+        } else {
+            guess as usize
+        };
+        // The guess may be off by one due to rounding errors at the edges
+        match bins[idx].cmp_with(value) {
+            Ordering::Equal => Ok(idx),
+            Ordering::Greater if idx == 0 => Err(0),
+            Ordering::Greater if bins[idx - 1].cmp_with(value) == Ordering::Equal => Ok(idx - 1),
+            Ordering::Less if idx == n - 1 => Err(n),
+            Ordering::Less if bins[idx + 1].cmp_with(value) == Ordering::Equal => Ok(idx + 1),
+            _ => bins.binary_search_by(|bin| bin.cmp_with(value)),
+        }
+    }
+
+    /// Add `weight` to the bin at `idxs` and `weight2` to its sum of
+    /// squared weights
+    fn increment(&mut self, idxs: &[usize], weight: A, weight2: A)
+    where A: Copy + AddAssign,
+    {
+        self.counts[idxs] += weight;
+        if let Some(ref mut sumw2) = self.sumw2 {
+            sumw2[idxs] += weight2;
+        }
+    }
+
+    /// Write the histogram as a NumPy `.npz` archive to `w`. It
+    /// contains the array `counts` and the bin edges along each axis
+    /// as `edges_0`, `edges_1`, etc., as well as `sumw2` if it is
+    /// tracked. The boolean array `flow_bins` tells which axes of
+    /// `counts` start with an underflow and end with an overflow bin.
+    /// The categories of categorical axes are stored as
+    /// `categories_0`, etc. In Python, it can be read with `numpy.load`.
+    pub fn write_npz<W: Write>(&self, w: W) -> Result<(), Error>
+    where A: npy_writer::Element,
+    {
+        let mut npz = npy_writer::NpzWriter::new(w);
+        let counts: Vec<A> = self.counts.iter().cloned().collect();
+        npz.add_array("counts", self.counts.shape(), &counts)?;
+        if let Some(ref sumw2) = self.sumw2 {
+            let sumw2: Vec<A> = sumw2.iter().cloned().collect();
+            npz.add_array("sumw2", self.counts.shape(), &sumw2)?;
+        }
+        for axis in 0..self.edges.len() {
+            let edges = self.edges(axis);
+            npz.add_array(&format!("edges_{}", axis), &[edges.len()], &edges)?;
+            if let Some(categories) = self.categories(axis) {
+                npz.add_array(&format!("categories_{}", axis), &[categories.len()], &categories)?;
+            }
+        }
+        npz.add_array("flow_bins", &[self.flow.len()], &self.flow)?;
+        npz.finish()?;
+        Ok(())
+    }
+}
+
 pub trait Centers {
     fn centers(&self, axis: usize) -> Vec<f64>;
 }
@@ -50,78 +362,649 @@ impl<A, D> Widths for Histogram<A, D> {
     }
 }
 
+/// Dimension types of histograms, such as `[usize; 2]`
+pub trait HistDim {
+    /// The number of axes; `None` if it is only known at runtime
+    fn ndim() -> Option<usize>;
+}
+
+impl HistDim for IxDyn {
+    fn ndim() -> Option<usize> {
+        None
+    }
+}
+
+impl<A, D> Histogram<A, D> {
+    /// Save the histogram including its bin edges, axis labels, sums
+    /// of squared weights and number of entries to the file `name`.
+    /// It can be read again with `load_from_file`.
+    pub fn save_to_file(&self, name: &str) -> Result<(), Error>
+    where A: Serialize + npy_writer::Element,
+    {
+        let mut w = BufWriter::new(File::create(name)?);
+        let header = FileHeader {
+            version: FILE_VERSION,
+            element: A::descr().to_string(),
+            ndim: self.edges.len() as u64,
+        };
+        w.write_all(FILE_MAGIC)?;
+        bincode::serialize_into(&mut w, &header)?;
+        bincode::serialize_into(&mut w, self)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Load a histogram saved with `save_to_file`. Fails if the
+    /// element type or the number of axes differ from those of the
+    /// saved histogram.
+    pub fn load_from_file(name: &str) -> Result<Histogram<A, D>, Error>
+    where A: DeserializeOwned + npy_writer::Element,
+          D: HistDim,
+    {
+        let f = File::open(name).map_err(|e| format_err!("Cannot open {}: {}", name, e))?;
+        let mut r = BufReader::new(f);
+        let header = FileHeader::read(&mut r, name)?;
+        if header.element != A::descr() {
+            return Err(format_err!("{} holds elements of type `{}` instead of `{}`",
+                                   name, header.element, A::descr()));
+        }
+        if D::ndim().is_some_and(|n| n as u64 != header.ndim) {
+            return Err(format_err!("{} holds a histogram with {} axes instead of {}",
+                                   name, header.ndim, D::ndim().unwrap()));
+        }
+        let h: Histogram<A, D> = bincode::deserialize_from(&mut r)?;
+        h.check_consistency()
+            .map_err(|e| format_err!("{} is corrupted: {}", name, e))?;
+        Ok(h)
+    }
+
+    /// Load and add up the histograms saved in the files `names`,
+    /// e.g. the partial outputs of distributed jobs. Fails if there
+    /// are no files or if their binnings differ.
+    pub fn merge_from_files(names: &[&str]) -> Result<Histogram<A, D>, Error>
+    where A: Clone + AddAssign + DeserializeOwned + npy_writer::Element,
+          D: HistDim,
+    {
+        let (first, rest) = names.split_first()
+            .ok_or_else(|| format_err!("No histograms to merge"))?;
+        let mut sum = Histogram::load_from_file(first)?;
+        for name in rest {
+            sum.add(&Histogram::load_from_file(name)?)
+                .map_err(|e| format_err!("Cannot merge {}: {}", name, e))?;
+        }
+        Ok(sum)
+    }
+
+    /// Fail if the shapes of the fields do not fit together
+    fn check_consistency(&self) -> Result<(), Error> {
+        let ndim = self.edges.len();
+        if self.kinds.len() != ndim || self.labels.len() != ndim || self.flow.len() != ndim {
+            return Err(format_err!("Inconsistent number of axes"));
+        }
+        let shape: Vec<_> = self.edges.iter().zip(&self.flow)
+            .map(|(bins, &flow)| bins.len() + 2 * flow as usize)
+            .collect();
+        if self.counts.shape() != shape.as_slice()
+            || self.sumw2.as_ref().is_some_and(|sumw2| sumw2.shape() != shape.as_slice()) {
+            return Err(format_err!("Shape of the counts does not match the bin edges"));
+        }
+        Ok(())
+    }
+}
+
+/// Add up the histograms saved in the files `inputs` and save the sum
+/// to the file `output`. The element type and the number of axes are
+/// taken from the files. Fails if they or the binnings differ.
+pub fn merge_files(inputs: &[&str], output: &str) -> Result<(), Error> {
+    fn merge<A>(inputs: &[&str], output: &str) -> Result<(), Error>
+        where A: Clone + AddAssign + Serialize + DeserializeOwned + npy_writer::Element
+    {
+        Histogram::<A, IxDyn>::merge_from_files(inputs)?.save_to_file(output)
+    }
+    let first = inputs.first().ok_or_else(|| format_err!("No histograms to merge"))?;
+    let f = File::open(first).map_err(|e| format_err!("Cannot open {}: {}", first, e))?;
+    let header = FileHeader::read(&mut BufReader::new(f), first)?;
+    match header.element.as_str() {
+        "<f8" => merge::<f64>(inputs, output),
+        "<f4" => merge::<f32>(inputs, output),
+        "<i8" => merge::<i64>(inputs, output),
+        "<i4" => merge::<i32>(inputs, output),
+        "<u8" => merge::<u64>(inputs, output),
+        "<u4" => merge::<u32>(inputs, output),
+        other => Err(format_err!("Cannot merge histograms with elements of type `{}`", other)),
+    }
+}
+
+/// Sum `a` along `axis` over each of the given ranges of indices
+fn merge_along<A>(a: &nd::ArrayD<A>, axis: usize, groups: &[Range<usize>]) -> nd::ArrayD<A>
+    where A: Clone + libnum::Zero + AddAssign
+{
+    let mut shape = a.shape().to_vec();
+    shape[axis] = groups.len();
+    let mut merged = nd::ArrayD::zeros(shape);
+    for (i, group) in groups.iter().enumerate() {
+        let mut lane = merged.subview_mut(Axis(axis), i);
+        for j in group.clone() {
+            lane += &a.subview(Axis(axis), j);
+        }
+    }
+    merged
+}
+
+impl<A, D> Histogram<A, D>
+    where A: Clone + libnum::Zero + AddAssign
+{
+    /// An empty histogram with the same axes as this one, e.g. to be
+    /// filled in another thread and added to this one afterwards
+    pub fn empty_clone(&self) -> Histogram<A, D> {
+        Histogram {
+            edges: self.edges.clone(),
+            kinds: self.kinds.clone(),
+            labels: self.labels.clone(),
+            flow: self.flow.clone(),
+            counts: nd::ArrayD::zeros(self.counts.shape()),
+            sumw2: self.sumw2.as_ref().map(|_| nd::ArrayD::zeros(self.counts.shape())),
+            entries: 0,
+            dim: PhantomData,
+        }
+    }
+
+    /// Sum over all but the given `axes`. The axes of the returned
+    /// histogram are in the order of `axes`. Under- and overflow bins
+    /// of the summed axes are included in the sums. Fails if the
+    /// number of `axes` does not match the dimension `E`.
+    pub fn project<E: HistDim>(&self, axes: &[usize]) -> Result<Histogram<A, E>, Error> {
+        let ndim = self.edges.len();
+        if E::ndim().is_some_and(|n| n != axes.len()) {
+            return Err(format_err!("Cannot project {} axes into {} dimensions",
+                                   axes.len(), E::ndim().unwrap()));
+        }
+        if axes.iter().enumerate().any(|(i, ax)| *ax >= ndim || axes[..i].contains(ax)) {
+            return Err(format_err!("Invalid axes {:?} for a histogram with {} axes", axes, ndim));
+        }
+        // Position of each of `axes` after summing over the others
+        let mut kept = axes.to_vec();
+        kept.sort();
+        let order: Vec<_> = axes.iter()
+            .map(|ax| kept.iter().position(|k| k == ax).unwrap())
+            .collect();
+        let project = |a: &nd::ArrayD<A>| {
+            let mut a = a.clone();
+            for ax in (0..ndim).rev().filter(|ax| !axes.contains(ax)) {
+                a = a.sum_axis(Axis(ax));
+            }
+            a.permuted_axes(IxDyn(&order))
+        };
+        Ok(Histogram {
+            edges: axes.iter().map(|&ax| self.edges[ax].clone()).collect(),
+            kinds: axes.iter().map(|&ax| self.kinds[ax].clone()).collect(),
+            labels: axes.iter().map(|&ax| self.labels[ax].clone()).collect(),
+            flow: axes.iter().map(|&ax| self.flow[ax]).collect(),
+            counts: project(&self.counts),
+            sumw2: self.sumw2.as_ref().map(project),
+            entries: self.entries,
+            dim: PhantomData,
+        })
+    }
+
+    /// Keep only the regular bins in `range` along `axis`. If that axis
+    /// has under- and overflow bins, the removed bins are added to
+    /// them; otherwise they are dropped.
+    pub fn slice(&self, axis: usize, range: Range<usize>) -> Result<Histogram<A, D>, Error> {
+        let nbins = self.edges.get(axis).map_or(0, |bins| bins.len());
+        if range.start >= range.end || range.end > nbins {
+            return Err(format_err!("Invalid range {:?} for axis {} with {} bins", range, axis, nbins));
+        }
+        let groups: Vec<_> = range.clone().map(|i| i..i + 1).collect();
+        let kind = match self.kinds[axis] {
+            AxisKind::Categories(ref lookup) => AxisKind::Categories(
+                lookup.iter()
+                    .filter(|&&(_, bin)| range.start <= bin && bin < range.end)
+                    .map(|&(category, bin)| (category, bin - range.start))
+                    .collect()),
+            ref kind => kind.clone(),
+        };
+        Ok(self.regroup(axis, self.edges[axis][range].to_vec(), kind, &groups))
+    }
+
+    /// Merge each `factor` neighboring bins along `axis`. Fails if the
+    /// number of bins is not a multiple of `factor` or if the axis is
+    /// categorical.
+    pub fn rebin(&self, axis: usize, factor: usize) -> Result<Histogram<A, D>, Error> {
+        let nbins = self.edges.get(axis).map_or(0, |bins| bins.len());
+        if factor == 0 || !nbins.is_multiple_of(factor) {
+            return Err(format_err!("Cannot merge {} bins of axis {} by {}", nbins, axis, factor));
+        }
+        if let AxisKind::Categories(_) = self.kinds[axis] {
+            return Err(format_err!("Cannot merge the bins of categorical axis {}", axis));
+        }
+        let groups: Vec<_> = (0..nbins / factor).map(|i| i * factor..(i + 1) * factor).collect();
+        let bins = groups.iter()
+            .map(|g| BinEdges {
+                lower: self.edges[axis][g.start].lower,
+                upper: self.edges[axis][g.end - 1].upper,
+            })
+            .collect();
+        let kind = self.kinds[axis].clone();
+        Ok(self.regroup(axis, bins, kind, &groups))
+    }
+
+    /// Merge the bins along `axis` such that the new bins have the
+    /// given `edges`, each of which has to be an edge of this
+    /// histogram. Bins outside of the new range are added to the
+    /// under- and overflow bins if there are any. Fails for
+    /// categorical axes.
+    pub fn rebin_to_edges(&self, axis: usize, edges: &[f64]) -> Result<Histogram<A, D>, Error> {
+        let old_edges = self.edges.get(axis)
+            .map(|_| self.edges(axis))
+            .ok_or_else(|| format_err!("Invalid axis {}", axis))?;
+        if let AxisKind::Categories(_) = self.kinds[axis] {
+            return Err(format_err!("Cannot merge the bins of categorical axis {}", axis));
+        }
+        let idxs = edges.iter()
+            .map(|e| old_edges.iter().position(|old| (old - e).abs() <= 1e-9 * e.abs().max(1.0)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format_err!("New edges {:?} are not a subset of {:?}", edges, old_edges))?;
+        if idxs.len() < 2 || idxs.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(format_err!("New edges {:?} are not increasing", edges));
+        }
+        let groups: Vec<_> = idxs.windows(2).map(|w| w[0]..w[1]).collect();
+        Ok(self.regroup(axis, edges_to_bins(edges), AxisKind::Variable, &groups))
+    }
+
+    /// A new histogram whose bins along `axis` are the sums over the
+    /// given groups of the current regular bins
+    fn regroup(&self, axis: usize, bins: Vec<BinEdges>, kind: AxisKind, groups: &[Range<usize>])
+               -> Histogram<A, D> {
+        let groups = if self.flow[axis] {
+            // Indices into `counts` are shifted by the underflow bin
+            let n = self.edges[axis].len();
+            let first = groups[0].start;
+            let last = groups[groups.len() - 1].end;
+            ::std::iter::once(0..first + 1)
+                .chain(groups.iter().map(|g| g.start + 1..g.end + 1))
+                .chain(::std::iter::once(last + 1..n + 2))
+                .collect()
+        } else {
+            groups.to_vec()
+        };
+        let mut edges = self.edges.clone();
+        edges[axis] = bins;
+        let mut kinds = self.kinds.clone();
+        kinds[axis] = kind;
+        Histogram {
+            edges,
+            kinds,
+            labels: self.labels.clone(),
+            flow: self.flow.clone(),
+            counts: merge_along(&self.counts, axis, &groups),
+            sumw2: self.sumw2.as_ref().map(|sumw2| merge_along(sumw2, axis, &groups)),
+            entries: self.entries,
+            dim: PhantomData,
+        }
+    }
+}
+
+impl<A, D> Histogram<A, D>
+    where A: Clone + AddAssign
+{
+    /// The sums of squared weights of this histogram and `other`
+    /// added up; `None` unless either of them tracks them. The fills
+    /// of a histogram which does not are assumed to be unweighted.
+    fn take_summed_sumw2(&mut self, other: &Histogram<A, D>) -> Option<nd::ArrayD<A>> {
+        match (self.sumw2.take(), other.sumw2.as_ref()) {
+            (None, None) => None,
+            (Some(mut sumw2), other_sumw2) => {
+                sumw2 += other_sumw2.unwrap_or(&other.counts);
+                Some(sumw2)
+            },
+            (None, Some(other_sumw2)) => {
+                let mut sumw2 = self.counts.clone();
+                sumw2 += other_sumw2;
+                Some(sumw2)
+            },
+        }
+    }
+
+    /// Add the counts of `other` to this histogram. If either
+    /// histogram tracks the sum of squared weights, so does the
+    /// result; the fills of the other one are then assumed to be
+    /// unweighted. Fails if the binnings differ.
+    pub fn add(&mut self, other: &Histogram<A, D>) -> Result<(), Error> {
+        self.check_binning(other)?;
+        self.add_same_binning(other);
+        Ok(())
+    }
+
+    /// Add the counts of `other`, which is known to have the same
+    /// binning as this histogram, e.g. because it is an `empty_clone`
+    pub(crate) fn add_same_binning(&mut self, other: &Histogram<A, D>) {
+        self.sumw2 = self.take_summed_sumw2(other);
+        self.counts += &other.counts;
+        self.entries += other.entries;
+    }
+
+    /// Subtract the counts of `other` from this histogram. The sums
+    /// of squared weights are added up as in `add`. Fails if the
+    /// binnings differ.
+    pub fn sub(&mut self, other: &Histogram<A, D>) -> Result<(), Error>
+    where A: SubAssign,
+    {
+        self.check_binning(other)?;
+        self.sumw2 = self.take_summed_sumw2(other);
+        self.counts -= &other.counts;
+        self.entries += other.entries;
+        Ok(())
+    }
+}
+
+impl<A, D> Histogram<A, D>
+    where A: libnum::Float
+{
+    /// The variances of the bins of this histogram for propagating
+    /// errors if either it or `other` tracks the sum of squared
+    /// weights
+    fn take_variances(&mut self, other: &Histogram<A, D>) -> Option<nd::ArrayD<A>> {
+        if self.sumw2.is_none() && other.sumw2.is_none() {
+            return None;
+        }
+        Some(self.sumw2.take().unwrap_or_else(|| self.counts.clone()))
+    }
+
+    /// Multiply each bin by the corresponding bin of `other`. Errors
+    /// are propagated if either histogram tracks the sum of squared
+    /// weights. Fails if the binnings differ.
+    pub fn mul(&mut self, other: &Histogram<A, D>) -> Result<(), Error> {
+        self.check_binning(other)?;
+        if let Some(mut var) = self.take_variances(other) {
+            let other_var = other.sumw2.as_ref().unwrap_or(&other.counts);
+            nd::Zip::from(&mut var).and(&self.counts).and(&other.counts).and(other_var)
+                .apply(|v, &a, &b, &vb| *v = *v * b * b + a * a * vb);
+            self.sumw2 = Some(var);
+        }
+        nd::Zip::from(&mut self.counts).and(&other.counts).apply(|a, &b| *a = *a * b);
+        Ok(())
+    }
+
+    /// Divide each bin by the corresponding bin of `other`, assuming
+    /// both are uncorrelated; bins with a zero denominator are set to
+    /// zero. Errors are propagated if either histogram tracks the sum
+    /// of squared weights. Fails if the binnings differ.
+    pub fn div(&mut self, other: &Histogram<A, D>) -> Result<(), Error> {
+        self.check_binning(other)?;
+        if let Some(mut var) = self.take_variances(other) {
+            let other_var = other.sumw2.as_ref().unwrap_or(&other.counts);
+            nd::Zip::from(&mut var).and(&self.counts).and(&other.counts).and(other_var)
+                .apply(|v, &a, &b, &vb| {
+                    let b2 = b * b;
+                    *v = if b.is_zero() { A::zero() } else { (*v * b2 + a * a * vb) / (b2 * b2) };
+                });
+            self.sumw2 = Some(var);
+        }
+        nd::Zip::from(&mut self.counts).and(&other.counts)
+            .apply(|a, &b| *a = if b.is_zero() { A::zero() } else { *a / b });
+        Ok(())
+    }
+
+    /// Turn this histogram of the selected entries into the
+    /// efficiency of the selection by dividing it by the histogram of
+    /// `total` entries. The errors of the result are binomial, i.e.
+    /// they account for this histogram being a subset of `total`,
+    /// and are always tracked as the sum of squared weights. Bins
+    /// with no total entries are set to zero. Fails if the binnings
+    /// differ.
+    pub fn div_binomial(&mut self, total: &Histogram<A, D>) -> Result<(), Error> {
+        self.check_binning(total)?;
+        let mut var = self.sumw2.take().unwrap_or_else(|| self.counts.clone());
+        let total_var = total.sumw2.as_ref().unwrap_or(&total.counts);
+        let two = A::one() + A::one();
+        nd::Zip::from(&mut var).and(&self.counts).and(&total.counts).and(total_var)
+            .apply(|v, &k, &n, &vn| {
+                *v = if n.is_zero() {
+                    A::zero()
+                } else {
+                    let eff = k / n;
+                    ((A::one() - two * eff) * *v + eff * eff * vn).abs() / (n * n)
+                };
+            });
+        self.sumw2 = Some(var);
+        nd::Zip::from(&mut self.counts).and(&total.counts)
+            .apply(|k, &n| *k = if n.is_zero() { A::zero() } else { *k / n });
+        Ok(())
+    }
+
+    /// Multiply all bins by `factor`
+    pub fn scale(&mut self, factor: A) {
+        self.counts.mapv_inplace(|v| v * factor);
+        if let Some(ref mut sumw2) = self.sumw2 {
+            sumw2.mapv_inplace(|v| v * factor * factor);
+        }
+    }
+
+    /// Scale this histogram such that the sum of its regular bins is
+    /// one. Fails if that sum is zero.
+    pub fn normalize(&mut self) -> Result<(), Error> {
+        let integral = self.in_range().scalar_sum();
+        if integral.is_zero() {
+            return Err(format_err!("Cannot normalize a histogram without entries"));
+        }
+        self.scale(A::one() / integral);
+        Ok(())
+    }
+
+    /// Normalize this histogram and divide each regular bin by its
+    /// volume, i.e. the product of its widths along all axes, such
+    /// that it estimates a probability density. Under- and overflow
+    /// bins only hold the fraction of the entries outside of the
+    /// range. Fails if the sum of the regular bins is zero.
+    pub fn normalize_density(&mut self) -> Result<(), Error> {
+        self.normalize()?;
+        let widths: Vec<_> = (0..self.edges.len()).map(|axis| self.widths(axis)).collect();
+        let flow = self.flow.clone();
+        // Volume of the bin at `idx` into `counts`; `None` for flow bins
+        let volume = |idx: &IxDyn| -> Option<A> {
+            let mut volume = 1.0;
+            for (axis, widths) in widths.iter().enumerate() {
+                let i = match (flow[axis], idx[axis]) {
+                    (false, i) => i,
+                    (true, i) if i == 0 || i == widths.len() + 1 => return None,
+                    (true, i) => i - 1,
+                };
+                volume *= widths[i];
+            }
+            A::from(volume)
+        };
+        for (idx, v) in self.counts.indexed_iter_mut() {
+            if let Some(volume) = volume(&idx) {
+                *v = *v / volume;
+            }
+        }
+        if let Some(ref mut sumw2) = self.sumw2 {
+            for (idx, v) in sumw2.indexed_iter_mut() {
+                if let Some(volume) = volume(&idx) {
+                    *v = *v / (volume * volume);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A, D> Histogram<A, D>
+    where A: Clone + libnum::ToPrimitive
+{
+    /// The sum of the regular bins along `axis` over the regular bins
+    /// of all other axes
+    fn axis_sums(&self, axis: usize) -> Vec<f64> {
+        let mut sums = vec![0.0; self.edges[axis].len()];
+        for (idx, v) in self.in_range().indexed_iter() {
+            sums[idx[axis]] += v.to_f64().unwrap_or(f64::NAN);
+        }
+        sums
+    }
+
+    /// The mean along `axis` of the entries in the regular bins, each
+    /// of which is assumed to be at its bin center; `None` if there
+    /// are no such entries
+    pub fn mean(&self, axis: usize) -> Option<f64> {
+        let sums = self.axis_sums(axis);
+        let total: f64 = sums.iter().sum();
+        if total == 0.0 {
+            return None;
+        }
+        Some(sums.iter().zip(self.centers(axis)).map(|(w, x)| w * x).sum::<f64>() / total)
+    }
+
+    /// The standard deviation along `axis` of the entries in the
+    /// regular bins, each of which is assumed to be at its bin center;
+    /// `None` if there are no such entries
+    pub fn std_dev(&self, axis: usize) -> Option<f64> {
+        let mean = self.mean(axis)?;
+        let sums = self.axis_sums(axis);
+        let total: f64 = sums.iter().sum();
+        let var = sums.iter().zip(self.centers(axis))
+            .map(|(w, x)| w * (x - mean) * (x - mean))
+            .sum::<f64>() / total;
+        Some(var.sqrt())
+    }
+
+    /// The sum of the regular bins `bins` along `axis` over the
+    /// regular bins of all other axes. Fails if `axis` does not exist
+    /// or `bins` is not within its bins.
+    pub fn integral(&self, axis: usize, bins: Range<usize>) -> Result<f64, Error> {
+        let nbins = self.edges.get(axis).map_or(0, |bins| bins.len());
+        if bins.start > bins.end || bins.end > nbins {
+            return Err(format_err!("Invalid range {:?} for axis {} with {} bins", bins, axis, nbins));
+        }
+        Ok(self.axis_sums(axis)[bins].iter().sum())
+    }
+
+    /// The fraction of the entries in the regular bins which are below
+    /// the upper edge of each bin along `axis`; `None` if there are no
+    /// such entries
+    pub fn cumulative(&self, axis: usize) -> Option<Vec<f64>> {
+        let sums = self.axis_sums(axis);
+        let total: f64 = sums.iter().sum();
+        if total == 0.0 {
+            return None;
+        }
+        Some(sums.iter()
+             .scan(0.0, |cum, w| {
+                 *cum += w;
+                 Some(*cum / total)
+             })
+             .collect())
+    }
+
+    /// The value along `axis` below which the fraction `q` of the
+    /// entries in the regular bins lie, interpolating linearly within
+    /// the bins; e.g. the median for `q = 0.5`. `None` if there are no
+    /// entries or if `q` is not between 0 and 1.
+    pub fn quantile(&self, axis: usize, q: f64) -> Option<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let cumulative = self.cumulative(axis)?;
+        let bins = &self.edges[axis];
+        // First bin whose upper edge is above the quantile, skipping
+        // empty bins at the start
+        let i = cumulative.iter().position(|&c| c >= q && c > 0.0)?;
+        let below = if i == 0 { 0.0 } else { cumulative[i - 1] };
+        let frac = if q <= below { 0.0 } else { (q - below) / (cumulative[i] - below) };
+        Some(bins[i].lower + frac * bins[i].width())
+    }
+}
+
 macro_rules! impl_histogram {
     ($N:expr, $($idx:expr)*) => {
+        impl HistDim for [usize; $N] {
+            fn ndim() -> Option<usize> {
+                Some($N)
+            }
+        }
+
         impl<A> Histogram<A, [usize; $N]>
             where A: Copy + libnum::Zero + Add + AddAssign + libnum::One + PartialOrd
         {
-            /// Find the bin index containing `value` on `axis`
-            /// Return None if the the value is not in range
-            pub fn find_bin_index_axis(&self, axis: usize, value: f64) -> Option<usize>{
-                let (edges1d, value) = (&self.edges[axis], value);
-                edges1d
-                    .binary_search_by(|bin| bin.cmp_with(value))
-                    .ok()
+            /// Indices into `counts` of the given regular bins
+            fn count_indices(&self, mut idxs: [usize; $N]) -> [usize; $N] {
+                for dim in 0..$N {
+                    idxs[dim] += self.flow[dim] as usize;
+                }
+                idxs
             }
 
-            /// Find indices of bins along each axis
+            /// Find indices into `counts` along each axis
             fn find_bin_indices(&self, values: &[f64; $N]) -> Option<[usize; $N]> {
                 let mut idxs = [0; $N];
-                for dim in 0..$N {
-                    match self.find_bin_index_axis(dim, values[dim]) {
-                        Some(idx) => idxs[dim] = idx,
-                        None => return None,
-                    }
+                if self.find_count_indices(values, &mut idxs) {
+                    Some(idxs)
+                } else {
+                    None
                 }
-                Some(idxs)
             }
 
             pub fn fill(&mut self, values: &[f64; $N])
             {
+                self.entries += 1;
+                if let Some(idxs) = self.find_bin_indices(values) {
+                    self.increment(idxs.as_ref(), A::one(), A::one());
+                }
+            }
+
+            /// Add `weight` to the bin containing `values`. The sum of
+            /// the squared weights is tracked from the first weighted
+            /// fill on, as in ROOT
+            pub fn fill_weighted(&mut self, values: &[f64; $N], weight: A)
+                where A: Mul<Output=A>
+            {
+                self.enable_sumw2();
+                self.entries += 1;
                 if let Some(idxs) = self.find_bin_indices(values) {
-                    self.counts[idxs.as_ref()] += A::one();
+                    self.increment(idxs.as_ref(), weight, weight * weight);
                 }
             }
+
+            /// Fill each of the given values with its weight
+            pub fn extend_weighted<T>(&mut self, values: T)
+                where T: IntoIterator<Item=([f64; $N], A)>,
+                      A: Mul<Output=A>
+            {
+                for (v, weight) in values {
+                    self.fill_weighted(&v, weight);
+                }
+            }
+
+            /// Increment the regular bin at `indices`
             pub fn fill_by_index<I>(&mut self, indices: [usize; $N])
             {
-                self.counts[indices.as_ref()] += A::one();
+                self.entries += 1;
+                let idxs = self.count_indices(indices);
+                self.increment(idxs.as_ref(), A::one(), A::one());
             }
             pub fn fill_by_index_bulk<T>(&mut self, indices_slice: T)
                 where T: IntoIterator<Item=[usize; $N]>
             {
                 for idxs in indices_slice {
-                    self.counts[idxs.as_ref()] += A::one();
+                    self.entries += 1;
+                    let idxs = self.count_indices(idxs);
+                    self.increment(idxs.as_ref(), A::one(), A::one());
                 }
             }
             pub fn fill_bulk<T>(&mut self, values: T, npairs: usize)
                  where T: IntoIterator<Item=[f64; $N]>
             {
                 let mut indices = Vec::<[usize; $N]>::with_capacity(npairs);
+                let mut entries = 0;
                 indices.extend(values
                                .into_iter()
+                               .inspect(|_| entries += 1)
                                .filter_map(|v| self.find_bin_indices(&v)));
+                self.entries += entries;
                 for idxs in indices {
-                    self.counts[idxs.as_ref()] += A::one();
+                    self.increment(idxs.as_ref(), A::one(), A::one());
                 }
             }
-
-            pub fn add(&mut self, other: &Histogram<A, [usize; $N]>) {
-                // assert_eq!(self.edges.as_slice(), other.edges.as_slice());
-                self.counts += &other.counts;
-            }
-
-            /// Dump histogram (without edges) to a file of `name`.
-            /// The binary layout is:
-            /// `(array_version: u8, ndim: u64, shape: [ndim; u64], a_size: u64, a: [a_size; A])`
-            pub fn dump_to_file(&self, name: &str) -> Result<(), Error>
-            where A: Serialize,
-            {
-                let buf = serialize(&self.counts)?;
-                let mut f = File::create(name)?;
-                f.write_all(buf.as_slice())?;
-                Ok(())
-            }
-
         }
 
         impl<A> Extend<[f64; $N]> for Histogram<A, [usize; $N]>
@@ -130,11 +1013,14 @@ macro_rules! impl_histogram {
             fn extend<T>(&mut self, values: T)
                 where T: IntoIterator<Item=[f64; $N]>
             {
+                let mut entries = 0;
                 let indices: Vec<_> = values.into_iter()
+                    .inspect(|_| entries += 1)
                     .filter_map(|v| self.find_bin_indices(&v))
                     .collect();
+                self.entries += entries;
                 for idxs in indices {
-                    self.counts[idxs.as_ref()] += A::one();
+                    self.increment(idxs.as_ref(), A::one(), A::one());
                 }
             }
         }
@@ -150,73 +1036,222 @@ impl_histogram!(6, 0 1 2 3 4 5);
 impl_histogram!(7, 0 1 2 3 4 5 6);
 impl_histogram!(8, 0 1 2 3 4 5 6 7);
 
+/// Histograms whose number of axes is only known at runtime, e.g.
+/// because the axes are read from a configuration file. The methods
+/// panic if the number of values or indices does not match the
+/// number of axes.
+impl<A> Histogram<A, IxDyn>
+    where A: Copy + libnum::Zero + AddAssign + libnum::One
+{
+    /// Find indices into `counts` along each axis
+    fn find_bin_indices(&self, values: &[f64]) -> Option<Vec<usize>> {
+        assert_eq!(values.len(), self.ndim(), "Number of values does not match the number of axes");
+        let mut idxs = vec![0; values.len()];
+        if self.find_count_indices(values, &mut idxs) {
+            Some(idxs)
+        } else {
+            None
+        }
+    }
+
+    /// Increment the bin containing `values`
+    pub fn fill(&mut self, values: &[f64]) {
+        self.entries += 1;
+        if let Some(idxs) = self.find_bin_indices(values) {
+            self.increment(&idxs, A::one(), A::one());
+        }
+    }
+
+    /// Add `weight` to the bin containing `values`. The sum of the
+    /// squared weights is tracked from the first weighted fill on, as
+    /// in ROOT
+    pub fn fill_weighted(&mut self, values: &[f64], weight: A)
+        where A: Mul<Output=A>
+    {
+        self.enable_sumw2();
+        self.entries += 1;
+        if let Some(idxs) = self.find_bin_indices(values) {
+            self.increment(&idxs, weight, weight * weight);
+        }
+    }
+
+    /// Increment the regular bin at `indices`
+    pub fn fill_by_index(&mut self, indices: &[usize]) {
+        assert_eq!(indices.len(), self.ndim(), "Number of indices does not match the number of axes");
+        self.entries += 1;
+        let idxs: Vec<_> = indices.iter().zip(&self.flow)
+            .map(|(idx, &flow)| idx + flow as usize)
+            .collect();
+        self.increment(&idxs, A::one(), A::one());
+    }
+}
+
 #[derive(Default)]
 pub struct HistogramBuilder<D> {
     edges: Vec<Vec<f64>>,
+    kinds: Vec<AxisKind>,
+    labels: Vec<String>,
+    flow: Vec<bool>,
     phantom: PhantomData<D>,
 }
 
-macro_rules! impl_histogram_builder {
-    ($N:expr, $($idx:expr)*) => {
-        impl HistogramBuilder<[usize; $N]>
-        {
-            pub fn new() -> HistogramBuilder<[usize; $N]> {
-                HistogramBuilder {
-                    edges: Vec::new(),
-                    phantom: PhantomData,
-                }
-            }
-            /// Create a new n-dimensional histogram
-            pub fn build<A>(&self) -> Option<Histogram<A, [usize; $N]>>
-                where A: Clone + libnum::Num
-            {
-                let edges: Vec<Vec<BinEdges>> = self.edges
-                    .iter()
-                    .map(|edges1d| edges_to_bins(edges1d))
-                    .collect();
-                if edges.len() != $N {
-                    return None;
-                }
-                let mut shape = [0; $N];
-                for dim in 0..$N {
-                    shape[dim] = edges[dim].len();
-                }
+impl<D> HistogramBuilder<D>
+{
+    pub fn new() -> HistogramBuilder<D> {
+        HistogramBuilder {
+            edges: Vec::new(),
+            kinds: Vec::new(),
+            labels: Vec::new(),
+            flow: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+    /// Create a new histogram. Returns `None` if the number of axes
+    /// does not match the dimension `D` or is zero, if an axis has no
+    /// bins or edges which are not increasing, or if a category
+    /// appears twice.
+    pub fn build<A>(&self) -> Option<Histogram<A, D>>
+        where A: Clone + libnum::Num,
+              D: HistDim,
+    {
+        let edges: Vec<Vec<BinEdges>> = self.edges
+            .iter()
+            .map(|edges1d| edges_to_bins(edges1d))
+            .collect();
+        if edges.is_empty() || D::ndim().is_some_and(|n| n != edges.len()) {
+            return None;
+        }
+        let increasing = |bins: &Vec<BinEdges>| {
+            !bins.is_empty()
+                && bins.iter().all(|b| b.lower.partial_cmp(&b.upper) == Some(Ordering::Less))
+        };
+        if !edges.iter().all(increasing) {
+            return None;
+        }
+        let duplicate_categories = self.kinds.iter().any(|kind| match *kind {
+            AxisKind::Categories(ref lookup) => lookup.windows(2).any(|w| w[0].0 == w[1].0),
+            _ => false,
+        });
+        if duplicate_categories {
+            return None;
+        }
+        let shape: Vec<_> = edges.iter().zip(&self.flow)
+            .map(|(bins, &flow)| bins.len() + 2 * flow as usize)
+            .collect();
 
-                let counts = nd::ArrayD::<A>::zeros(IxDyn(shape.as_ref()));
-                Some(Histogram::<A, [usize; $N]> {
-                    counts: counts,
-                    edges: edges,
-                    dim: PhantomData,
-                })
-            }
-            pub fn add_equal_width_axis(&mut self, nbins: usize, min: f64, max: f64)
-                                        -> &mut HistogramBuilder<[usize; $N]> {
-                let width = (max - min) / nbins as f64;
-                self.edges.push(
-                    (0..nbins + 1)
-                        .map(|i| min + width * i as f64)
-                        .collect::<Vec<f64>>());
-                self
-            }
-            pub fn add_variable_width_axis<'a>(&'a mut self, edges1d: &[f64])
-                                               -> &'a mut HistogramBuilder<[usize; $N]> {
-                self.edges.push(edges1d.to_vec());
-                self
-            }
+        let counts = nd::ArrayD::<A>::zeros(IxDyn(&shape));
+        Some(Histogram::<A, D> {
+            counts,
+            sumw2: None,
+            edges,
+            kinds: self.kinds.clone(),
+            labels: self.labels.clone(),
+            flow: self.flow.clone(),
+            entries: 0,
+            dim: PhantomData,
+        })
+    }
+    pub fn add_equal_width_axis(&mut self, nbins: usize, min: f64, max: f64)
+                                -> &mut HistogramBuilder<D> {
+        let width = (max - min) / nbins as f64;
+        self.edges.push(
+            (0..nbins + 1)
+                .map(|i| min + width * i as f64)
+                .collect::<Vec<f64>>());
+        self.kinds.push(AxisKind::Uniform);
+        self.labels.push(String::new());
+        self.flow.push(false);
+        self
+    }
+    pub fn add_variable_width_axis<'a>(&'a mut self, edges1d: &[f64])
+                                       -> &'a mut HistogramBuilder<D> {
+        self.edges.push(edges1d.to_vec());
+        self.kinds.push(AxisKind::Variable);
+        self.labels.push(String::new());
+        self.flow.push(false);
+        self
+    }
+    /// Add an axis of `nbins` bins between `min` and `max`
+    /// whose edges are equally spaced on a logarithmic scale,
+    /// e.g. for transverse momentum spectra. `min` has to be
+    /// positive.
+    pub fn add_log_axis(&mut self, nbins: usize, min: f64, max: f64)
+                        -> &mut HistogramBuilder<D> {
+        let ratio = (max / min).powf(1.0 / nbins as f64);
+        self.edges.push(
+            (0..nbins + 1)
+                .map(|i| if i == nbins { max } else { min * ratio.powi(i as i32) })
+                .collect::<Vec<f64>>());
+        self.kinds.push(AxisKind::Log);
+        self.labels.push(String::new());
+        self.flow.push(false);
+        self
+    }
+    /// Add an axis with one bin for each integer from `min` to
+    /// `max` (inclusive); bin edges are half way between
+    /// integers
+    pub fn add_integer_axis(&mut self, min: i64, max: i64)
+                            -> &mut HistogramBuilder<D> {
+        self.edges.push(
+            (min..max + 2)
+                .map(|i| i as f64 - 0.5)
+                .collect::<Vec<f64>>());
+        self.kinds.push(AxisKind::Uniform);
+        self.labels.push(String::new());
+        self.flow.push(false);
+        self
+    }
+    /// Add an axis with one bin for each of the given
+    /// `categories`, e.g. run numbers or trigger classes. The
+    /// bins are in the given order, and bin `i` spans from
+    /// `i - 0.5` to `i + 0.5`. Values which are not one of the
+    /// categories are counted in the overflow bin if the axis
+    /// has flow bins and dropped otherwise.
+    pub fn add_category_axis(&mut self, categories: &[i64])
+                             -> &mut HistogramBuilder<D> {
+        self.edges.push(
+            (0..categories.len() + 1)
+                .map(|i| i as f64 - 0.5)
+                .collect::<Vec<f64>>());
+        let mut lookup: Vec<_> = categories.iter().cloned().zip(0..).collect();
+        lookup.sort();
+        self.kinds.push(AxisKind::Categories(lookup));
+        self.labels.push(String::new());
+        self.flow.push(false);
+        self
+    }
+    /// Count values outside of the range of the most recently
+    /// added axis in an under- and an overflow bin
+    pub fn with_flow_bins(&mut self) -> &mut HistogramBuilder<D> {
+        if let Some(flow) = self.flow.last_mut() {
+            *flow = true;
         }
+        self
+    }
+    /// Set the label of the most recently added axis
+    pub fn with_label(&mut self, label: &str) -> &mut HistogramBuilder<D> {
+        if let Some(l) = self.labels.last_mut() {
+            *l = label.to_string();
+        }
+        self
     }
 }
 
-impl_histogram_builder!(1, 0);
-impl_histogram_builder!(2, 0 1);
-impl_histogram_builder!(3, 0 1 2);
-impl_histogram_builder!(4, 0 1 2 3);
-impl_histogram_builder!(5, 0 1 2 3 4);
-impl_histogram_builder!(6, 0 1 2 3 4 5);
-impl_histogram_builder!(7, 0 1 2 3 4 5 6);
-impl_histogram_builder!(8, 0 1 2 3 4 5 6 7);
+/// How the bin containing a value is found along an axis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum AxisKind {
+    /// Arbitrary edges; the bin is found with a binary search
+    Variable,
+    /// Bins of equal width; the bin is computed directly
+    Uniform,
+    /// Bins of equal width on a logarithmic scale; the bin is
+    /// computed directly
+    Log,
+    /// Categories and their bins, sorted by category
+    Categories(Vec<(i64, usize)>),
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 struct BinEdges {
     lower: f64,
     upper: f64,
@@ -330,6 +1365,429 @@ mod tests {
         assert_eq!(h.counts, nd::arr2(&[[1., 0.], [0., 0.]]).into_dyn());
     }
 
+    #[test]
+    fn weighted_filling() {
+        let mut h = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        h.fill(&[0.5]);
+        h.enable_sumw2();
+        h.fill_weighted(&[0.5], 2.0);
+        h.extend_weighted(vec![([1.5], 0.5), ([5.0], 3.0)]);
+        assert_eq!(h.counts, nd::arr1(&[3., 0.5]).into_dyn());
+        assert_eq!(h.sumw2().unwrap(), &nd::arr1(&[5., 0.25]).into_dyn());
+        assert_eq!(h.errors(), nd::arr1(&[5f64.sqrt(), 0.5]).into_dyn());
+
+        // Weighted fills track the squared weights without enabling them first
+        let mut auto = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        auto.fill(&[0.5]);
+        assert!(auto.sumw2().is_none());
+        auto.fill_weighted(&[0.5], 2.0);
+        auto.extend_weighted(vec![([1.5], 0.5), ([5.0], 3.0)]);
+        assert_eq!(auto.sumw2(), h.sumw2());
+        assert_eq!(auto.errors(), nd::arr1(&[5f64.sqrt(), 0.5]).into_dyn());
+
+        // Errors of unweighted histograms follow from the counts
+        let mut other = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        other.fill(&[1.5]);
+        assert!(other.sumw2().is_none());
+        assert_eq!(other.errors(), nd::arr1(&[0., 1.]).into_dyn());
+        // Sums of squared weights survive adding
+        other.add(&h).unwrap();
+        assert_eq!(other.counts, nd::arr1(&[3., 1.5]).into_dyn());
+        assert_eq!(other.sumw2().unwrap(), &nd::arr1(&[5., 1.25]).into_dyn());
+        h.add(&other).unwrap();
+        assert_eq!(h.sumw2().unwrap(), &nd::arr1(&[10., 1.5]).into_dyn());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let build = || HistogramBuilder::<[usize; 1]>::new()
+            .add_variable_width_axis(&[0., 1., 3.])
+            .build::<f64>()
+            .unwrap();
+        let mut a = build();
+        a.extend(vec![[0.5], [0.5], [2.]]);
+        let mut b = build();
+        b.extend(vec![[0.5], [0.5], [0.5], [0.5], [2.], [2.]]);
+        let other_binning = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(2, 0., 3.)
+            .build::<f64>()
+            .unwrap();
+        assert!(a.add(&other_binning).is_err());
+        assert!(a.div(&other_binning).is_err());
+        let with_flow = HistogramBuilder::<[usize; 1]>::new()
+            .add_variable_width_axis(&[0., 1., 3.])
+            .with_flow_bins()
+            .build::<f64>()
+            .unwrap();
+        assert!(a.sub(&with_flow).is_err());
+
+        // Errors are only propagated if sumw2 is tracked
+        let mut diff = b.clone();
+        diff.sub(&a).unwrap();
+        assert_eq!(diff.counts, nd::arr1(&[2., 1.]).into_dyn());
+        assert!(diff.sumw2().is_none());
+        diff.enable_sumw2();
+        diff.sub(&a).unwrap();
+        assert_eq!(diff.counts, nd::arr1(&[0., 0.]).into_dyn());
+        assert_eq!(diff.sumw2().unwrap(), &nd::arr1(&[4., 2.]).into_dyn());
+
+        let mut prod = a.clone();
+        prod.enable_sumw2();
+        prod.mul(&b).unwrap();
+        assert_eq!(prod.counts, nd::arr1(&[8., 2.]).into_dyn());
+        // 4^2 * 2 + 2^2 * 4 and 2^2 * 1 + 1^2 * 2
+        assert_eq!(prod.sumw2().unwrap(), &nd::arr1(&[48., 6.]).into_dyn());
+
+        let mut ratio = a.clone();
+        ratio.enable_sumw2();
+        ratio.div(&b).unwrap();
+        assert_eq!(ratio.counts, nd::arr1(&[0.5, 0.5]).into_dyn());
+        // (2 * 16 + 4 * 4) / 4^4 and (1 * 4 + 1 * 2) / 2^4
+        assert_eq!(ratio.sumw2().unwrap(), &nd::arr1(&[48. / 256., 6. / 16.]).into_dyn());
+        let mut ratio = a.clone();
+        ratio.div(&build()).unwrap();
+        assert_eq!(ratio.counts, nd::arr1(&[0., 0.]).into_dyn());
+
+        // Binomial errors of an efficiency: eff * (1 - eff) / total
+        let mut eff = a.clone();
+        eff.div_binomial(&b).unwrap();
+        assert_eq!(eff.counts, nd::arr1(&[0.5, 0.5]).into_dyn());
+        assert_eq!(eff.errors(), nd::arr1(&[0.0625, 0.125]).mapv(f64::sqrt).into_dyn());
+
+        let mut scaled = a.clone();
+        scaled.enable_sumw2();
+        scaled.scale(2.);
+        assert_eq!(scaled.counts, nd::arr1(&[4., 2.]).into_dyn());
+        assert_eq!(scaled.sumw2().unwrap(), &nd::arr1(&[8., 4.]).into_dyn());
+        scaled.normalize().unwrap();
+        assert_eq!(scaled.counts, nd::arr1(&[2. / 3., 1. / 3.]).into_dyn());
+        assert!(build().normalize().is_err());
+    }
+
+    #[test]
+    fn density() {
+        let mut h = HistogramBuilder::<[usize; 2]>::new()
+            .add_variable_width_axis(&[0., 1., 3.])
+            .with_flow_bins()
+            .add_equal_width_axis(1, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        h.extend(vec![[0.5, 1.], [2., 1.], [2., 1.], [5., 1.]]);
+        h.normalize_density().unwrap();
+        // Only the regular bins are normalized; the overflow keeps its
+        // fraction of the entries
+        assert_eq!(h.in_range(), nd::arr2(&[[1. / 6.], [1. / 6.]]).into_dyn());
+        assert_eq!(h.overflow(0).unwrap(), nd::arr1(&[1. / 3.]).into_dyn());
+        let integral: f64 = h.in_range().iter()
+            .zip(&[2., 4.])
+            .map(|(density, volume)| density * volume)
+            .sum();
+        assert!((integral - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn axis_kinds() {
+        let mut h = HistogramBuilder::<[usize; 3]>::new()
+            .add_log_axis(3, 0.1, 100.)
+            .with_flow_bins()
+            .add_integer_axis(-1, 1)
+            .add_category_axis(&[245064, 244918, 246087])
+            .with_flow_bins()
+            .build::<f64>()
+            .unwrap();
+        assert_eq!(h.edges(0).len(), 4);
+        assert!((h.edges(0)[1] - 1.).abs() < 1e-12 && (h.edges(0)[2] - 10.).abs() < 1e-12);
+        assert_eq!(h.edges(1), vec![-1.5, -0.5, 0.5, 1.5]);
+        assert_eq!(h.categories(2), Some(vec![245064, 244918, 246087]));
+        assert!(h.categories(1).is_none());
+
+        assert_eq!(h.find_bin_index_axis(0, 0.1), Some(0));
+        assert_eq!(h.find_bin_index_axis(0, 5.), Some(1));
+        assert_eq!(h.find_bin_index_axis(0, 100.), None);
+        assert_eq!(h.find_bin_index_axis(0, -1.), None);
+        assert_eq!(h.find_bin_index_axis(1, -1.), Some(0));
+        assert_eq!(h.find_bin_index_axis(1, 1.), Some(2));
+        assert_eq!(h.find_bin_index_axis(2, 244918.), Some(1));
+        assert_eq!(h.find_bin_index_axis(2, 244918.5), None);
+        assert_eq!(h.find_bin_index_axis(2, 1.), None);
+
+        h.fill(&[5., 0., 246087.]);
+        h.fill(&[0.01, 1., 245064.]);
+        // Unknown categories end up in the overflow bin
+        h.fill(&[500., 1., 1.]);
+        assert_eq!(h.counts[[2, 1, 3]], 1.);
+        assert_eq!(h.counts[[0, 2, 1]], 1.);
+        assert_eq!(h.counts[[4, 2, 4]], 1.);
+
+        // Slicing keeps the categories of the remaining bins
+        let sliced = h.slice(2, 1..3).unwrap();
+        assert_eq!(sliced.categories(2), Some(vec![244918, 246087]));
+        assert_eq!(sliced.find_bin_index_axis(2, 246087.), Some(1));
+        assert!(h.rebin(2, 3).is_err());
+        assert!(h.add(&sliced.slice(2, 0..1).unwrap()).is_err());
+
+        // Duplicate categories and decreasing edges are rejected
+        assert!(HistogramBuilder::<[usize; 1]>::new().add_category_axis(&[1, 1]).build::<f64>().is_none());
+        assert!(HistogramBuilder::<[usize; 1]>::new().add_log_axis(2, 0., 1.).build::<f64>().is_none());
+        assert!(HistogramBuilder::<[usize; 1]>::new().add_variable_width_axis(&[1., 0.]).build::<f64>().is_none());
+    }
+
+    #[test]
+    fn direct_index_computation() {
+        // The computed bins of equal width axes agree with a binary search
+        let h = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(7, -0.3, 1.1)
+            .add_log_axis(9, 0.15, 20.)
+            .build::<f32>()
+            .unwrap();
+        let mut value = 0.0;
+        for i in 0..10000 {
+            value = (value * 7.31 + 0.123 * i as f64) % 25. - 1.;
+            for axis in 0..2 {
+                let expected = h.edges[axis].binary_search_by(|bin| bin.cmp_with(value)).ok();
+                assert_eq!(h.find_bin_index_axis(axis, value), expected);
+            }
+        }
+        for axis in 0..2 {
+            for edge in h.edges(axis) {
+                let expected = h.edges[axis].binary_search_by(|bin| bin.cmp_with(edge)).ok();
+                assert_eq!(h.find_bin_index_axis(axis, edge), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn save_and_merge() {
+        let dir = ::std::env::temp_dir();
+        let path = |name: &str| {
+            dir.join(format!("histogram-{}-{}.hist", ::std::process::id(), name))
+                .to_str().unwrap().to_string()
+        };
+        let (a, b, merged, bogus) = (path("a"), path("b"), path("merged"), path("bogus"));
+        let mut h = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .with_label("x")
+            .with_flow_bins()
+            .add_category_axis(&[7, 3])
+            .with_label("trigger")
+            .build::<f64>()
+            .unwrap();
+        h.fill(&[0.5, 3.]);
+        h.fill(&[5., 7.]);
+        h.fill(&[0.5, 1.]);
+        h.enable_sumw2();
+        h.fill_weighted(&[1.5, 7.], 2.);
+        assert_eq!(h.entries(), 4);
+        h.save_to_file(&a).unwrap();
+        h.save_to_file(&b).unwrap();
+
+        let loaded = Histogram::<f64, [usize; 2]>::load_from_file(&a).unwrap();
+        assert_eq!(loaded.counts, h.counts);
+        assert_eq!(loaded.sumw2(), h.sumw2());
+        assert_eq!(loaded.edges(0), h.edges(0));
+        assert_eq!(loaded.categories(1), Some(vec![7, 3]));
+        assert_eq!((loaded.label(0), loaded.label(1)), ("x", "trigger"));
+        assert!(loaded.has_flow_bins(0));
+        assert_eq!(loaded.entries(), 4);
+        assert!(Histogram::<f32, [usize; 2]>::load_from_file(&a).is_err());
+        assert!(Histogram::<f64, [usize; 1]>::load_from_file(&a).is_err());
+
+        merge_files(&[&a, &b], &merged).unwrap();
+        let sum = Histogram::<f64, [usize; 2]>::load_from_file(&merged).unwrap();
+        assert_eq!(sum.counts, h.counts.mapv(|c| 2. * c));
+        assert_eq!(sum.entries(), 8);
+
+        ::std::fs::write(&bogus, b"not a histogram").unwrap();
+        assert!(Histogram::<f64, IxDyn>::load_from_file(&bogus).is_err());
+        assert!(merge_files(&[&a, &bogus], &merged).is_err());
+        for name in &[a, b, merged, bogus] {
+            ::std::fs::remove_file(name).unwrap();
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let mut h = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(4, 0., 4.)
+            .with_flow_bins()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<u32>()
+            .unwrap();
+        assert_eq!(h.mean(0), None);
+        assert_eq!(h.cumulative(0), None);
+        assert_eq!(h.quantile(0, 0.5), None);
+        h.extend(vec![[1.5, 0.5], [1.5, 1.5], [2.5, 0.5], [3.5, 0.5]]);
+        // Out of range entries are ignored
+        h.fill(&[10., 0.5]);
+        assert_eq!(h.mean(0), Some(2.25));
+        assert_eq!(h.mean(1), Some(0.75));
+        let var = (2. * 0.75 * 0.75 + 0.25 * 0.25 + 1.25 * 1.25) / 4.;
+        assert!((h.std_dev(0).unwrap() - f64::sqrt(var)).abs() < 1e-12);
+        assert_eq!(h.integral(0, 1..3).unwrap(), 3.);
+        assert_eq!(h.integral(1, 0..2).unwrap(), 4.);
+        assert!(h.integral(0, 2..5).is_err());
+        assert!(h.integral(2, 0..1).is_err());
+        assert_eq!(h.cumulative(0), Some(vec![0., 0.5, 0.75, 1.]));
+        assert_eq!(h.quantile(0, 0.), Some(1.));
+        assert_eq!(h.quantile(0, 0.25), Some(1.5));
+        assert_eq!(h.quantile(0, 0.5), Some(2.));
+        assert_eq!(h.quantile(0, 0.875), Some(3.5));
+        assert_eq!(h.quantile(0, 1.), Some(4.));
+        assert_eq!(h.quantile(0, 1.5), None);
+    }
+
+    #[test]
+    fn runtime_dimension() {
+        let axes = vec![(2, 0., 2.), (3, 0., 3.), (1, 0., 1.)];
+        let mut builder = HistogramBuilder::<IxDyn>::new();
+        for &(nbins, min, max) in &axes {
+            builder.add_equal_width_axis(nbins, min, max);
+        }
+        let mut h = builder.with_flow_bins().build::<f64>().unwrap();
+        assert_eq!(h.ndim(), 3);
+        assert_eq!(h.counts.shape(), &[2, 3, 3]);
+        h.fill(&[0.5, 2.5, 0.5]);
+        h.fill(&[0.5, 2.5, 5.]);
+        h.fill(&[5., 0.5, 0.5]);
+        h.fill_weighted(&[1.5, 0.5, 0.5], 2.);
+        h.fill_by_index(&[1, 1, 0]);
+        assert_eq!(h.entries(), 5);
+        assert_eq!(h.counts[[0, 2, 1]], 1.);
+        assert_eq!(h.counts[[0, 2, 2]], 1.);
+        assert_eq!(h.counts[[1, 0, 1]], 2.);
+        assert_eq!(h.counts[[1, 1, 1]], 1.);
+        assert_eq!(h.in_range().scalar_sum(), 4.);
+
+        // Projections into a fixed number of axes and back
+        let p = h.project::<[usize; 1]>(&[1]).unwrap();
+        assert_eq!(p.counts, nd::arr1(&[2., 1., 2.]).into_dyn());
+        let p = h.project::<IxDyn>(&[0, 1]).unwrap();
+        assert_eq!(p.ndim(), 2);
+        assert!(HistogramBuilder::<IxDyn>::new().build::<f64>().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn runtime_dimension_mismatch() {
+        let mut h = HistogramBuilder::<IxDyn>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        h.fill(&[0.5, 0.5]);
+    }
+
+    #[test]
+    fn flow_bins() {
+        let mut h = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .with_flow_bins()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        assert!(h.has_flow_bins(0) && !h.has_flow_bins(1));
+        assert_eq!(h.counts.shape(), &[4, 2]);
+        h.fill(&[-5., 0.5]);
+        h.fill(&[5., 1.5]);
+        h.fill(&[5., 1.5]);
+        // Out of range along an axis without flow bins
+        h.fill(&[0.5, 5.]);
+        h.fill(&[f64::NAN, 0.5]);
+        h.fill(&[1.5, 0.5]);
+        h.fill_by_index::<()>([0, 1]);
+        assert_eq!(h.underflow(0).unwrap(), nd::arr1(&[1., 0.]).into_dyn());
+        assert_eq!(h.overflow(0).unwrap(), nd::arr1(&[0., 2.]).into_dyn());
+        assert!(h.underflow(1).is_none());
+        assert_eq!(h.in_range(), nd::arr2(&[[0., 1.], [1., 0.]]).into_dyn());
+
+        // Flow bins survive adding
+        let other = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .with_flow_bins()
+            .add_equal_width_axis(2, 0., 2.)
+            .build::<f64>()
+            .unwrap();
+        let mut sum = other;
+        sum.add(&h).unwrap();
+        assert_eq!(sum.overflow(0).unwrap(), nd::arr1(&[0., 2.]).into_dyn());
+    }
+
+    #[test]
+    fn projections() {
+        let mut h = HistogramBuilder::<[usize; 3]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .add_equal_width_axis(3, 0., 3.)
+            .with_flow_bins()
+            .add_equal_width_axis(4, 0., 4.)
+            .build::<f64>()
+            .unwrap();
+        h.enable_sumw2();
+        h.fill_weighted(&[0.5, 0.5, 3.5], 2.);
+        h.fill(&[1.5, -1., 0.5]);
+        h.fill(&[1.5, 2.5, 0.5]);
+
+        let p = h.project::<[usize; 1]>(&[0]).unwrap();
+        assert_eq!(p.counts, nd::arr1(&[2., 2.]).into_dyn());
+        assert_eq!(p.sumw2().unwrap(), &nd::arr1(&[4., 2.]).into_dyn());
+        assert_eq!(p.edges(0), h.edges(0));
+
+        // Axes are in the given order and keep their flow bins
+        let p = h.project::<[usize; 2]>(&[2, 1]).unwrap();
+        assert_eq!(p.edges(0), h.edges(2));
+        assert!(!p.has_flow_bins(0) && p.has_flow_bins(1));
+        assert_eq!(p.counts.shape(), &[4, 5]);
+        assert_eq!(p.counts[[0, 0]], 1.);
+        assert_eq!(p.counts[[3, 1]], 2.);
+
+        assert!(h.project::<[usize; 2]>(&[0]).is_err());
+        assert!(h.project::<[usize; 2]>(&[0, 0]).is_err());
+        assert!(h.project::<[usize; 1]>(&[3]).is_err());
+    }
+
+    #[test]
+    fn slicing_and_rebinning() {
+        let mut h = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(4, 0., 4.)
+            .with_flow_bins()
+            .build::<i32>()
+            .unwrap();
+        h.extend((0..8).map(|i| [f64::from(i) / 2. - 0.5]));
+        assert_eq!(h.counts, nd::arr1(&[1, 2, 2, 2, 1, 0]).into_dyn());
+
+        let s = h.slice(0, 1..3).unwrap();
+        assert_eq!(s.edges(0), &[1., 2., 3.]);
+        assert_eq!(s.counts, nd::arr1(&[3, 2, 2, 1]).into_dyn());
+        assert!(h.slice(0, 2..5).is_err());
+
+        let r = h.rebin(0, 2).unwrap();
+        assert_eq!(r.edges(0), &[0., 2., 4.]);
+        assert_eq!(r.counts, nd::arr1(&[1, 4, 3, 0]).into_dyn());
+        assert_eq!(r.centers(0), &[1., 3.]);
+        assert!(h.rebin(0, 3).is_err());
+
+        let r = h.rebin_to_edges(0, &[1., 2., 4.]).unwrap();
+        assert_eq!(r.widths(0), &[1., 2.]);
+        assert_eq!(r.counts, nd::arr1(&[3, 2, 3, 0]).into_dyn());
+        assert!(h.rebin_to_edges(0, &[0.5, 2.]).is_err());
+        assert!(h.rebin_to_edges(0, &[2., 1.]).is_err());
+
+        // Without flow bins, sliced off bins are dropped
+        let mut h = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(4, 0., 4.)
+            .build::<i32>()
+            .unwrap();
+        h.extend((0..8).map(|i| [f64::from(i) / 2.]));
+        assert_eq!(h.slice(0, 1..3).unwrap().counts, nd::arr1(&[2, 2]).into_dyn());
+    }
+
     #[test]
     fn edges_and_centers() {
         let h = HistogramBuilder::<[usize; 2]>::new()
@@ -343,6 +1801,24 @@ mod tests {
         assert_eq!(h.edges[0][1].upper, 1.0);
 
         assert_eq!(h.centers(0), &[-0.5, 0.5]);
+        assert_eq!(h.edges(1), &[-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn npz_export() {
+        let mut h = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .add_equal_width_axis(3, 0., 3.)
+            .build::<i32>()
+            .unwrap();
+        h.fill(&[1.5, 0.5]);
+        let mut buf = vec![];
+        h.write_npz(&mut buf).unwrap();
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("counts.npy"));
+        assert!(s.contains("'descr': '<i4', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(s.contains("edges_1.npy"));
+        assert!(s.contains("'descr': '<f8', 'fortran_order': False, 'shape': (4,), }"));
     }
 
     #[test]
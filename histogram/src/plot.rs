@@ -0,0 +1,619 @@
+//! Render histograms to SVG images without any external programs,
+//! e.g. to produce plots as artifacts of batch jobs:
+//! ```text
+//! let mut fig = Figure::new(2);
+//! fig.plot()
+//!     .set_title("Primary vertex position")
+//!     .set_x_label("z [cm]")
+//!     .bars(&z_vertex, "#d95f02")
+//!     .error_bars(&z_vertex, "black");
+//! fig.save_svg("z_vertex.svg")?;
+//! ```
+use std::fmt::{self, Write as FmtWrite};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use ndarray as nd;
+use failure::Error;
+use libnum::ToPrimitive;
+
+use Histogram;
+
+const MARGIN_LEFT: f64 = 70.;
+const MARGIN_RIGHT: f64 = 20.;
+const MARGIN_TOP: f64 = 30.;
+const MARGIN_BOTTOM: f64 = 50.;
+/// Additional space on the right of a plot with a heatmap
+const COLOR_BAR_WIDTH: f64 = 70.;
+const TICK_LENGTH: f64 = 5.;
+
+/// Anchors of the color map of heatmaps, from low to high values
+const COLOR_MAP: [(f64, f64, f64); 5] = [
+    (68., 1., 84.),
+    (59., 82., 139.),
+    (33., 145., 140.),
+    (94., 201., 98.),
+    (253., 231., 37.),
+];
+
+/// One or more plots arranged in a grid, which is written as an SVG
+/// image
+#[derive(Debug, Clone)]
+pub struct Figure {
+    plots: Vec<Plot>,
+    columns: usize,
+    panel_size: (f64, f64),
+}
+
+/// A single plot of a `Figure`
+#[derive(Debug, Clone, Default)]
+pub struct Plot {
+    title: String,
+    x_label: String,
+    y_label: String,
+    x_range: Option<(f64, f64)>,
+    y_range: Option<(f64, f64)>,
+    x_ticks: Option<Vec<(f64, String)>>,
+    log_y: bool,
+    items: Vec<Item>,
+}
+
+/// Something drawn in a plot; values are those of the regular bins
+#[derive(Debug, Clone)]
+enum Item {
+    Bars { edges: Vec<f64>, values: Vec<f64>, color: String },
+    Step { edges: Vec<f64>, values: Vec<f64>, color: String },
+    ErrorBars { centers: Vec<f64>, values: Vec<f64>, errors: Vec<f64>, color: String },
+    Heatmap { x_edges: Vec<f64>, y_edges: Vec<f64>, values: nd::Array2<f64> },
+}
+
+impl Figure {
+    /// A new figure with plots arranged in `columns` columns
+    pub fn new(columns: usize) -> Figure {
+        Figure {
+            plots: Vec::new(),
+            columns: columns.max(1),
+            panel_size: (480., 360.),
+        }
+    }
+
+    /// The size of each plot in pixels; 480x360 by default
+    pub fn set_panel_size(&mut self, width: f64, height: f64) -> &mut Figure {
+        self.panel_size = (width, height);
+        self
+    }
+
+    /// Add a new plot after the previous ones
+    pub fn plot(&mut self) -> &mut Plot {
+        self.plots.push(Plot::default());
+        self.plots.last_mut().unwrap()
+    }
+
+    /// This figure as an SVG document
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        self.write_svg(&mut svg).expect("Writing to a string cannot fail");
+        svg
+    }
+
+    /// Write this figure as an SVG image to the file `name`
+    pub fn save_svg(&self, name: &str) -> Result<(), Error> {
+        let mut f = BufWriter::new(File::create(name)?);
+        f.write_all(self.to_svg().as_bytes())?;
+        f.flush()?;
+        Ok(())
+    }
+
+    fn write_svg(&self, svg: &mut String) -> fmt::Result {
+        let (w, h) = self.panel_size;
+        let columns = self.columns.min(self.plots.len()).max(1);
+        let rows = self.plots.len().div_ceil(columns);
+        let (width, height) = (w * columns as f64, h * rows.max(1) as f64);
+        writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(svg,
+                 r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" font-size="12">"#,
+                 width, height)?;
+        writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height)?;
+        for (i, plot) in self.plots.iter().enumerate() {
+            let (x0, y0) = ((i % columns) as f64 * w, (i / columns) as f64 * h);
+            plot.write_svg(svg, i, x0, y0, w, h)?;
+        }
+        writeln!(svg, "</svg>")
+    }
+}
+
+impl Plot {
+    /// The title shown above the plot
+    pub fn set_title(&mut self, title: &str) -> &mut Plot {
+        self.title = title.to_owned();
+        self
+    }
+
+    /// The label of the horizontal axis
+    pub fn set_x_label(&mut self, label: &str) -> &mut Plot {
+        self.x_label = label.to_owned();
+        self
+    }
+
+    /// The label of the vertical axis
+    pub fn set_y_label(&mut self, label: &str) -> &mut Plot {
+        self.y_label = label.to_owned();
+        self
+    }
+
+    /// Show the horizontal axis from `min` to `max` instead of the
+    /// range of the plotted histograms
+    pub fn set_x_range(&mut self, min: f64, max: f64) -> &mut Plot {
+        self.x_range = Some((min, max));
+        self
+    }
+
+    /// Show the vertical axis from `min` to `max` instead of the
+    /// range of the plotted values
+    pub fn set_y_range(&mut self, min: f64, max: f64) -> &mut Plot {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    /// Label the horizontal axis at the given positions instead of
+    /// evenly spaced round numbers
+    pub fn set_x_ticks(&mut self, ticks: &[(f64, &str)]) -> &mut Plot {
+        self.x_ticks = Some(ticks.iter().map(|&(x, label)| (x, label.to_owned())).collect());
+        self
+    }
+
+    /// Use a logarithmic vertical axis. Bins with values of zero or
+    /// less are not drawn.
+    pub fn set_y_log(&mut self) -> &mut Plot {
+        self.log_y = true;
+        self
+    }
+
+    /// Draw each bin of `hist` as a filled bar
+    pub fn bars<A>(&mut self, hist: &Histogram<A, [usize; 1]>, color: &str) -> &mut Plot
+        where A: Clone + ToPrimitive
+    {
+        self.items.push(Item::Bars {
+            edges: hist.edges(0),
+            values: hist.in_range().iter().map(to_f64).collect(),
+            color: color.to_owned(),
+        });
+        self
+    }
+
+    /// Draw `hist` as the outline of its bins
+    pub fn step<A>(&mut self, hist: &Histogram<A, [usize; 1]>, color: &str) -> &mut Plot
+        where A: Clone + ToPrimitive
+    {
+        self.items.push(Item::Step {
+            edges: hist.edges(0),
+            values: hist.in_range().iter().map(to_f64).collect(),
+            color: color.to_owned(),
+        });
+        self
+    }
+
+    /// Draw the statistical error of each bin of `hist` as a vertical
+    /// bar around its value; see `Histogram::errors`
+    pub fn error_bars<A>(&mut self, hist: &Histogram<A, [usize; 1]>, color: &str) -> &mut Plot
+        where A: Clone + ToPrimitive
+    {
+        let offset = if hist.has_flow_bins(0) { 1 } else { 0 };
+        let values: Vec<f64> = hist.in_range().iter().map(to_f64).collect();
+        let errors = hist.errors().iter().skip(offset).take(values.len()).cloned().collect();
+        self.items.push(Item::ErrorBars {
+            centers: hist.edges[0].iter().map(|bin| bin.center()).collect(),
+            values,
+            errors,
+            color: color.to_owned(),
+        });
+        self
+    }
+
+    /// Draw the bins of the two dimensional `hist` as colored
+    /// rectangles with the first axis horizontal. Empty bins are not
+    /// drawn.
+    pub fn heatmap<A>(&mut self, hist: &Histogram<A, [usize; 2]>) -> &mut Plot
+        where A: Clone + ToPrimitive
+    {
+        let values = hist.in_range().map(to_f64)
+            .into_dimensionality::<nd::Ix2>()
+            .expect("Histogram has two axes");
+        self.items.push(Item::Heatmap {
+            x_edges: hist.edges(0),
+            y_edges: hist.edges(1),
+            values,
+        });
+        self
+    }
+
+    fn has_heatmap(&self) -> bool {
+        self.items.iter().any(|item| matches!(*item, Item::Heatmap { .. }))
+    }
+
+    /// The range of the horizontal axis
+    fn x_limits(&self) -> (f64, f64) {
+        if let Some(range) = self.x_range {
+            return expand(range);
+        }
+        let xs = self.items.iter().flat_map(|item| match *item {
+            Item::Bars { ref edges, .. } | Item::Step { ref edges, .. } => edges.iter(),
+            Item::ErrorBars { ref centers, .. } => centers.iter(),
+            Item::Heatmap { ref x_edges, .. } => x_edges.iter(),
+        });
+        expand(min_max(xs.cloned()).unwrap_or((0., 1.)))
+    }
+
+    /// The range of the vertical axis; in decades if it is logarithmic
+    fn y_limits(&self) -> (f64, f64) {
+        let mut ys = Vec::new();
+        for item in &self.items {
+            match *item {
+                Item::Bars { ref values, .. } | Item::Step { ref values, .. } => {
+                    ys.extend(values);
+                    ys.push(0.);
+                }
+                Item::ErrorBars { ref values, ref errors, .. } => {
+                    for (v, e) in values.iter().zip(errors) {
+                        ys.push(v + e);
+                        ys.push(if self.log_y && v - e <= 0. { *v } else { v - e });
+                    }
+                }
+                Item::Heatmap { ref y_edges, .. } => ys.extend(y_edges),
+            }
+        }
+        if self.log_y {
+            let (lo, hi) = self.y_range
+                .or_else(|| min_max(ys.into_iter().filter(|&y| y > 0.)))
+                .unwrap_or((1., 10.));
+            let (lo, hi) = (lo.log10().floor(), hi.log10().ceil());
+            (lo, if hi > lo { hi } else { lo + 1. })
+        } else {
+            self.y_range.map(expand).unwrap_or_else(|| {
+                let (lo, hi) = expand(min_max(ys.into_iter()).unwrap_or((0., 1.)));
+                if self.has_heatmap() { (lo, hi) } else { (lo, hi + 0.05 * (hi - lo)) }
+            })
+        }
+    }
+
+    fn write_svg(&self, svg: &mut String, id: usize, x0: f64, y0: f64, width: f64, height: f64)
+                 -> fmt::Result
+    {
+        let right = if self.has_heatmap() { MARGIN_RIGHT + COLOR_BAR_WIDTH } else { MARGIN_RIGHT };
+        let frame = Frame {
+            left: x0 + MARGIN_LEFT,
+            right: x0 + width - right,
+            top: y0 + MARGIN_TOP,
+            bottom: y0 + height - MARGIN_BOTTOM,
+            x: self.x_limits(),
+            y: self.y_limits(),
+            log_y: self.log_y,
+        };
+        writeln!(svg, "<g>")?;
+        writeln!(svg,
+                 r#"<clipPath id="clip-{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                 id, frame.left, frame.top, frame.right - frame.left, frame.bottom - frame.top)?;
+        writeln!(svg, r#"<g clip-path="url(#clip-{})">"#, id)?;
+        for item in &self.items {
+            item.write_svg(svg, &frame)?;
+        }
+        writeln!(svg, "</g>")?;
+        writeln!(svg,
+                 r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+                 frame.left, frame.top, frame.right - frame.left, frame.bottom - frame.top)?;
+        self.write_axes(svg, &frame)?;
+        for item in &self.items {
+            if let Item::Heatmap { ref values, .. } = *item {
+                write_color_bar(svg, &frame, values)?;
+            }
+        }
+        writeln!(svg,
+                 r#"<text x="{}" y="{}" text-anchor="middle" font-size="14">{}</text>"#,
+                 (frame.left + frame.right) / 2., y0 + MARGIN_TOP - 10., escape(&self.title))?;
+        writeln!(svg,
+                 r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                 (frame.left + frame.right) / 2., frame.bottom + 38., escape(&self.x_label))?;
+        let (x, y) = (x0 + 16., (frame.top + frame.bottom) / 2.);
+        writeln!(svg,
+                 r#"<text x="{0}" y="{1}" text-anchor="middle" transform="rotate(-90 {0} {1})">{2}</text>"#,
+                 x, y, escape(&self.y_label))?;
+        writeln!(svg, "</g>")
+    }
+
+    /// Draw the ticks and their labels on both axes
+    fn write_axes(&self, svg: &mut String, frame: &Frame) -> fmt::Result {
+        let x_ticks = match self.x_ticks {
+            Some(ref ticks) => ticks.clone(),
+            None => {
+                let (ticks, step) = linear_ticks(frame.x);
+                ticks.into_iter().map(|x| (x, format_tick(x, step))).collect()
+            }
+        };
+        for (x, label) in x_ticks {
+            if x < frame.x.0 || x > frame.x.1 {
+                continue;
+            }
+            let x = frame.x(x);
+            writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{0}" y2="{2}" stroke="black"/>"#,
+                     x, frame.bottom, frame.bottom - TICK_LENGTH)?;
+            writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                     x, frame.bottom + 16., escape(&label))?;
+        }
+        let y_ticks: Vec<_> = if self.log_y {
+            let (lo, hi) = (frame.y.0 as i32, frame.y.1 as i32);
+            (lo..hi + 1).map(|e| (10f64.powi(e), format_decade(e))).collect()
+        } else {
+            let (ticks, step) = linear_ticks(frame.y);
+            ticks.into_iter().map(|y| (y, format_tick(y, step))).collect()
+        };
+        for (y, label) in y_ticks {
+            let y = frame.y(y);
+            writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{2}" y2="{1}" stroke="black"/>"#,
+                     frame.left, y, frame.left + TICK_LENGTH)?;
+            writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                     frame.left - 4., y + 4., label)?;
+        }
+        Ok(())
+    }
+}
+
+impl Item {
+    fn write_svg(&self, svg: &mut String, frame: &Frame) -> fmt::Result {
+        match *self {
+            Item::Bars { ref edges, ref values, ref color } => {
+                let base = frame.baseline();
+                for (bin, &v) in edges.windows(2).zip(values) {
+                    if !v.is_finite() || v == 0. || (frame.log_y && v < 0.) {
+                        continue;
+                    }
+                    let (x, y) = (frame.x(bin[0]), frame.y(v));
+                    writeln!(svg,
+                             r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.8"/>"#,
+                             x, y.min(base), frame.x(bin[1]) - x, (base - y).abs(), escape(color))?;
+                }
+            }
+            Item::Step { ref edges, ref values, ref color } => {
+                let base = frame.baseline();
+                write!(svg, r#"<path d="M {} {}"#, frame.x(edges[0]), base)?;
+                for (&upper, &v) in edges[1..].iter().zip(values) {
+                    let y = if v.is_finite() { frame.y(v) } else { base };
+                    write!(svg, " V {} H {}", y, frame.x(upper))?;
+                }
+                writeln!(svg, r#" V {}" fill="none" stroke="{}"/>"#, base, escape(color))?;
+            }
+            Item::ErrorBars { ref centers, ref values, ref errors, ref color } => {
+                for ((&x, &v), &e) in centers.iter().zip(values).zip(errors) {
+                    if !v.is_finite() || !e.is_finite() || (frame.log_y && v <= 0.) {
+                        continue;
+                    }
+                    let x = frame.x(x);
+                    let (lo, hi) = (frame.y(v - e), frame.y(v + e));
+                    writeln!(svg,
+                             r#"<path d="M {0} {1} V {2} M {3} {1} H {4} M {3} {2} H {4}" stroke="{5}"/>"#,
+                             x, lo, hi, x - 2., x + 2., escape(color))?;
+                    writeln!(svg, r#"<circle cx="{}" cy="{}" r="1.5" fill="{}"/>"#,
+                             x, frame.y(v), escape(color))?;
+                }
+            }
+            Item::Heatmap { ref x_edges, ref y_edges, ref values } => {
+                let (lo, hi) = z_limits(values);
+                for ((i, j), &v) in values.indexed_iter() {
+                    if !v.is_finite() || v == 0. {
+                        continue;
+                    }
+                    let (x, y) = (frame.x(x_edges[i]), frame.y(y_edges[j + 1]));
+                    writeln!(svg,
+                             r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                             x, y, frame.x(x_edges[i + 1]) - x, frame.y(y_edges[j]) - y,
+                             color(if hi > lo { (v - lo) / (hi - lo) } else { 1. }))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The area of a plot in which values are drawn and the ranges of its
+/// axes
+struct Frame {
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    x: (f64, f64),
+    y: (f64, f64),
+    log_y: bool,
+}
+
+impl Frame {
+    /// The horizontal position of `x` in the image
+    fn x(&self, x: f64) -> f64 {
+        self.left + (x - self.x.0) / (self.x.1 - self.x.0) * (self.right - self.left)
+    }
+
+    /// The vertical position of `y` in the image; values of zero or
+    /// less are at the bottom of a logarithmic axis
+    fn y(&self, y: f64) -> f64 {
+        let y = if !self.log_y {
+            y
+        } else if y > 0. {
+            y.log10()
+        } else {
+            self.y.0
+        };
+        self.bottom - (y - self.y.0) / (self.y.1 - self.y.0) * (self.bottom - self.top)
+    }
+
+    /// The vertical position from which bars are drawn
+    fn baseline(&self) -> f64 {
+        if self.log_y {
+            self.bottom
+        } else {
+            self.y(0.0f64.max(self.y.0).min(self.y.1))
+        }
+    }
+}
+
+fn to_f64<A: ToPrimitive>(v: &A) -> f64 {
+    v.to_f64().unwrap_or(f64::NAN)
+}
+
+/// The smallest and largest finite value
+fn min_max<I: Iterator<Item = f64>>(values: I) -> Option<(f64, f64)> {
+    values
+        .filter(|v| v.is_finite())
+        .fold(None, |acc, v| match acc {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+        })
+}
+
+/// Widen an empty range such that it can be drawn
+fn expand((lo, hi): (f64, f64)) -> (f64, f64) {
+    if hi > lo { (lo, hi) } else { (lo - 0.5, hi + 0.5) }
+}
+
+/// The range of the non-empty bins of a heatmap
+fn z_limits(values: &nd::Array2<f64>) -> (f64, f64) {
+    min_max(values.iter().cloned().filter(|&v| v != 0.)).unwrap_or((0., 1.))
+}
+
+/// About five evenly spaced round numbers within `range` and their
+/// spacing. An empty range is widened first; there are no ticks if
+/// the range is inverted or not finite.
+fn linear_ticks(range: (f64, f64)) -> (Vec<f64>, f64) {
+    let (lo, hi) = expand(range);
+    let raw = (hi - lo) / 5.;
+    if !(raw > 0. && raw.is_finite()) {
+        return (Vec::new(), 1.);
+    }
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1., 2., 5., 10.].iter()
+        .map(|f| f * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10. * magnitude);
+    let (first, last) = ((lo / step).ceil() as i64, (hi / step).floor() as i64);
+    ((first..last + 1).map(|i| i as f64 * step).collect(), step)
+}
+
+/// Format `value` with as many decimals as the tick spacing `step`
+/// requires
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.) as usize;
+    let label = format!("{:.*}", decimals, value);
+    if label.starts_with('-') && label[1..].chars().all(|c| c == '0' || c == '.') {
+        label[1..].to_owned()
+    } else {
+        label
+    }
+}
+
+/// The label of the power of ten `exponent`
+fn format_decade(exponent: i32) -> String {
+    if (-3..=4).contains(&exponent) {
+        format!("{}", 10f64.powi(exponent))
+    } else {
+        format!("1e{}", exponent)
+    }
+}
+
+/// The color of a fraction `t` between 0 and 1 of the range of a
+/// heatmap
+fn color(t: f64) -> String {
+    let t = t.clamp(0., 1.) * (COLOR_MAP.len() - 1) as f64;
+    let i = (t as usize).min(COLOR_MAP.len() - 2);
+    let f = t - i as f64;
+    let (a, b) = (COLOR_MAP[i], COLOR_MAP[i + 1]);
+    format!("#{:02x}{:02x}{:02x}",
+            (a.0 + f * (b.0 - a.0)).round() as u8,
+            (a.1 + f * (b.1 - a.1)).round() as u8,
+            (a.2 + f * (b.2 - a.2)).round() as u8)
+}
+
+/// Draw the scale of a heatmap on the right of `frame`
+fn write_color_bar(svg: &mut String, frame: &Frame, values: &nd::Array2<f64>) -> fmt::Result {
+    let (lo, hi) = z_limits(values);
+    let x = frame.right + 15.;
+    let height = frame.bottom - frame.top;
+    for i in 0..50u32 {
+        let t = f64::from(i) / 50.;
+        writeln!(svg, r#"<rect x="{}" y="{}" width="15" height="{}" fill="{}"/>"#,
+                 x, frame.bottom - (t + 0.02) * height, 0.02 * height + 0.5, color(t))?;
+    }
+    writeln!(svg, r#"<rect x="{}" y="{}" width="15" height="{}" fill="none" stroke="black"/>"#,
+             x, frame.top, height)?;
+    let (ticks, step) = linear_ticks(expand((lo, hi)));
+    for z in ticks {
+        let y = frame.bottom - (z - lo) / (hi - lo) * height;
+        if hi <= lo || y < frame.top - 0.5 || y > frame.bottom + 0.5 {
+            continue;
+        }
+        writeln!(svg, r#"<text x="{}" y="{}">{}</text>"#,
+                 x + 19., y + 4., format_tick(z, step))?;
+    }
+    Ok(())
+}
+
+/// Escape the characters of `s` which have a meaning in XML
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use HistogramBuilder;
+
+    #[test]
+    fn render_svg() {
+        let mut h1 = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(4, 0., 4.)
+            .with_flow_bins()
+            .build::<f64>()
+            .unwrap();
+        h1.extend(vec![[0.5], [1.5], [1.5], [3.5], [-1.]]);
+        let mut h2 = HistogramBuilder::<[usize; 2]>::new()
+            .add_equal_width_axis(2, 0., 1.)
+            .add_equal_width_axis(3, 0., 3.)
+            .build::<u32>()
+            .unwrap();
+        h2.fill(&[0.5, 0.5]);
+
+        let mut fig = Figure::new(2);
+        fig.plot()
+            .set_title("a < b & c")
+            .set_y_log()
+            .bars(&h1, "#d95f02")
+            .error_bars(&h1, "black");
+        fig.plot().step(&h1, "blue");
+        fig.plot().heatmap(&h2);
+        let svg = fig.to_svg();
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(r#"width="960" height="720""#));
+        assert!(svg.contains("a &lt; b &amp; c"));
+        // One bar per non-empty bin; the underflow bin is not drawn
+        assert_eq!(svg.matches(r##"fill="#d95f02""##).count(), 3);
+        assert_eq!(svg.matches("<circle").count(), 3);
+        // The only filled bin of the heatmap has the largest value
+        assert!(svg.contains(r##"fill="#fde725"/>"##));
+    }
+
+    #[test]
+    fn ticks() {
+        assert_eq!(linear_ticks((0., 1.)), (vec![0., 0.2, 0.4, 0.6000000000000001, 0.8, 1.], 0.2));
+        assert_eq!(linear_ticks((-10., 10.)).0, vec![-10., -5., 0., 5., 10.]);
+        assert_eq!(linear_ticks((1., 1.)).0.len(), 5);
+        assert!(linear_ticks((2., 0.)).0.is_empty());
+        assert!(linear_ticks((0., f64::INFINITY)).0.is_empty());
+        assert!(linear_ticks((f64::NAN, f64::NAN)).0.is_empty());
+        assert_eq!(format_tick(0.6000000000000001, 0.2), "0.6");
+        assert_eq!(format_tick(-0.0001, 0.5), "0.0");
+        assert_eq!(format_decade(-2), "0.01");
+        assert_eq!(format_decade(6), "1e6");
+        assert_eq!(color(0.), "#440154");
+        assert_eq!(color(1.), "#fde725");
+    }
+}
@@ -0,0 +1,169 @@
+use ndarray as nd;
+use failure::Error;
+
+use {HistDim, Histogram, HistogramBuilder, IxDyn};
+
+/// The mean of a quantity `y` in each bin, like ROOT's `TProfile`;
+/// e.g. the mean transverse momentum versus the multiplicity. For each
+/// bin, the number of entries as well as the sum and the sum of
+/// squares of `y` are tracked.
+#[derive(Debug, Clone)]
+pub struct Profile<D> {
+    /// The number of entries in each bin; also holds the binning
+    hist: Histogram<f64, D>,
+    sum: nd::ArrayD<f64>,
+    sum_sq: nd::ArrayD<f64>,
+}
+
+impl<D> HistogramBuilder<D> {
+    /// Create a new profile histogram with the axes added so far.
+    /// Returns `None` under the same conditions as `build`.
+    pub fn build_profile(&self) -> Option<Profile<D>>
+        where D: HistDim
+    {
+        let hist = self.build::<f64>()?;
+        Some(Profile {
+            sum: hist.counts.clone(),
+            sum_sq: hist.counts.clone(),
+            hist,
+        })
+    }
+}
+
+impl<D> Profile<D> {
+    /// The histogram of the number of entries in each bin, which also
+    /// provides the bin edges and centers
+    pub fn histogram(&self) -> &Histogram<f64, D> {
+        &self.hist
+    }
+
+    /// The number of entries in each bin
+    pub fn counts(&self) -> &nd::ArrayD<f64> {
+        &self.hist.counts
+    }
+
+    /// The sum of `y` in each bin
+    pub fn sums(&self) -> &nd::ArrayD<f64> {
+        &self.sum
+    }
+
+    /// The sum of the squares of `y` in each bin
+    pub fn sums_of_squares(&self) -> &nd::ArrayD<f64> {
+        &self.sum_sq
+    }
+
+    /// The mean of `y` in each bin; zero for empty bins
+    pub fn means(&self) -> nd::ArrayD<f64> {
+        let mut means = self.sum.clone();
+        nd::Zip::from(&mut means).and(&self.hist.counts)
+            .apply(|m, &n| *m = if n > 0.0 { *m / n } else { 0.0 });
+        means
+    }
+
+    /// The standard deviation of `y` in each bin; zero for empty bins
+    pub fn std_devs(&self) -> nd::ArrayD<f64> {
+        let mut std_devs = self.means();
+        nd::Zip::from(&mut std_devs).and(&self.sum_sq).and(&self.hist.counts)
+            .apply(|s, &sum_sq, &n| {
+                *s = if n > 0.0 { (sum_sq / n - *s * *s).max(0.0).sqrt() } else { 0.0 };
+            });
+        std_devs
+    }
+
+    /// The error of the mean of `y` in each bin, i.e. the standard
+    /// deviation divided by the square root of the number of entries;
+    /// zero for empty bins
+    pub fn errors(&self) -> nd::ArrayD<f64> {
+        let mut errors = self.std_devs();
+        nd::Zip::from(&mut errors).and(&self.hist.counts)
+            .apply(|e, &n| if n > 0.0 { *e /= n.sqrt() });
+        errors
+    }
+
+    /// Add the entries of `other` to this profile. Fails if the
+    /// binnings differ.
+    pub fn add(&mut self, other: &Profile<D>) -> Result<(), Error> {
+        self.hist.add(&other.hist)?;
+        self.sum += &other.sum;
+        self.sum_sq += &other.sum_sq;
+        Ok(())
+    }
+
+    /// Add `y` to the bin at `idxs` into `counts`, if any
+    fn add_value(&mut self, idxs: Option<&[usize]>, y: f64) {
+        self.hist.entries += 1;
+        if let Some(idxs) = idxs {
+            self.hist.counts[idxs] += 1.0;
+            self.sum[idxs] += y;
+            self.sum_sq[idxs] += y * y;
+        }
+    }
+}
+
+macro_rules! impl_profile {
+    ($N:expr) => {
+        impl Profile<[usize; $N]> {
+            /// Add `y` to the bin containing `values`
+            pub fn fill(&mut self, values: &[f64; $N], y: f64) {
+                let idxs = self.hist.find_bin_indices(values);
+                self.add_value(idxs.as_ref().map(|idxs| &idxs[..]), y);
+            }
+        }
+    }
+}
+
+impl_profile!(1);
+impl_profile!(2);
+impl_profile!(3);
+impl_profile!(4);
+impl_profile!(5);
+impl_profile!(6);
+impl_profile!(7);
+impl_profile!(8);
+
+impl Profile<IxDyn> {
+    /// Add `y` to the bin containing `values`. Panics if the number of
+    /// `values` does not match the number of axes.
+    pub fn fill(&mut self, values: &[f64], y: f64) {
+        let idxs = self.hist.find_bin_indices(values);
+        self.add_value(idxs.as_ref().map(|idxs| &idxs[..]), y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_per_bin() {
+        let mut p = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .build_profile()
+            .unwrap();
+        p.fill(&[0.5], 1.);
+        p.fill(&[0.5], 3.);
+        p.fill(&[1.5], 2.);
+        p.fill(&[5.], 100.);
+        assert_eq!(p.histogram().entries(), 4);
+        assert_eq!(p.counts(), &nd::arr1(&[2., 1.]).into_dyn());
+        assert_eq!(p.sums(), &nd::arr1(&[4., 2.]).into_dyn());
+        assert_eq!(p.sums_of_squares(), &nd::arr1(&[10., 4.]).into_dyn());
+        assert_eq!(p.means(), nd::arr1(&[2., 2.]).into_dyn());
+        assert_eq!(p.std_devs(), nd::arr1(&[1., 0.]).into_dyn());
+        assert_eq!(p.errors(), nd::arr1(&[1. / 2f64.sqrt(), 0.]).into_dyn());
+
+        let mut sum = p.clone();
+        sum.add(&p).unwrap();
+        assert_eq!(sum.means(), p.means());
+        assert_eq!(sum.counts(), &nd::arr1(&[4., 2.]).into_dyn());
+
+        // Runtime number of axes
+        let mut p = HistogramBuilder::<IxDyn>::new()
+            .add_equal_width_axis(2, 0., 2.)
+            .add_equal_width_axis(1, 0., 1.)
+            .build_profile()
+            .unwrap();
+        p.fill(&[1.5, 0.5], 3.);
+        assert_eq!(p.means(), nd::arr2(&[[0.], [3.]]).into_dyn());
+    }
+}
@@ -0,0 +1,133 @@
+use std::ops::{AddAssign, Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use failure::Error;
+use libnum::Zero;
+
+use Histogram;
+
+/// A histogram which is filled from several threads. Each thread
+/// fills its own `LocalHistogram` obtained with `local`, which is
+/// added to the shared histogram when it is dropped. Hence, the
+/// threads only synchronize once per local histogram rather than on
+/// every fill. Cloning a `SharedHistogram` gives another handle to
+/// the same histogram.
+#[derive(Debug)]
+pub struct SharedHistogram<A, D> {
+    shared: Arc<Mutex<Histogram<A, D>>>,
+}
+
+impl<A, D> Clone for SharedHistogram<A, D> {
+    fn clone(&self) -> SharedHistogram<A, D> {
+        SharedHistogram { shared: self.shared.clone() }
+    }
+}
+
+/// A thread's copy of a `SharedHistogram`. It is filled like any
+/// other `Histogram` and added to the shared histogram when dropped.
+#[derive(Debug)]
+pub struct LocalHistogram<A, D>
+    where A: Clone + Zero + AddAssign
+{
+    hist: Histogram<A, D>,
+    shared: Arc<Mutex<Histogram<A, D>>>,
+}
+
+impl<A, D> SharedHistogram<A, D>
+    where A: Clone + Zero + AddAssign
+{
+    /// Share `hist` between threads
+    pub fn new(hist: Histogram<A, D>) -> SharedHistogram<A, D> {
+        SharedHistogram { shared: Arc::new(Mutex::new(hist)) }
+    }
+
+    /// An empty histogram with the same axes to be filled by the
+    /// current thread
+    pub fn local(&self) -> LocalHistogram<A, D> {
+        let hist = self.shared.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .empty_clone();
+        LocalHistogram { hist, shared: self.shared.clone() }
+    }
+
+    /// A copy of the current state, which includes the fills of all
+    /// local histograms dropped so far
+    pub fn snapshot(&self) -> Histogram<A, D>
+        where D: Clone
+    {
+        self.shared.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// The filled histogram. Fails if other handles or local
+    /// histograms are still alive.
+    pub fn into_inner(self) -> Result<Histogram<A, D>, Error> {
+        let mutex = Arc::try_unwrap(self.shared)
+            .map_err(|_| format_err!("Histogram is still shared"))?;
+        Ok(mutex.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+impl<A, D> Deref for LocalHistogram<A, D>
+    where A: Clone + Zero + AddAssign
+{
+    type Target = Histogram<A, D>;
+    fn deref(&self) -> &Histogram<A, D> {
+        &self.hist
+    }
+}
+
+impl<A, D> DerefMut for LocalHistogram<A, D>
+    where A: Clone + Zero + AddAssign
+{
+    fn deref_mut(&mut self) -> &mut Histogram<A, D> {
+        &mut self.hist
+    }
+}
+
+impl<A, D> Drop for LocalHistogram<A, D>
+    where A: Clone + Zero + AddAssign
+{
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // The local histogram is an empty clone of the shared one, so
+        // the binnings need not be checked
+        shared.add_same_binning(&self.hist);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+    use HistogramBuilder;
+
+    #[test]
+    fn fill_from_threads() {
+        let hist = HistogramBuilder::<[usize; 1]>::new()
+            .add_equal_width_axis(4, 0., 4.)
+            .build::<u64>()
+            .unwrap();
+        let shared = SharedHistogram::new(hist);
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let mut local = shared.local();
+                    for _ in 0..1000 {
+                        local.fill(&[f64::from(i) + 0.5]);
+                    }
+                    local.fill(&[10.]);
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(shared.snapshot().counts.scalar_sum(), 4000);
+        let hist = shared.into_inner().unwrap();
+        assert!(hist.counts.iter().all(|&c| c == 1000));
+        assert_eq!(hist.entries(), 4004);
+    }
+}
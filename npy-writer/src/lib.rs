@@ -0,0 +1,257 @@
+//! Writers for NumPy's `.npy` and `.npz` file formats. A `.npy` file
+//! holds a single array; a `.npz` file is a zip archive of `.npy`
+//! files. Both can be read in Python with `numpy.load`. The arrays
+//! are written in C (row-major) order and the archives are not
+//! compressed.
+
+extern crate byteorder;
+#[macro_use]
+extern crate failure;
+
+use std::io::Write;
+use byteorder::{LittleEndian, WriteBytesExt};
+use failure::Error;
+
+/// Types which can be elements of a NumPy array
+pub trait Element: Copy {
+    /// The NumPy type description of this type, e.g. `<f4`
+    fn descr() -> &'static str;
+    /// Write this value as little endian bytes
+    fn write_le(&self, w: &mut Vec<u8>);
+}
+
+macro_rules! impl_element {
+    ($t:ty, $descr:expr, $write:ident) => {
+        impl Element for $t {
+            fn descr() -> &'static str {
+                $descr
+            }
+            fn write_le(&self, w: &mut Vec<u8>) {
+                w.$write::<LittleEndian>(*self).unwrap();
+            }
+        }
+    };
+    ($t:ty, $descr:expr, $write:ident, single_byte) => {
+        impl Element for $t {
+            fn descr() -> &'static str {
+                $descr
+            }
+            fn write_le(&self, w: &mut Vec<u8>) {
+                w.$write(*self).unwrap();
+            }
+        }
+    };
+}
+
+impl_element!(i8, "|i1", write_i8, single_byte);
+impl_element!(u8, "|u1", write_u8, single_byte);
+impl_element!(i16, "<i2", write_i16);
+impl_element!(u16, "<u2", write_u16);
+impl_element!(i32, "<i4", write_i32);
+impl_element!(u32, "<u4", write_u32);
+impl_element!(i64, "<i8", write_i64);
+impl_element!(u64, "<u8", write_u64);
+impl_element!(f32, "<f4", write_f32);
+impl_element!(f64, "<f8", write_f64);
+
+impl Element for bool {
+    fn descr() -> &'static str {
+        "|b1"
+    }
+    fn write_le(&self, w: &mut Vec<u8>) {
+        w.push(*self as u8);
+    }
+}
+
+/// Serialize an array of the given `shape` as a `.npy` file
+pub fn npy_bytes<T: Element>(shape: &[usize], data: &[T]) -> Result<Vec<u8>, Error> {
+    if shape.iter().product::<usize>() != data.len() {
+        return Err(format_err!("Shape {:?} does not match {} elements", shape, data.len()));
+    }
+    let shape = match shape.len() {
+        1 => format!("({},)", shape[0]),
+        _ => format!("({})", shape.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+                             T::descr(), shape);
+    // Magic string, version and header length take 10 bytes; the
+    // data has to start at a multiple of 64 bytes
+    let padding = 63 - (10 + header.len()) % 64;
+    header.extend((0..padding).map(|_| ' '));
+    header.push('\n');
+
+    let mut buf = Vec::with_capacity(10 + header.len() + data.len() * 8);
+    buf.extend_from_slice(b"\x93NUMPY\x01\x00");
+    buf.write_u16::<LittleEndian>(header.len() as u16)?;
+    buf.extend_from_slice(header.as_bytes());
+    for v in data {
+        v.write_le(&mut buf);
+    }
+    Ok(buf)
+}
+
+/// Write an array of the given `shape` to `w` as a `.npy` file
+pub fn write_npy<W: Write, T: Element>(mut w: W, shape: &[usize], data: &[T]) -> Result<(), Error> {
+    w.write_all(&npy_bytes(shape, data)?)?;
+    Ok(())
+}
+
+/// CRC-32 checksum as used in zip archives
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(i as u32, |c, _| if c & 1 == 1 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 });
+    }
+    !data.iter().fold(!0u32, |c, &b| table[((c ^ u32::from(b)) & 0xFF) as usize] ^ (c >> 8))
+}
+
+/// A file which was added to a `.npz` archive
+struct NpzEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writer of a `.npz` archive; the name of each array is its key in
+/// the dictionary-like object returned by `numpy.load`
+pub struct NpzWriter<W: Write> {
+    w: W,
+    entries: Vec<NpzEntry>,
+    offset: u64,
+}
+
+impl<W: Write> NpzWriter<W> {
+    /// Create a new archive which is written to `w`
+    pub fn new(w: W) -> NpzWriter<W> {
+        NpzWriter { w, entries: vec![], offset: 0 }
+    }
+
+    /// Add the array `name` of the given `shape`
+    pub fn add_array<T: Element>(&mut self, name: &str, shape: &[usize], data: &[T])
+                                 -> Result<(), Error> {
+        if self.entries.iter().any(|e| e.name[..e.name.len() - 4] == *name) {
+            return Err(format_err!("Array `{}` was already added", name));
+        }
+        let npy = npy_bytes(shape, data)?;
+        let name = format!("{}.npy", name);
+        if self.offset + 30 + name.len() as u64 + npy.len() as u64 > u64::from(u32::MAX) {
+            return Err(format_err!("Archives larger than 4 GiB are not supported"));
+        }
+        let entry = NpzEntry {
+            crc: crc32(&npy),
+            size: npy.len() as u32,
+            offset: self.offset as u32,
+            name,
+        };
+        let mut header = vec![];
+        header.write_u32::<LittleEndian>(0x0403_4b50)?;
+        write_common_header(&mut header, &entry)?;
+        header.extend_from_slice(entry.name.as_bytes());
+        self.w.write_all(&header)?;
+        self.w.write_all(&npy)?;
+        self.offset += (header.len() + npy.len()) as u64;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory of the archive and return the
+    /// underlying writer
+    pub fn finish(mut self) -> Result<W, Error> {
+        let mut dir = vec![];
+        for e in &self.entries {
+            dir.write_u32::<LittleEndian>(0x0201_4b50)?;
+            // Version made by
+            dir.write_u16::<LittleEndian>(20)?;
+            write_common_header(&mut dir, e)?;
+            // Comment length, disk number, internal and external attributes
+            dir.write_u16::<LittleEndian>(0)?;
+            dir.write_u16::<LittleEndian>(0)?;
+            dir.write_u16::<LittleEndian>(0)?;
+            dir.write_u32::<LittleEndian>(0)?;
+            dir.write_u32::<LittleEndian>(e.offset)?;
+            dir.extend_from_slice(e.name.as_bytes());
+        }
+        if self.offset + dir.len() as u64 > u64::from(u32::MAX) {
+            return Err(format_err!("Archives larger than 4 GiB are not supported"));
+        }
+        if self.entries.len() > usize::from(u16::MAX) {
+            return Err(format_err!("Archives with more than 65535 arrays are not supported"));
+        }
+        let dir_size = dir.len() as u32;
+        // End of central directory record
+        dir.write_u32::<LittleEndian>(0x0605_4b50)?;
+        dir.write_u16::<LittleEndian>(0)?;
+        dir.write_u16::<LittleEndian>(0)?;
+        dir.write_u16::<LittleEndian>(self.entries.len() as u16)?;
+        dir.write_u16::<LittleEndian>(self.entries.len() as u16)?;
+        dir.write_u32::<LittleEndian>(dir_size)?;
+        dir.write_u32::<LittleEndian>(self.offset as u32)?;
+        dir.write_u16::<LittleEndian>(0)?;
+        self.w.write_all(&dir)?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+/// The part of a zip file header which is shared between the local
+/// header and the central directory
+fn write_common_header(buf: &mut Vec<u8>, e: &NpzEntry) -> Result<(), Error> {
+    // Version needed to extract, flags and method (stored)
+    buf.write_u16::<LittleEndian>(20)?;
+    buf.write_u16::<LittleEndian>(0)?;
+    buf.write_u16::<LittleEndian>(0)?;
+    // Modification time and date (1980-01-01 00:00)
+    buf.write_u16::<LittleEndian>(0)?;
+    buf.write_u16::<LittleEndian>(0x21)?;
+    buf.write_u32::<LittleEndian>(e.crc)?;
+    buf.write_u32::<LittleEndian>(e.size)?;
+    buf.write_u32::<LittleEndian>(e.size)?;
+    buf.write_u16::<LittleEndian>(e.name.len() as u16)?;
+    // Extra field length
+    buf.write_u16::<LittleEndian>(0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn npy_header() {
+        let buf = npy_bytes(&[2, 3], &[1i32, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(&buf[..8], b"\x93NUMPY\x01\x00");
+        let header_len = buf[8] as usize + 256 * buf[9] as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = String::from_utf8(buf[10..10 + header_len].to_vec()).unwrap();
+        assert!(header.starts_with("{'descr': '<i4', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!(buf.len(), 10 + header_len + 6 * 4);
+        assert_eq!(&buf[10 + header_len..10 + header_len + 4], &[1, 0, 0, 0]);
+
+        let buf = npy_bytes(&[1], &[1.0f64]).unwrap();
+        assert!(String::from_utf8_lossy(&buf).contains("'shape': (1,)"));
+        assert!(npy_bytes(&[2], &[1u8]).is_err());
+    }
+
+    #[test]
+    fn npz_archive() {
+        let mut npz = NpzWriter::new(vec![]);
+        npz.add_array("a", &[2], &[1.0f32, 2.0]).unwrap();
+        npz.add_array("b", &[0], &[] as &[bool]).unwrap();
+        assert!(npz.add_array("a", &[1], &[1u8]).is_err());
+        let buf = npz.finish().unwrap();
+        assert_eq!(&buf[..4], b"PK\x03\x04");
+        assert_eq!(&buf[30..35], b"a.npy");
+        assert_eq!(&buf[35..41], b"\x93NUMPY");
+        // End of central directory with two entries
+        let end = &buf[buf.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(&end[8..12], &[2, 0, 2, 0]);
+    }
+}
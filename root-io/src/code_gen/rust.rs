@@ -1,5 +1,10 @@
 /// Types to map out the inter-dependences of the streamed objects
-use quote::Tokens;
+use std::collections::HashSet;
+
+use quote::{Ident, Tokens};
+
+use core::{TStreamer, TStreamerInfo};
+use code_gen::utils::{class_ident, type_is_core};
 
 pub(crate) trait ToRustType {
     fn type_doc(&self) -> Tokens {quote!()}
@@ -8,17 +13,145 @@ pub(crate) trait ToRustType {
 
 pub(crate) trait ToRustParser: ToRustType {
     /// The definition of the parser parsing this thing such that it can be used in-line
-    fn to_inline_parser(&self) -> Tokens {quote!{#(self.parser_name())}}
+    fn to_inline_parser(&self) -> Tokens;
 }
 
-pub(crate) trait ToNamedRustParser: ToRustParser {
+pub(crate) trait ToNamedRustParser {
     /// The name of the parser of this thing
     fn parser_name(&self) -> Tokens;
-    
+
     /// The definition of the parser parsing this thing; May be blank if it is build-in
-    fn to_named_parser(&self) -> Tokens;
+    fn to_named_parser(&self, classes: &Classes) -> Tokens;
+}
+
+pub(crate) trait ToRustStruct {
+    fn to_struct(&self, classes: &Classes) -> Tokens;
+}
+
+/// The classes for which code is generated. Types and parsers of
+/// members depend on whether the class of a member is one of them.
+pub(crate) struct Classes<'a> {
+    /// The streamer info of each class; only the first one is used if
+    /// there are several versions of a class
+    infos: Vec<&'a TStreamerInfo>,
+    /// Classes whose struct borrows from the parsed buffer
+    with_lifetime: HashSet<String>,
+}
+
+impl<'a> Classes<'a> {
+    pub(crate) fn new(infos: &'a [TStreamerInfo]) -> Classes<'a> {
+        let mut infos_unique: Vec<&TStreamerInfo> = vec![];
+        for info in infos {
+            if !infos_unique.iter().any(|i| i.name() == info.name()) {
+                infos_unique.push(info);
+            }
+        }
+        let mut classes = Classes {infos: infos_unique, with_lifetime: HashSet::new()};
+        // A class needs a lifetime if any of its members needs one;
+        // iterate until no new such classes are found
+        loop {
+            let new: Vec<_> = classes.generated().into_iter()
+                .filter(|info| !classes.needs_lifetime(info.name()))
+                .filter(|info| info.data_members().iter().any(|m| m.needs_lifetime(&classes)))
+                .map(|info| info.name().to_string())
+                .collect();
+            if new.is_empty() {
+                return classes;
+            }
+            classes.with_lifetime.extend(new);
+        }
+    }
+
+    /// The classes for which a struct and a parser are generated
+    pub(crate) fn generated(&self) -> Vec<&'a TStreamerInfo> {
+        self.infos.iter().cloned().filter(|info| !type_is_core(info.name())).collect()
+    }
+
+    fn get(&self, class: &str) -> Option<&'a TStreamerInfo> {
+        self.generated().into_iter().find(|info| info.name() == class)
+    }
+
+    /// Does the Rust type of the given class have a lifetime?
+    pub(crate) fn needs_lifetime(&self, class: &str) -> bool {
+        match class {
+            "TObjArray" | "TList" => true,
+            c => self.with_lifetime.contains(c),
+        }
+    }
+
+    /// The Rust type of an object of the given class
+    pub(crate) fn type_of(&self, class: &str) -> Tokens {
+        let t = match class {
+            "TObject" => "TObject".to_string(),
+            "TNamed" => "TNamed".to_string(),
+            "TString" | "TObjString" => "String".to_string(),
+            "TObjArray" => "Vec<Raw<'s>>".to_string(),
+            "TList" => "TList<'s>".to_string(),
+            "TArrayC" => "Vec<i8>".to_string(),
+            "TArrayS" => "Vec<i16>".to_string(),
+            "TArrayI" => "Vec<i32>".to_string(),
+            "TArrayL" | "TArrayL64" => "Vec<i64>".to_string(),
+            "TArrayF" => "Vec<f32>".to_string(),
+            "TArrayD" => "Vec<f64>".to_string(),
+            c if self.get(c).is_some() => {
+                if self.needs_lifetime(c) {
+                    format!("{}<'s>", class_ident(c))
+                } else {
+                    class_ident(c)
+                }
+            },
+            // Classes without a streamer are kept as bytes
+            _ => "Vec<u8>".to_string(),
+        };
+        let t = Ident::new(t);
+        quote!(#t)
+    }
+
+    /// Parser of an object of the given class streamed in place,
+    /// including its leading byte count if it has one
+    pub(crate) fn parser_of(&self, class: &str) -> Tokens {
+        let p = match class {
+            // These are written without byte count
+            "TObject" => "tobject".to_string(),
+            "TString" => "string".to_string(),
+            c if c.starts_with("TArray") => c.to_lowercase(),
+            "TNamed" => "length_value!(checked_byte_count, tnamed)".to_string(),
+            "TObjString" => "length_value!(checked_byte_count, tobjstring)".to_string(),
+            "TObjArray" => "length_value!(checked_byte_count, apply!(tobjarray, context))".to_string(),
+            "TList" => "length_value!(checked_byte_count, apply!(tlist, context))".to_string(),
+            c if self.get(c).is_some() =>
+                format!("length_value!(checked_byte_count, apply!({}, context))", parser_ident(c)),
+            _ => "map!(length_data!(checked_byte_count), |v| v.to_vec())".to_string(),
+        };
+        let p = Ident::new(p);
+        quote!(#p)
+    }
+
+    /// The path of field names through which the member `member` is
+    /// reached from an object of class `class`. Members of base
+    /// classes are nested in fields named after the base class
+    pub(crate) fn member_path(&self, class: &str, member: &str) -> Option<Vec<Ident>> {
+        let info = self.get(class)?;
+        let members = info.data_members();
+        if let Some(m) = members.iter().find(|m| m.elem().name.name == member) {
+            return Some(vec![m.member_name()]);
+        }
+        members.iter()
+            .filter_map(|m| match *m {
+                TStreamer::Base{ref el, ..} => {
+                    self.member_path(&el.name.name, member)
+                        .map(|mut path| {
+                            path.insert(0, m.member_name());
+                            path
+                        })
+                },
+                _ => None,
+            })
+            .next()
+    }
 }
 
-pub(crate) trait ToRustStruct: ToRustType {
-    fn to_struct(&self) -> Tokens;
+/// Name of the generated parser of the given class
+pub(crate) fn parser_ident(class: &str) -> String {
+    class_ident(class).to_lowercase()
 }
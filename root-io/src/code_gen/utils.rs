@@ -8,19 +8,17 @@ pub(crate) fn type_is_core(name: &str) -> bool {
     }
 }
 
-/// If necessary, annotate the given type name with a life time or replace with an alias
-pub(crate) fn alias_or_lifetime(t: &str) -> String {
-    // Most core types do not need a life time specifier
-    if type_is_core(t) && t != "TObjArray" {
-        return t.to_string();
-    }
-    // All non-core types get a life time
-    // This is over zealous, but currently, I don't have a proper way
-    // to check if a type has a member with a lifetime
-    return format!("{}<'s>", t);
+/// Turn a class name such as `ROOT::Math::PxPyPzE4D<double>` into a
+/// valid Rust identifier
+pub(crate) fn class_ident(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
 }
 
+/// Turn a member name into a valid Rust identifier
 pub(crate) fn sanitize(n: &str) -> String {
+    let n = &class_ident(n);
     let keywords = vec!["as", "break", "const", "continue", "crate",
     "else", "enum", "extern", "false", "fn", "for", "if", "impl",
     "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
@@ -0,0 +1,246 @@
+use failure::Error;
+use nom::{be_u16, be_u32, IResult};
+
+use core::{RootFile, TStreamerInfo};
+use core::read_rule::ReadRule;
+
+/// Where the value of a member of a class comes from when reading
+/// an object which was written with another layout of that class
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberSource {
+    /// The member of the same name in the streamed object
+    Streamed,
+    /// The member of the given name in the streamed object; the
+    /// member was renamed according to a read rule
+    Renamed(String),
+    /// The member is missing in the streamed object. Its value is
+    /// set by the C++ code of a read rule
+    Rule(String),
+    /// The member is missing in the streamed object and takes the
+    /// default value of its type
+    Default,
+}
+
+/// Layouts (`TStreamerInfo`s) of classes keyed by the class name
+/// and its version or checksum. The layouts of several files may be
+/// collected in one registry in order to read objects written by
+/// different versions of a class (e.g. ESDs of different
+/// reconstruction passes).
+#[derive(Debug, Default)]
+pub struct ClassRegistry {
+    infos: Vec<TStreamerInfo>,
+    rules: Vec<ReadRule>,
+}
+
+impl ClassRegistry {
+    /// Create an empty registry
+    pub fn new() -> ClassRegistry {
+        ClassRegistry::default()
+    }
+
+    /// Add the layouts and read rules found in the given file
+    pub fn add_file(&mut self, f: &RootFile) -> Result<(), Error> {
+        for info in f.streamers()? {
+            let known = self.infos.iter()
+                .any(|i| i.name() == info.name() && i.version() == info.version()
+                     && i.checksum() == info.checksum());
+            if !known {
+                self.infos.push(info);
+            }
+        }
+        for rule in f.read_rules()? {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+            }
+        }
+        Ok(())
+    }
+
+    /// The layout of `class` with the given version
+    pub fn get(&self, class: &str, version: u32) -> Option<&TStreamerInfo> {
+        self.infos.iter().find(|i| i.name() == class && i.version() == version)
+    }
+
+    /// The layout of `class` with the given checksum
+    pub fn get_by_checksum(&self, class: &str, checksum: u32) -> Option<&TStreamerInfo> {
+        self.infos.iter().find(|i| i.name() == class && i.checksum() == checksum)
+    }
+
+    /// All known versions of `class` in ascending order
+    pub fn versions(&self, class: &str) -> Vec<u32> {
+        let mut versions: Vec<_> = self.infos.iter()
+            .filter(|i| i.name() == class)
+            .map(|i| i.version())
+            .collect();
+        versions.sort();
+        versions.dedup();
+        versions
+    }
+
+    /// The layout with which the object of class `class` at the
+    /// beginning of `obj` was written. `obj` starts with the version
+    /// of the object (i.e. after its byte count). A version of 0
+    /// is followed by the checksum of the layout
+    pub fn layout_of(&self, class: &str, obj: &[u8]) -> Option<&TStreamerInfo> {
+        match be_u16(obj) {
+            IResult::Done(rest, 0) => match be_u32(rest) {
+                IResult::Done(_, checksum) => self.get_by_checksum(class, checksum),
+                _ => None,
+            },
+            IResult::Done(_, version) => self.get(class, u32::from(version)),
+            _ => None,
+        }
+    }
+
+    /// The newest known layout of `class`
+    pub fn latest(&self, class: &str) -> Option<&TStreamerInfo> {
+        self.infos.iter()
+            .filter(|i| i.name() == class)
+            .max_by_key(|i| i.version())
+    }
+
+    /// For each member of the layout `to` of `class`, determine where
+    /// its value comes from if the object was written with the layout
+    /// `from`. Members which are not streamed with `from` are set by
+    /// the read rules applying to `from` or take default values.
+    pub fn evolve(&self, class: &str, from: u32, to: u32) -> Result<Vec<(String, MemberSource)>, Error> {
+        let missing = |v| format_err!("Unknown version {} of class {}", v, class);
+        let from_info = self.get(class, from).ok_or_else(|| missing(from))?;
+        let to_info = self.get(class, to).ok_or_else(|| missing(to))?;
+        Ok(self.evolve_layout(from_info, to_info))
+    }
+
+    /// Like `evolve`, but from the layout with which the object of
+    /// class `class` at the beginning of `obj` was written (see
+    /// `layout_of`) to the newest known layout of `class`
+    pub fn evolve_object(&self, class: &str, obj: &[u8]) -> Result<Vec<(String, MemberSource)>, Error> {
+        let from_info = self.layout_of(class, obj)
+            .ok_or_else(|| format_err!("Unknown layout of the object of class {}", class))?;
+        let to_info = self.latest(class)
+            .ok_or_else(|| format_err!("Unknown class {}", class))?;
+        Ok(self.evolve_layout(from_info, to_info))
+    }
+
+    fn evolve_layout(&self, from_info: &TStreamerInfo, to_info: &TStreamerInfo) -> Vec<(String, MemberSource)> {
+        let class = to_info.name();
+        let streamed = from_info.member_names();
+        let rules: Vec<_> = self.rules.iter()
+            .filter(|r| r.source_class == class && r.target_class == class)
+            .filter(|r| r.applies_to(from_info.version() as i32)
+                    || r.checksums.contains(&from_info.checksum()))
+            .collect();
+        to_info.member_names().into_iter()
+            .map(|member| {
+                let rule = rules.iter().find(|r| r.target.iter().any(|t| t == member));
+                let source = match rule {
+                    _ if streamed.contains(&member) => MemberSource::Streamed,
+                    Some(rule) => match rule.renamed_from(member) {
+                        Some(src) if streamed.contains(&src) => MemberSource::Renamed(src.to_string()),
+                        _ => MemberSource::Rule(rule.code.to_owned()),
+                    },
+                    None => MemberSource::Default,
+                };
+                (member.to_string(), source)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+    use core::{Raw, RootFileWriter, RootObject, TStreamer};
+    use super::*;
+
+    #[test]
+    fn registry_from_files() {
+        let mut reg = ClassRegistry::new();
+        for name in &["sample-5.23.02-zlib.root", "sample-6.10.05-zlib.root"] {
+            let path = PathBuf::from("./src/test_data").join(name);
+            reg.add_file(&RootFile::new_from_file(&path).unwrap()).unwrap();
+        }
+        let versions = reg.versions("TTree");
+        assert_eq!(versions, vec![16, 19]);
+        let old = reg.get("TTree", 16).unwrap();
+        assert_eq!(reg.get_by_checksum("TTree", old.checksum()).unwrap().version(), 16);
+
+        // Version 0 is followed by the checksum
+        let obj = [0, 0, (old.checksum() >> 24) as u8, (old.checksum() >> 16) as u8,
+                   (old.checksum() >> 8) as u8, old.checksum() as u8];
+        assert_eq!(reg.layout_of("TTree", &obj).unwrap().version(), 16);
+        assert_eq!(reg.layout_of("TTree", &[0, 19]).unwrap().version(), 19);
+
+        let evolved = reg.evolve("TTree", 16, 19).unwrap();
+        let source = |m: &str| evolved.iter().find(|(n, _)| n == m).unwrap().1.clone();
+        assert_eq!(source("fEntries"), MemberSource::Streamed);
+        assert_eq!(source("fDefaultEntryOffsetLen"),
+                   MemberSource::Rule("{ fDefaultEntryOffsetLen = 1000; }".to_string()));
+        assert_eq!(source("fNClusterRange"),
+                   MemberSource::Rule("{ fNClusterRange = 0; }".to_string()));
+    }
+
+    #[test]
+    fn renamed_members() {
+        let layout = |version, member| TStreamerInfo::new("A", "", version, vec![
+            TStreamer::string(member, ""),
+        ]);
+        let rule = "type=read sourceClass=\"A\" targetClass=\"A\" version=\"[1]\" \
+                    source=\"TString fOld\" target=\"fNew\" code=\"{ fNew = onfile.fOld; }\"";
+        let reg = ClassRegistry {
+            infos: vec![layout(1, "fOld"), layout(2, "fNew")],
+            rules: vec![ReadRule::parse(rule).unwrap().unwrap()],
+        };
+        assert_eq!(reg.evolve("A", 1, 2).unwrap(),
+                   vec![("fNew".to_string(), MemberSource::Renamed("fOld".to_string()))]);
+        assert_eq!(reg.evolve("A", 2, 1).unwrap(),
+                   vec![("fOld".to_string(), MemberSource::Default)]);
+    }
+
+    #[test]
+    fn unknown_objects() {
+        let v1 = TStreamerInfo::new("A", "", 1, vec![TStreamer::string("fOld", "")]);
+        let v2 = TStreamerInfo::new("A", "", 2, vec![TStreamer::string("fNew", ""),
+                                                     TStreamer::string("fExtra", ""),
+                                                     TStreamer::string("fOther", "")]);
+        let renamed = "type=read sourceClass=\"A\" targetClass=\"A\" version=\"[1]\" \
+                       source=\"TString fOld\" target=\"fNew\" code=\"{ fNew = onfile.fOld; }\"";
+        // Only applies to the layout with the given checksum
+        let extra = format!("type=read sourceClass=\"A\" targetClass=\"A\" checksum=\"[{}]\" \
+                                   source=\"\" target=\"fExtra\" code=\"{{ fExtra = \"x\"; }}\"",
+                                  v1.checksum());
+        let other_checksum = "type=read sourceClass=\"A\" targetClass=\"A\" checksum=\"[1]\" \
+                              source=\"\" target=\"fOther\" code=\"{ fOther = \"y\"; }\"";
+        let checksum = v1.checksum();
+        let reg = ClassRegistry {
+            infos: vec![v1, v2],
+            rules: [renamed, &extra, other_checksum].iter()
+                .map(|r| ReadRule::parse(r).unwrap().unwrap())
+                .collect(),
+        };
+        let expected = vec![
+            ("fNew".to_string(), MemberSource::Renamed("fOld".to_string())),
+            ("fExtra".to_string(), MemberSource::Rule("{ fExtra = \"x\"; }".to_string())),
+            ("fOther".to_string(), MemberSource::Default),
+        ];
+        // Written with version 1 and with the checksum of version 1
+        let by_version = [0, 1, 3, b'a', b'b', b'c'];
+        let by_checksum = [0, 0, (checksum >> 24) as u8, (checksum >> 16) as u8,
+                           (checksum >> 8) as u8, checksum as u8, 3, b'a', b'b', b'c'];
+
+        let path = env::temp_dir().join("root-io-class-registry.root");
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.write_object("by_version", "", &Raw {classinfo: "A".to_string(), obj: &by_version}).unwrap();
+            f.write_object("by_checksum", "", &Raw {classinfo: "A".to_string(), obj: &by_checksum}).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&path).unwrap();
+        for item in f.items() {
+            match item.object_with(&reg).unwrap() {
+                RootObject::Unknown {ref members, ..} => assert_eq!(members, &expected),
+                o => panic!("Unexpected {:?}", o),
+            }
+        }
+    }
+}
@@ -9,7 +9,8 @@ use nom::*;
 
 use MAP_OFFSET;
 use ::core::*;
-use ::code_gen::rust::{ToNamedRustParser, ToRustStruct};
+use ::code_gen::rust::{Classes, ToNamedRustParser, ToRustStruct};
+use ::tree_reader::{model_as_rust, Tree};
 
 /// `RootFile` wraps the most basic information of a ROOT file.
 #[derive(Debug)]
@@ -38,14 +39,16 @@ struct FileHeader {
 
 #[derive(Debug, PartialEq)]
 pub struct Directory {
-    version: i16,
-    c_time: u32,
-    m_time: u32,
-    n_bytes_keys: i32,
-    n_bytes_name: i32,
-    seek_dir: SeekFrom,
-    seek_parent: SeekFrom,
-    seek_keys: SeekFrom,
+    pub(crate) version: i16,
+    pub(crate) c_time: u32,
+    pub(crate) m_time: u32,
+    pub(crate) n_bytes_keys: i32,
+    pub(crate) n_bytes_name: i32,
+    pub(crate) seek_dir: SeekFrom,
+    pub(crate) seek_parent: SeekFrom,
+    pub(crate) seek_keys: SeekFrom,
+    /// Zeros if the directory was written without one
+    pub(crate) uuid: [u8; 16],
 }
 
 
@@ -79,7 +82,7 @@ named!(
 
 /// Directory within a root file; exists on ever file
 named!(
-    directory<&[u8], Directory>,
+    pub directory<&[u8], Directory>,
     do_parse!(
         version: be_i16 >>
         c_time: be_u32 >>
@@ -92,16 +95,53 @@ named!(
             cond_reduce!(version > 1000, be_u64) | be_i32 => {|val| val as u64}) >>
         seek_keys: alt_complete!(
             cond_reduce!(version > 1000, be_u64) | be_i32 => {|val| val as u64}) >>
+        uuid: opt!(complete!(preceded!(be_u16, take!(16)))) >>
             ({
                 let seek_dir = SeekFrom::Start(seek_dir);
                 let seek_parent = SeekFrom::Start(seek_parent);
                 let seek_keys = SeekFrom::Start(seek_keys);
+                let mut uuid_bytes = [0; 16];
+                if let Some(uuid) = uuid {
+                    uuid_bytes.copy_from_slice(uuid);
+                }
                 Directory {version, c_time, m_time, n_bytes_keys,
-                           n_bytes_name, seek_dir, seek_parent, seek_keys,
+                           n_bytes_name, seek_dir, seek_parent, seek_keys, uuid: uuid_bytes,
                 }})
     )
 );
 
+/// Counterpart to the `directory` parser; the layout is that of
+/// ROOT's `TDirectoryFile::FillBuffer`
+impl Streamable for Directory {
+    fn class_name(&self) -> &str {
+        "TDirectory"
+    }
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_i16(self.version);
+        buf.put_u32(self.c_time);
+        buf.put_u32(self.m_time);
+        buf.put_i32(self.n_bytes_keys);
+        buf.put_i32(self.n_bytes_name);
+        for seek in &[&self.seek_dir, &self.seek_parent, &self.seek_keys] {
+            let pos = match **seek {
+                SeekFrom::Start(pos) => pos,
+                _ => unreachable!("Seek points are always absolute"),
+            };
+            if self.version > 1000 {
+                buf.put_u64(pos);
+            } else {
+                buf.put_i32(pos as i32);
+            }
+        }
+        buf.put_u16(1);
+        buf.put_bytes(&self.uuid);
+        if self.version <= 1000 {
+            // Reserved for 64 bit seek points
+            buf.put_bytes(&[0; 12]);
+        }
+    }
+}
+
 
 impl RootFile {
     /// Open a ROOT file and read in the necessary meta information
@@ -114,27 +154,19 @@ impl RootFile {
         // Jump to the TDirectory and parse it
         reader.seek(hdr.seek_dir)?;
         let dir = parse_buffer(&mut reader, 256, directory)?;
-        let tkey_of_keys = {
-            // Jump to TKey holding a list of TKeys describing the file content
-            reader.seek(dir.seek_keys)?;
-            parse_buffer(&mut reader, 1024, tkey)?
-        };
-        let keys = match tkey_headers(&tkey_of_keys.obj) {
-            IResult::Done(_, hdrs) => Ok(hdrs),
-            _ => Err(format_err!("Expected TKeyHeaders"))
-        }?;
-        let items = keys.iter()
-            .map(|k_hdr| FileItem::new(k_hdr, &path))
-            .collect();
+        let items = items_in_directory(path, &dir)?;
 
         let path = path.to_owned();
         Ok(RootFile {path, hdr, items})
     }
 
-    /// Return all `TSreamerInfo` for the data in this file
-    pub fn streamers(&self) -> Result<Vec<TStreamerInfo>, Error> {
-        let f = File::open(&self.path)?;
-        let mut reader = BufReader::new(f);
+    /// Apply `f` to the objects of the `TList` holding the streamer
+    /// info of this file
+    fn with_streamer_info_list<T, F>(&self, f: F) -> Result<T, Error>
+        where F: for<'s> FnOnce(&[Raw<'s>], &'s Context<'s>) -> Result<T, Error>
+    {
+        let f_in = File::open(&self.path)?;
+        let mut reader = BufReader::new(f_in);
         
         // Read streamer info
         reader.seek(self.hdr.seek_info)?;
@@ -154,30 +186,54 @@ impl RootFile {
                 IResult::Done(_, l) => Ok(l.objs),
                 _ => Err(format_err!("Expected TStreamerInfo's TList"))
             }?;
+        f(&tlist_objs, &context)
+    }
+
+    /// Return all `TSreamerInfo` for the data in this file
+    pub fn streamers(&self) -> Result<Vec<TStreamerInfo>, Error> {
         // Mainly this is a TList of `TStreamerInfo`s, but there might
         // be some "rules" in the end
-        let streamers = Ok(tlist_objs.iter()
-                           .filter_map(|raw| {match raw.classinfo.as_str() {
-                               "TStreamerInfo" => Some(raw.obj),
-                               _ => None
-                           }})
-                           .map(|i| tstreamerinfo(i, &context).unwrap().1)
-                           .collect());
-        // Parse the "rules", if any, from the same tlist
-        let _rules: Vec<_> = tlist_objs.iter()
-            .filter_map(|raw| {match raw.classinfo.as_str() {
-                "TList" => Some(raw.obj),
-                _ => None
-            }})
-            .map(|i| {
-                let tl = tlist(i, &context).unwrap().1;
-                // Each `Rule` is a TList of `TObjString`s
-                tl.objs.iter()
-                    .map(|el| tobjstring(el.obj).unwrap().1)
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-        streamers
+        self.with_streamer_info_list(|objs, context| {
+            objs.iter()
+                .filter(|raw| raw.classinfo == "TStreamerInfo")
+                .map(|raw| match tstreamerinfo(raw.obj, context) {
+                    IResult::Done(_, info) => Ok(info),
+                    _ => Err(format_err!("Failed to parse TStreamerInfo")),
+                })
+                .collect()
+        })
+    }
+
+    /// The schema evolution rules for reading objects written with
+    /// older layouts of their classes. These are stored as strings
+    /// in a `TList` after the `TStreamerInfo`s
+    pub fn read_rules(&self) -> Result<Vec<ReadRule>, Error> {
+        self.with_streamer_info_list(|objs, context| {
+            // The rules are in a `TList` of `TObjString`s after the `TStreamerInfo`s
+            let mut rules = vec![];
+            for raw in objs.iter().filter(|raw| raw.classinfo == "TList") {
+                let rule_strings = match tlist(raw.obj, context) {
+                    IResult::Done(_, l) => Ok(l.objs),
+                    _ => Err(format_err!("Expected a TList of rules")),
+                }?;
+                for el in rule_strings {
+                    if let IResult::Done(_, s) = tobjstring(el.obj) {
+                        if let Some(rule) = ReadRule::parse(&s)? {
+                            rules.push(rule);
+                        }
+                    }
+                }
+            }
+            Ok(rules)
+        })
+    }
+
+    /// The layouts of the classes in this file and the rules to read
+    /// objects written with older layouts
+    pub fn class_registry(&self) -> Result<ClassRegistry, Error> {
+        let mut reg = ClassRegistry::new();
+        reg.add_file(self)?;
+        Ok(reg)
     }
 
     /// Slice of the items contained in this file
@@ -187,45 +243,83 @@ impl RootFile {
 
     /// Translate the streamer info of this file to a YAML file
     pub fn streamer_info_as_yaml(&self, s: &mut fmt::Write) -> Result<(), Error> {
-        for el in &self.streamers()? {
-            writeln!(s, "{:#}", el.to_yaml())?;
+        let streamers = self.streamers()?;
+        let classes = Classes::new(&streamers);
+        for el in classes.generated() {
+            writeln!(s, "{:#}", el.to_yaml(&classes))?;
         }
         Ok(())
     }
 
-    /// Generate Rust code from the streamer info of this file
+    /// Generate Rust code from the streamer info of this file. The
+    /// code consists of a struct and a parser for each class and
+    /// compiles against the public API of this crate. The crate
+    /// including it must declare `#[macro_use] extern crate nom;`
+    /// and `extern crate root_io;`
     pub fn streamer_info_as_rust(&self, s: &mut fmt::Write) -> Result<(), Error> {
+        let streamers = self.streamers()?;
+        let classes = Classes::new(&streamers);
         // Add necessary imports at the top of the file
         writeln!(s, "{}",
                  quote!{
-                     use std::marker::PhantomData;
                      use nom::*;
-                     use parsers::*;
-                     use parsers::utils::*;
-                     use core_types::*;
+                     use root_io::core::parsers::*;
+                     use root_io::core::types::*;
                  }.to_string())?;
 
         // generate structs
-        for el in &self.streamers()? {
-            // The structs contain comments which introduce line breaks; i.e. readable
-            writeln!(s, "{}", el.to_struct().to_string())?;
+        for el in classes.generated() {
+            writeln!(s, "{}", el.to_struct(&classes))?;
         }
 
         // generate parsers
-        for el in &self.streamers()? {
-            // The parsers have no comments, but are ugly; We introduce some
-            // Linebreaks here to not have rustfmt choke later (doing it later
-            // is inconvinient since the comments in the structs might contain
-            // the patterns
-            let parsers = el.to_named_parser().to_string();
-            let parsers = parsers.replace(",", ",\n");
-            let parsers = parsers.replace(">>", ">>\n");
-            // macro names are generated as my_macro ! (...) by `quote`
-            let parsers = parsers.replace(" ! (", "!(");
-            writeln!(s, "{}", parsers)?;
-        }        
+        for el in classes.generated() {
+            writeln!(s, "{}", el.to_named_parser(&classes))?;
+        }
         Ok(())
     }
+
+    /// Generate a typed model for the tree `tree_name` of this
+    /// file. The model is a struct named `model_name` holding one
+    /// entry of the branches matching any of the glob patterns in
+    /// `branches` (e.g. `Tracks.f*`; `*` matches any sequence of
+    /// characters and `?` a single one). It comes with an iterator
+    /// `<model_name>IntoIter` over the entries of a `Tree` built
+    /// from `ColumnFixedIntoIter`s and `ColumnVarIntoIter`s. This is
+    /// meant to be called from a build script; the crate including
+    /// the output must declare `#[macro_use] extern crate nom;`,
+    /// `extern crate failure;`, and `extern crate root_io;`
+    pub fn tree_model_as_rust(&self, tree_name: &str, branches: &[&str], model_name: &str,
+                              s: &mut dyn fmt::Write) -> Result<(), Error> {
+        let tree = self.tree(tree_name)?;
+        model_as_rust(&tree, model_name, branches, s)
+    }
+
+    /// Read the tree called `name` from the top level of this file
+    pub fn tree(&self, name: &str) -> Result<Tree, Error> {
+        self.items.iter()
+            .find(|item| item.tkey_hdr.class_name == "TTree" && item.tkey_hdr.obj_name == name)
+            .ok_or_else(|| format_err!("No tree named `{}` in this file", name))?
+            .as_tree()
+    }
+}
+
+/// Read the headers of the keys in the given directory of the file at `path`
+pub(crate) fn items_in_directory(path: &Path, dir: &Directory) -> Result<Vec<FileItem>, Error> {
+    let f = File::open(path)?;
+    let mut reader = BufReader::new(f);
+    let tkey_of_keys = {
+        // Jump to TKey holding a list of TKeys describing the directory content
+        reader.seek(dir.seek_keys)?;
+        parse_buffer(&mut reader, 1024, tkey)?
+    };
+    let keys = match tkey_headers(&tkey_of_keys.obj) {
+        IResult::Done(_, hdrs) => Ok(hdrs),
+        _ => Err(format_err!("Expected TKeyHeaders"))
+    }?;
+    Ok(keys.iter()
+       .map(|k_hdr| FileItem::new(k_hdr, path))
+       .collect())
 }
 
 /// Use given parse on reader. If the initial `buf_size` is not large
@@ -306,7 +400,9 @@ mod test {
             n_bytes_name: 58,
             seek_dir: SeekFrom::Start(100),
             seek_parent: SeekFrom::Start(0),
-            seek_keys: SeekFrom::Start(1021)
+            seek_keys: SeekFrom::Start(1021),
+            uuid: [0x74, 0x62, 0xdc, 0x84, 0xce, 0x85, 0x11, 0xe5,
+                   0x97, 0x17, 0x01, 0x00, 0x00, 0x7f, 0xbe, 0xef],
         });
     }
 
@@ -4,15 +4,19 @@ use std::fs::File;
 use std::io::{BufReader, Seek, SeekFrom, Read};
 use failure::Error;
 
-use ::core::{TKeyHeader, Context};
+use ::core::{ClassRegistry, TKeyHeader, Context, RootFile, RootObject};
 use ::tree_reader::{ttree, Tree};
-use core::{decompress, checked_byte_count};
+use core::{decompress, checked_byte_count, root_object};
+use core::file::{directory, items_in_directory};
+use histograms::{tgraph, th1, tprofile, TGraph, TH1, TProfile};
+#[cfg(feature = "histogram")]
+use histograms::FromTH1;
 
 /// Describes a single item within this file (e.g. a `Tree`)
 #[derive(Debug)]
 pub struct FileItem {
     file_path: PathBuf,
-    tkey_hdr: TKeyHeader,
+    pub(crate) tkey_hdr: TKeyHeader,
 }
 
 impl FileItem {
@@ -28,6 +32,63 @@ impl FileItem {
         self.parse_with(ttree)
     }
 
+    /// Parse this item as a histogram (`TH1`, `TH2`, or `TH3` and
+    /// their subclasses such as `TH1F`). Profiles are read with `as_tprofile`
+    pub fn as_th1(&self) -> Result<TH1, Error> {
+        let class = &self.tkey_hdr.class_name;
+        self.parse_with(|i, c| th1(i, c, class))
+            .map_err(|_| format_err!("Cannot read {} as a histogram", self.name()))
+    }
+
+    /// Parse this item as a `TProfile` or `TProfile2D`
+    pub fn as_tprofile(&self) -> Result<TProfile, Error> {
+        let class = &self.tkey_hdr.class_name;
+        self.parse_with(|i, c| tprofile(i, c, class))
+            .map_err(|_| format_err!("Cannot read {} as a profile", self.name()))
+    }
+
+    /// Parse this item as a `TGraph`, `TGraphErrors`, or `TGraphAsymmErrors`
+    pub fn as_graph(&self) -> Result<TGraph, Error> {
+        let class = &self.tkey_hdr.class_name;
+        self.parse_with(|i, c| tgraph(i, c, class))
+            .map_err(|_| format_err!("Cannot read {} as a graph", self.name()))
+    }
+
+    /// Parse this item as a histogram and convert it to a
+    /// `histogram::Histogram` of the matching dimension
+    #[cfg(feature = "histogram")]
+    pub fn as_histogram<H: FromTH1>(&self) -> Result<H, Error> {
+        H::from_th1(&self.as_th1()?)
+    }
+
+    /// Parse this item into the `RootObject` matching its class
+    /// name. Objects of classes which cannot be parsed are returned
+    /// as `RootObject::Unknown` along with how their members map to
+    /// the newest layout of their class found in this item's file
+    pub fn object(&self) -> Result<RootObject, Error> {
+        let registry = RootFile::new_from_file(&self.file_path)?.class_registry()?;
+        self.object_with(&registry)
+    }
+
+    /// Like `object`, but the members of objects of unknown classes
+    /// are mapped using the layouts in `registry`, which may be
+    /// collected from several files
+    pub fn object_with(&self, registry: &ClassRegistry) -> Result<RootObject, Error> {
+        let class = self.tkey_hdr.class_name.as_str();
+        match class {
+            // Directories are not preceeded by a byte count
+            "TDirectory" | "TDirectoryFile" => {
+                let dir = match directory(&self.payload()?) {
+                    IResult::Done(_, dir) => Ok(dir),
+                    _ => Err(format_err!("Cannot read {} as a directory", self.name())),
+                }?;
+                Ok(RootObject::Directory(items_in_directory(&self.file_path, &dir)?))
+            },
+            _ => self.parse_with(|i, c| root_object(i, c, class, registry))
+                .map_err(|_| format_err!("Cannot parse {}", self.name())),
+        }
+    }
+
     /// Information about this file item in Human readable form
     pub fn verbose_info(&self) -> String {
         format!("{:#?}", self.tkey_hdr)
@@ -40,30 +101,12 @@ impl FileItem {
     /// Read (and posibly decompress) data from disk and parse it as
     /// the appropriate type using the TStreamerInfo types.
     /// The return type of the parser function must not contain a
-    /// reference to the parsed buffer
-    pub(crate) fn parse_with<O, F>(&self, parser: F) -> Result<O, Error>
+    /// reference to the parsed buffer. The parsers generated by
+    /// `RootFile::streamer_info_as_rust` can be used here.
+    pub fn parse_with<O, F>(&self, parser: F) -> Result<O, Error>
         where F: for<'s> Fn(&'s[u8], &'s Context<'s>) -> IResult<&'s[u8], O>,
     {
-        let f = File::open(&self.file_path)?;
-        let mut reader = BufReader::new(f);
-        let mut comp_buf = vec![0; (self.tkey_hdr.total_size - self.tkey_hdr.key_len as u32) as usize];
-        let key_start = self.tkey_hdr.seek_key;
-        let payload_offset = SeekFrom::Current(i64::from(self.tkey_hdr.key_len));
-
-        // Skip TKey and jump right to the payload
-        reader.seek(key_start)?;
-        reader.seek(payload_offset)?;
-        reader.read_exact(&mut comp_buf)?;
-
-        let buf = {
-            if self.tkey_hdr.total_size < self.tkey_hdr.uncomp_len {
-                // Decompress the read buffer; buf is Vec<u8>
-                let (_, buf) = decompress(comp_buf.as_slice()).unwrap();
-                buf
-            } else {
-                comp_buf
-            }
-        };
+        let buf = self.payload()?;
         let s = buf.as_slice();
         let k_map_offset = 2;
         let context = Context{
@@ -77,13 +120,40 @@ impl FileItem {
             _ => Err(format_err!("Supplied parser failed!"))
         }
     }
+
+    /// Read (and posibly decompress) the payload of this item from disk
+    fn payload(&self) -> Result<Vec<u8>, Error> {
+        let f = File::open(&self.file_path)?;
+        let mut reader = BufReader::new(f);
+        let comp_len = (self.tkey_hdr.total_size - self.tkey_hdr.key_len as u32) as usize;
+        let mut comp_buf = vec![0; comp_len];
+        let key_start = self.tkey_hdr.seek_key;
+        let payload_offset = SeekFrom::Current(i64::from(self.tkey_hdr.key_len));
+
+        // Skip TKey and jump right to the payload
+        reader.seek(key_start)?;
+        reader.seek(payload_offset)?;
+        reader.read_exact(&mut comp_buf)?;
+
+        if self.tkey_hdr.uncomp_len as usize > comp_len {
+            // Decompress the read buffer; buf is Vec<u8>
+            match decompress(comp_buf.as_slice()) {
+                IResult::Done(_, buf) => Ok(buf),
+                _ => Err(format_err!("Failed to decompress {}", self.name())),
+            }
+        } else {
+            Ok(comp_buf)
+        }
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::env;
     use std::path::{PathBuf};
-    use core::RootFile;
+    use core::*;
+    use histograms::{TAxis, TH1};
     
     #[test]
     fn open_simple() {
@@ -95,6 +165,101 @@ mod tests {
         assert_eq!(f.streamers().unwrap().len(), 18);
     }
 
+    #[test]
+    fn object_dispatch() {
+        let path = env::temp_dir().join("root-io-objects.root");
+        let named = TNamed {name: "name".to_string(), title: "title".to_string()};
+        let obj_string = {
+            let mut buf = WBuffer::new(0);
+            TObject::default().stream(&mut buf);
+            buf.put_string("some string");
+            buf
+        };
+        let with_version = |bytes: &[u8]| {
+            let mut buf = WBuffer::new(0);
+            buf.put_u16(1);
+            buf.put_bytes(bytes);
+            buf.as_slice().to_vec()
+        };
+        let obj_string = with_version(obj_string.as_slice());
+        let unknown = with_version(&[1, 2, 3]);
+        let named_bytes = {
+            let mut buf = WBuffer::new(0);
+            named.stream(&mut buf);
+            // Strip the byte count
+            buf.as_slice()[4..].to_vec()
+        };
+        let list = TList {
+            ver: 5,
+            tobj: TObject::default(),
+            name: "".to_string(),
+            len: 2,
+            objs: vec![Raw {classinfo: "TObjString".to_string(), obj: &obj_string},
+                       Raw {classinfo: "TNamed".to_string(), obj: &named_bytes}],
+        };
+        let hist = TH1::new("h", "", vec![TAxis::new("xaxis", "", vec![0., 1.])]).unwrap();
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.write_object("named", "", &named).unwrap();
+            f.write_object("list", "", &list).unwrap();
+            f.write_object("hist", "", &hist).unwrap();
+            f.write_object("foo", "", &Raw {classinfo: "TFoo".to_string(), obj: &unknown}).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&path).unwrap();
+        let objs: Vec<_> = f.items().iter().map(|i| i.object().unwrap()).collect();
+        match objs[0] {
+            RootObject::Named(ref n) => assert_eq!(n.title, "title"),
+            ref o => panic!("Unexpected {:?}", o),
+        }
+        match objs[1] {
+            RootObject::List(ref l) => match (&l[0], &l[1]) {
+                (RootObject::ObjString(s), RootObject::Named(n)) => {
+                    assert_eq!(s, "some string");
+                    assert_eq!(n.name, "name");
+                },
+                o => panic!("Unexpected {:?}", o),
+            },
+            ref o => panic!("Unexpected {:?}", o),
+        }
+        match objs[2] {
+            RootObject::Histogram(ref h) => assert_eq!(h, &hist),
+            ref o => panic!("Unexpected {:?}", o),
+        }
+        match objs[3] {
+            RootObject::Unknown {ref class_name, ref data, ref members} => {
+                assert_eq!(class_name, "TFoo");
+                assert_eq!(data, &unknown);
+                // The file does not describe the layout of `TFoo`
+                assert!(members.is_empty());
+            },
+            ref o => panic!("Unexpected {:?}", o),
+        }
+
+        let path = PathBuf::from("./src/test_data/simple.root");
+        let f = RootFile::new_from_file(&path).unwrap();
+        match f.items()[0].object().unwrap() {
+            RootObject::Tree(t) => assert!(!t.branch_names_and_types().is_empty()),
+            o => panic!("Unexpected {:?}", o),
+        }
+    }
+
+    #[test]
+    fn nested_directories() {
+        let path = PathBuf::from("./src/test_data/nesteddirs.root");
+        let f = RootFile::new_from_file(&path).unwrap();
+        let items = match f.items()[0].object().unwrap() {
+            RootObject::Directory(items) => items,
+            o => panic!("Unexpected {:?}", o),
+        };
+        let names: Vec<_> = items.iter().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["`two` of type `TDirectory`", "`tree` of type `TTree`"]);
+        match items[1].object().unwrap() {
+            RootObject::Tree(_) => {},
+            o => panic!("Unexpected {:?}", o),
+        }
+    }
+
     #[test]
     #[ignore]
     fn open_esd() {
@@ -0,0 +1,394 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use failure::Error;
+
+use ::core::*;
+use core::file::Directory;
+#[cfg(feature = "histogram")]
+use histograms::ToTH1;
+
+/// Version of ROOT which we claim to have written the file
+const ROOT_VERSION: i32 = 60600;
+/// Start of the first key (the top directory)
+const BEGIN: u64 = 100;
+/// The file is considered to have free space from the end of the
+/// file up to this point; See ROOT's `TFree`
+const FREE_LAST: i32 = 2_000_000_000;
+
+/// Write objects to a new ROOT file. The file is only valid after
+/// `close` has been called.
+///
+/// ```no_run
+/// # extern crate root_io;
+/// # use root_io::RootFileWriter;
+/// # use root_io::core::types::TNamed;
+/// # fn main() {
+/// let mut f = RootFileWriter::create("out.root").unwrap();
+/// let named = TNamed {name: "name".to_string(), title: "title".to_string()};
+/// f.write_object("my_named", "", &named).unwrap();
+/// f.close().unwrap();
+/// # }
+/// ```
+pub struct RootFileWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Name of the file (without the directories) as written to its directory
+    name: String,
+    title: String,
+    compression: u32,
+    datime: u32,
+    uuid: [u8; 16],
+    /// Current end of the file; the next key is written here
+    end: u64,
+    /// Size of the top directory's key including its name and title
+    n_bytes_name: u32,
+    /// Headers of the keys of all the objects in the top directory
+    keys: Vec<TKeyHeader>,
+    /// Classes which have been written to this file
+    classes: Vec<String>,
+}
+
+impl RootFileWriter {
+    /// Create a new ROOT file at `path`. An existing file is overwritten.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format_err!("Invalid file name {:?}", path))?
+            .to_string();
+        let writer = BufWriter::new(File::create(&path)?);
+        let mut f = RootFileWriter {
+            path, writer, name,
+            title: "".to_string(),
+            compression: 1,
+            datime: datime(SystemTime::now()),
+            uuid: new_uuid(),
+            end: 0,
+            n_bytes_name: 0,
+            keys: vec![],
+            classes: vec![],
+        };
+        // The header is written when closing the file; reserve space for it
+        f.writer.write_all(&[0; BEGIN as usize])?;
+        f.end = BEGIN;
+        f.write_directory(0, 0)?;
+        Ok(f)
+    }
+
+    /// Set the zlib compression level (0-9) for the objects
+    /// written from now on. 0 disables compression. Defaults to 1
+    pub fn set_compression(&mut self, level: u32) {
+        self.compression = ::std::cmp::min(level, 9);
+    }
+
+    /// Path of the file being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the given object to the top directory of the file. If
+    /// an object of the same name already exists in the file, the
+    /// new one is written with an increased cycle number.
+    pub fn write_object(&mut self, name: &str, title: &str, obj: &dyn Streamable) -> Result<(), Error> {
+        let cycle = self.keys.iter()
+            .filter(|k| k.obj_name == name)
+            .map(|k| k.cycle + 1)
+            .max()
+            .unwrap_or(1);
+        let hdr = self.write_key(obj.class_name(), name, title, cycle, |w| obj.stream(w))?;
+        self.keys.push(hdr);
+        Ok(())
+    }
+
+    /// Write a `histogram::Histogram` as `TH1D`, `TH2D`, or `TH3D`
+    #[cfg(feature = "histogram")]
+    pub fn write_histogram<H: ToTH1>(&mut self, name: &str, title: &str, h: &H) -> Result<(), Error> {
+        self.write_object(name, title, &h.to_th1(name, title)?)
+    }
+
+    /// Write a new key with the content streamed by `f` at the end of
+    /// the file and return its header. The payload is compressed
+    /// according to the current compression level.
+    pub(crate) fn write_key<F>(&mut self, class_name: &str, name: &str, title: &str,
+                               cycle: i16, f: F) -> Result<TKeyHeader, Error>
+        where F: FnOnce(&mut WBuffer)
+    {
+        let mut hdr = self.new_key_header(class_name, name, title, cycle);
+        let mut buf = WBuffer::new(hdr.key_len as u32);
+        f(&mut buf);
+        for class in buf.classes().into_iter().chain(Some(class_name.to_string())) {
+            if !self.classes.contains(&class) {
+                self.classes.push(class);
+            }
+        }
+        let payload = buf.into_inner();
+        let comp = self.compress(&payload)?;
+        let data = comp.as_ref().unwrap_or(&payload);
+        hdr.set_sizes(data.len(), payload.len());
+        let mut key = WBuffer::new(0);
+        hdr.stream(&mut key);
+        key.put_bytes(data);
+        self.append(key.as_slice())?;
+        Ok(hdr)
+    }
+
+    /// Header of a new key in the top directory at the current end of the file
+    pub(crate) fn new_key_header(&self, class_name: &str, name: &str, title: &str,
+                                 cycle: i16) -> TKeyHeader {
+        TKeyHeader::new(class_name, name, title, cycle, self.end, BEGIN, self.datime)
+    }
+
+    /// Compression setting as it is stored in ROOT files and branches
+    pub(crate) fn compression_setting(&self) -> i32 {
+        if self.compression > 0 { 100 + self.compression as i32 } else { 0 }
+    }
+
+    /// Compress `buf` according to the current compression level. See `compress`
+    pub(crate) fn compress(&self, buf: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        compress(buf, self.compression)
+    }
+
+    /// Write the meta data of the file (streamer info, list of keys,
+    /// free segments) and close it.
+    pub fn close(mut self) -> Result<(), Error> {
+        let (file_name, title) = (self.name.clone(), self.title.clone());
+
+        // Description of all the written classes. Not a regular key
+        // so it is not added to the list of keys
+        let infos = {
+            let classes = self.classes.iter().map(|s| s.as_str());
+            streamer_infos_for(classes)
+        };
+        let info_key = self.write_key("TList", "StreamerInfo", "Doubly linked list", 1, |w| {
+            let infos: Vec<&dyn Streamable> = infos.iter().map(|info| info as _).collect();
+            put_tlist(w, 5, &TObject::default(), "", &infos);
+        })?;
+
+        // The list of keys in the top directory. ROOT reads it without
+        // decompressing, so it is always stored uncompressed
+        let mut keys_key = self.new_key_header("TFile", &file_name, &title, 1);
+        let mut buf = WBuffer::new(keys_key.key_len as u32);
+        buf.put_i32(self.keys.len() as i32);
+        for k in &self.keys {
+            k.stream(&mut buf);
+        }
+        let keys_len = buf.len();
+        keys_key.set_sizes(keys_len, keys_len);
+        let mut key = WBuffer::new(0);
+        keys_key.stream(&mut key);
+        key.put_bytes(buf.as_slice());
+        self.append(key.as_slice())?;
+
+        // The free segments record; the segment starts after this key
+        let mut free_key = self.new_key_header("TFile", &file_name, &title, 1);
+        let free_len = 2 + 4 + 4;
+        free_key.set_sizes(free_len, free_len);
+        let mut buf = WBuffer::new(0);
+        free_key.stream(&mut buf);
+        buf.put_u16(1);
+        buf.put_i32((self.end + u64::from(free_key.total_size)) as i32);
+        buf.put_i32(FREE_LAST);
+        self.append(buf.as_slice())?;
+
+        self.write_directory(seek(&keys_key.seek_key), keys_key.total_size)?;
+
+        // Finally, the file header
+        let mut hdr = WBuffer::new(0);
+        hdr.put_bytes(b"root");
+        hdr.put_i32(ROOT_VERSION);
+        hdr.put_i32(BEGIN as i32);
+        hdr.put_i32(self.end as i32);
+        hdr.put_i32(seek(&free_key.seek_key) as i32);
+        hdr.put_u32(free_key.total_size);
+        // Number of free segments
+        hdr.put_i32(1);
+        hdr.put_u32(self.n_bytes_name);
+        // Size of seek points
+        hdr.put_u8(4);
+        hdr.put_i32(self.compression_setting());
+        hdr.put_i32(seek(&info_key.seek_key) as i32);
+        hdr.put_u32(info_key.total_size);
+        hdr.put_u16(1);
+        hdr.put_bytes(&self.uuid);
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(hdr.as_slice())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the key of the top directory. The first call creates
+    /// it, later calls update the position of the list of keys
+    fn write_directory(&mut self, seek_keys: u64, n_bytes_keys: u32) -> Result<(), Error> {
+        let mut hdr = TKeyHeader::new("TFile", &self.name, &self.title, 1, BEGIN, 0, self.datime);
+        let mut buf = WBuffer::new(0);
+        buf.put_string(&self.name);
+        buf.put_string(&self.title);
+        let n_bytes_name = hdr.key_len as u32 + buf.len() as u32;
+        let dir = Directory {
+            version: 5,
+            c_time: self.datime,
+            m_time: self.datime,
+            n_bytes_keys: n_bytes_keys as i32,
+            n_bytes_name: n_bytes_name as i32,
+            seek_dir: SeekFrom::Start(BEGIN),
+            seek_parent: SeekFrom::Start(0),
+            seek_keys: SeekFrom::Start(seek_keys),
+            uuid: self.uuid,
+        };
+        dir.stream(&mut buf);
+
+        hdr.set_sizes(buf.len(), buf.len());
+        let mut key = WBuffer::new(0);
+        hdr.stream(&mut key);
+        key.put_bytes(buf.as_slice());
+        if self.end == BEGIN {
+            self.n_bytes_name = n_bytes_name;
+            self.append(key.as_slice())
+        } else {
+            self.writer.seek(SeekFrom::Start(BEGIN))?;
+            self.writer.write_all(key.as_slice())?;
+            self.writer.seek(SeekFrom::Start(self.end))?;
+            Ok(())
+        }
+    }
+
+    /// Append the given bytes at the end of the file
+    pub(crate) fn append(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if self.end + bytes.len() as u64 > i32::MAX as u64 {
+            return Err(format_err!("Files larger than 2GB are not supported"));
+        }
+        self.writer.write_all(bytes)?;
+        self.end += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+fn seek(s: &SeekFrom) -> u64 {
+    match *s {
+        SeekFrom::Start(v) => v,
+        _ => unreachable!("Seek points are always absolute"),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use nom::IResult;
+    use super::*;
+
+    fn tmp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("root-io-{}-{}", ::std::process::id(), name))
+    }
+
+    #[test]
+    fn write_and_read_tnamed() {
+        let path = tmp_path("tnamed.root");
+        let named = TNamed {name: "some name".to_string(), title: "some title".to_string()};
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.write_object("a", "first", &named).unwrap();
+            f.write_object("b", "", &named).unwrap();
+            f.write_object("a", "second", &named).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&path).unwrap();
+        let items = f.items();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2].name(), "`a` of type `TNamed`");
+        assert_eq!(items[2].tkey_hdr.cycle, 2);
+        for item in items {
+            let n = item.parse_with(|i, _| tnamed(i)).unwrap();
+            assert_eq!(n.name, named.name);
+            assert_eq!(n.title, named.title);
+        }
+        let mut streamers: Vec<_> = f.streamers().unwrap().iter()
+            .map(|s| s.name().to_string())
+            .collect();
+        streamers.sort();
+        assert_eq!(streamers, vec!["TNamed", "TObject"]);
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keys_list_is_uncompressed() {
+        let path = tmp_path("keys.root");
+        let named = TNamed {name: "name".to_string(), title: "title".to_string()};
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.set_compression(9);
+            for i in 0..20 {
+                f.write_object(&format!("obj{}", i), "", &named).unwrap();
+            }
+            f.close().unwrap();
+        }
+        let bytes = ::std::fs::read(&path).unwrap();
+        // The list of keys is the first key of class `TFile` after the
+        // one of the top directory
+        let mut pos = BEGIN as usize;
+        let keys_hdr = loop {
+            let hdr = match tkey_header(&bytes[pos..]) {
+                IResult::Done(_, hdr) => hdr,
+                _ => panic!("Expected a key header at {}", pos),
+            };
+            if hdr.class_name == "TFile" && pos != BEGIN as usize {
+                break hdr;
+            }
+            pos += hdr.total_size as usize;
+        };
+        let payload = &bytes[pos + keys_hdr.key_len as usize..pos + keys_hdr.total_size as usize];
+        assert_eq!(payload.len(), keys_hdr.uncomp_len as usize);
+        assert!(!payload.starts_with(b"ZL"));
+        assert_eq!(&payload[..4], &[0, 0, 0, 20]);
+        assert_eq!(RootFile::new_from_file(&path).unwrap().items().len(), 20);
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn write_large_compressed_list() {
+        let path = tmp_path("tlist.root");
+        let inner: Vec<_> = (0..1000)
+            .map(|i| {
+                let named = TNamed {name: format!("name {}", i), title: "title".to_string()};
+                let mut buf = WBuffer::new(0);
+                named.stream(&mut buf);
+                // Strip the byte count
+                buf.as_slice()[4..].to_vec()
+            })
+            .collect();
+        let list = TList {
+            ver: 5,
+            tobj: TObject::default(),
+            name: "".to_string(),
+            len: inner.len(),
+            objs: inner.iter()
+                .map(|obj| Raw {classinfo: "TNamed".to_string(), obj})
+                .collect(),
+        };
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.set_compression(9);
+            f.write_object("list", "", &list).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&path).unwrap();
+        assert!(f.items()[0].tkey_hdr.total_size < f.items()[0].tkey_hdr.uncomp_len);
+        let names = f.items()[0].parse_with(|i, c| {
+            map!(i, apply!(tlist, c), |l| {
+                l.objs.iter()
+                    .map(|raw| match tnamed(raw.obj) {
+                        IResult::Done(_, n) => n.name,
+                        _ => panic!("Expected TNamed"),
+                    })
+                    .collect::<Vec<_>>()
+            })
+        }).unwrap();
+        assert_eq!(names.len(), 1000);
+        assert_eq!(names[999], "name 999");
+        assert!(f.streamers().unwrap().iter().any(|s| s.name() == "TList"));
+        ::std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,361 @@
+//! `TStreamerInfo`s of the classes which can be written by this
+//! crate. Every ROOT file contains a description of the classes
+//! stored in it. The descriptions here are equivalent to those
+//! written by ROOT 6 itself.
+
+use ::core::*;
+use ::core::TStreamer as S;
+
+/// Checksum of `TSeqCollection`; ROOT does not compute it from its
+/// (empty) list of members
+const TSEQCOLLECTION_CHECKSUM: u32 = 4_234_951_622;
+
+fn p(id: i32) -> PrimitiveID {
+    PrimitiveID(id)
+}
+
+/// A base class; its version and checksum are taken from its own `TStreamerInfo`
+fn base(name: &str, title: &str) -> TStreamer {
+    let info = streamer_info(name)
+        .unwrap_or_else(|| panic!("No streamer info for base class {}", name));
+    S::base(name, title, info.version() as i32, info.checksum())
+}
+
+/// One of the `TLeaf` subclasses of primitive types such as `TLeafF`
+fn leaf(name: &str, id: PrimitiveID, type_name: &str) -> TStreamerInfo {
+    TStreamerInfo::new(name, "", 1, vec![
+        base("TLeaf", "Leaf: description of a Branch data type"),
+        S::basic("fMinimum", "Minimum value if leaf range is specified", id.clone(), type_name),
+        S::basic("fMaximum", "Maximum value if leaf range is specified", id, type_name),
+    ])
+}
+
+/// The `TStreamerInfo` of the class `name` if it is known
+pub(crate) fn streamer_info(name: &str) -> Option<TStreamerInfo> {
+    let info = match name {
+        "TObject" => TStreamerInfo::new(name, "", 1, vec![
+            S::basic("fUniqueID", "object unique identifier", p(13), "unsigned int"),
+            S::basic("fBits", "bit field status word", p(15), "unsigned int"),
+        ]),
+        "TNamed" => TStreamerInfo::new(name, "", 1, vec![
+            base("TObject", "Basic ROOT object"),
+            S::string("fName", "object identifier"),
+            S::string("fTitle", "object title"),
+        ]),
+        "TObjString" => TStreamerInfo::new(name, "", 1, vec![
+            base("TObject", "Basic ROOT object"),
+            S::string("fString", "wrapped TString"),
+        ]),
+        "TCollection" => TStreamerInfo::new(name, "", 3, vec![
+            base("TObject", "Basic ROOT object"),
+            S::string("fName", "name of the collection"),
+            S::basic("fSize", "number of elements in collection", p(3), "int"),
+        ]),
+        "TSeqCollection" => TStreamerInfo::new(name, "", 0, vec![
+            base("TCollection", "Collection abstract base class"),
+        ]).with_checksum(TSEQCOLLECTION_CHECKSUM),
+        "TList" => TStreamerInfo::new(name, "", 5, vec![
+            base("TSeqCollection", "Sequenceable collection ABC"),
+        ]),
+        "TObjArray" => TStreamerInfo::new(name, "", 3, vec![
+            base("TSeqCollection", "Sequenceable collection ABC"),
+            S::basic("fLowerBound", "Lower bound of the array", p(3), "int"),
+            S::basic("fLast", "Last element in array containing an object", p(3), "int"),
+        ]),
+        "TAttLine" => TStreamerInfo::new(name, "", 2, vec![
+            S::basic("fLineColor", "Line color", p(2), "short"),
+            S::basic("fLineStyle", "Line style", p(2), "short"),
+            S::basic("fLineWidth", "Line width", p(2), "short"),
+        ]),
+        "TAttFill" => TStreamerInfo::new(name, "", 2, vec![
+            S::basic("fFillColor", "Fill area color", p(2), "short"),
+            S::basic("fFillStyle", "Fill area style", p(2), "short"),
+        ]),
+        "TAttMarker" => TStreamerInfo::new(name, "", 2, vec![
+            S::basic("fMarkerColor", "Marker color", p(2), "short"),
+            S::basic("fMarkerStyle", "Marker style", p(2), "short"),
+            S::basic("fMarkerSize", "Marker size", p(5), "float"),
+        ]),
+        "TTree" => TStreamerInfo::new(name, "", 19, vec![
+            base("TNamed", "The basis for a named object (name, title)"),
+            base("TAttLine", "Line attributes"),
+            base("TAttFill", "Fill area attributes"),
+            base("TAttMarker", "Marker attributes"),
+            S::basic("fEntries", "Number of entries", p(16), "Long64_t"),
+            S::basic("fTotBytes", "Total number of bytes in all branches before compression",
+                     p(16), "Long64_t"),
+            S::basic("fZipBytes", "Total number of bytes in all branches after compression",
+                     p(16), "Long64_t"),
+            S::basic("fSavedBytes", "Number of autosaved bytes", p(16), "Long64_t"),
+            S::basic("fFlushedBytes", "Number of auto-flushed bytes", p(16), "Long64_t"),
+            S::basic("fWeight", "Tree weight (see TTree::SetWeight)", p(8), "double"),
+            S::basic("fTimerInterval", "Timer interval in milliseconds", p(3), "int"),
+            S::basic("fScanField", "Number of runs before prompting in Scan", p(3), "int"),
+            S::basic("fUpdate", "Update frequency for EntryLoop", p(3), "int"),
+            S::basic("fDefaultEntryOffsetLen",
+                     "Initial Length of fEntryOffset table in the basket buffers", p(3), "int"),
+            S::basic("fNClusterRange",
+                     "Number of Cluster range in addition to the one defined by 'AutoFlush'",
+                     p(6), "int"),
+            S::basic("fMaxEntries", "Maximum number of entries in case of circular buffers",
+                     p(16), "Long64_t"),
+            S::basic("fMaxEntryLoop", "Maximum number of entries to process", p(16), "Long64_t"),
+            S::basic("fMaxVirtualSize", "Maximum total size of buffers kept in memory",
+                     p(16), "Long64_t"),
+            S::basic("fAutoSave",
+                     "Autosave tree when fAutoSave entries written or -fAutoSave (compressed) bytes produced",
+                     p(16), "Long64_t"),
+            S::basic("fAutoFlush",
+                     "Auto-flush tree when fAutoFlush entries written or -fAutoFlush (compressed) bytes produced",
+                     p(16), "Long64_t"),
+            S::basic("fEstimate", "Number of entries to estimate histogram limits", p(16), "Long64_t"),
+            S::basic_pointer("fClusterRangeEnd", "[fNClusterRange] Last entry of a cluster range.",
+                             p(16), "Long64_t*", "fNClusterRange", "TTree", 19),
+            S::basic_pointer("fClusterSize",
+                             "[fNClusterRange] Number of entries in each cluster for a given range.",
+                             p(16), "Long64_t*", "fNClusterRange", "TTree", 19),
+            S::object("fBranches", "List of Branches", "TObjArray", 64),
+            S::object("fLeaves", "Direct pointers to individual branch leaves", "TObjArray", 64),
+            S::object_pointer("fAliases",
+                              "List of aliases for expressions based on the tree branches.",
+                              "TList*"),
+            S::object_any("fIndexValues", "Sorted index values", "TArrayD", 24),
+            S::object_any("fIndex", "Index of sorted values", "TArrayI", 24),
+            S::object_pointer("fTreeIndex", "Pointer to the tree Index (if any)", "TVirtualIndex*"),
+            S::object_pointer("fFriends", "pointer to list of friend elements", "TList*"),
+            S::object_pointer("fUserInfo", "pointer to a list of user objects associated to this Tree",
+                              "TList*"),
+            S::object_pointer("fBranchRef", "Branch supporting the TRefTable (if any)", "TBranchRef*"),
+        ]),
+        "TBranch" => TStreamerInfo::new(name, "", 12, vec![
+            base("TNamed", "The basis for a named object (name, title)"),
+            base("TAttFill", "Fill area attributes"),
+            S::basic("fCompress", "Compression level and algorithm", p(3), "int"),
+            S::basic("fBasketSize", "Initial Size of  Basket Buffer", p(3), "int"),
+            S::basic("fEntryOffsetLen", "Initial Length of fEntryOffset table in the basket buffers",
+                     p(3), "int"),
+            S::basic("fWriteBasket", "Last basket number written", p(3), "int"),
+            S::basic("fEntryNumber", "Current entry number (last one filled in this branch)",
+                     p(16), "Long64_t"),
+            S::basic("fOffset", "Offset of this branch", p(3), "int"),
+            S::basic("fMaxBaskets", "Maximum number of Baskets so far", p(6), "int"),
+            S::basic("fSplitLevel", "Branch split level", p(3), "int"),
+            S::basic("fEntries", "Number of entries", p(16), "Long64_t"),
+            S::basic("fFirstEntry", "Number of the first entry in this branch", p(16), "Long64_t"),
+            S::basic("fTotBytes", "Total number of bytes in all leaves before compression",
+                     p(16), "Long64_t"),
+            S::basic("fZipBytes", "Total number of bytes in all leaves after compression",
+                     p(16), "Long64_t"),
+            S::object("fBranches", "-> List of Branches of this branch", "TObjArray", 64),
+            S::object("fLeaves", "-> List of leaves of this branch", "TObjArray", 64),
+            S::object("fBaskets", "-> List of baskets of this branch", "TObjArray", 64),
+            S::basic_pointer("fBasketBytes", "[fMaxBaskets] Length of baskets on file",
+                             p(3), "int*", "fMaxBaskets", "TBranch", 12),
+            S::basic_pointer("fBasketEntry", "[fMaxBaskets] Table of first entry in each basket",
+                             p(16), "Long64_t*", "fMaxBaskets", "TBranch", 12),
+            S::basic_pointer("fBasketSeek", "[fMaxBaskets] Addresses of baskets on file",
+                             p(16), "Long64_t*", "fMaxBaskets", "TBranch", 12),
+            S::string("fFileName",
+                      "Name of file where buffers are stored (\"\" if in same file as Tree header)"),
+        ]),
+        "TLeaf" => TStreamerInfo::new(name, "", 2, vec![
+            base("TNamed", "The basis for a named object (name, title)"),
+            S::basic("fLen", "Number of fixed length elements", p(3), "int"),
+            S::basic("fLenType", "Number of bytes for this data type", p(3), "int"),
+            S::basic("fOffset", "Offset in ClonesArray object (if one)", p(3), "int"),
+            S::basic("fIsRange", "(=kTRUE if leaf has a range, kFALSE otherwise)", p(18), "bool"),
+            S::basic("fIsUnsigned", "(=kTRUE if unsigned, kFALSE otherwise)", p(18), "bool"),
+            S::object_pointer("fLeafCount",
+                              "Pointer to Leaf count if variable length (we do not own the counter)",
+                              "TLeaf*"),
+        ]),
+        "TLeafB" => leaf(name, p(1), "char"),
+        "TLeafS" => leaf(name, p(2), "short"),
+        "TLeafI" => leaf(name, p(3), "int"),
+        "TLeafL" => leaf(name, p(16), "Long64_t"),
+        "TLeafF" => leaf(name, p(5), "float"),
+        "TLeafD" => leaf(name, p(8), "double"),
+        "TLeafO" => leaf(name, p(18), "bool"),
+        "TArray" => TStreamerInfo::new(name, "", 1, vec![
+            S::basic("fN", "Number of array elements", p(3), "int"),
+        ]),
+        "TArrayD" => TStreamerInfo::new(name, "", 1, vec![
+            base("TArray", "Abstract array base class"),
+            S::basic_pointer("fArray", "[fN] Array of fN doubles", p(8), "double*", "fN", "TArrayD", 1),
+        ]),
+        "TAttAxis" => TStreamerInfo::new(name, "", 4, vec![
+            S::basic("fNdivisions", "Number of divisions(10000*n3 + 100*n2 + n1)", p(3), "int"),
+            S::basic("fAxisColor", "Color of the line axis", p(2), "short"),
+            S::basic("fLabelColor", "Color of labels", p(2), "short"),
+            S::basic("fLabelFont", "Font for labels", p(2), "short"),
+            S::basic("fLabelOffset", "Offset of labels", p(5), "float"),
+            S::basic("fLabelSize", "Size of labels", p(5), "float"),
+            S::basic("fTickLength", "Length of tick marks", p(5), "float"),
+            S::basic("fTitleOffset", "Offset of axis title", p(5), "float"),
+            S::basic("fTitleSize", "Size of axis title", p(5), "float"),
+            S::basic("fTitleColor", "Color of axis title", p(2), "short"),
+            S::basic("fTitleFont", "Font for axis title", p(2), "short"),
+        ]),
+        "TAxis" => TStreamerInfo::new(name, "", 10, vec![
+            base("TNamed", "The basis for a named object (name, title)"),
+            base("TAttAxis", "Axis attributes"),
+            S::basic("fNbins", "Number of bins", p(3), "int"),
+            S::basic("fXmin", "low edge of first bin", p(8), "double"),
+            S::basic("fXmax", "upper edge of last bin", p(8), "double"),
+            S::object_any("fXbins", "Bin edges array in X", "TArrayD", 24),
+            S::basic("fFirst", "first bin to display", p(3), "int"),
+            S::basic("fLast", "last bin to display", p(3), "int"),
+            S::basic("fBits2", "second bit status word", p(12), "unsigned short"),
+            S::basic("fTimeDisplay", "on/off displaying time values instead of numerics",
+                     p(18), "bool"),
+            S::string("fTimeFormat", "Date&time format, ex: 09/12/99 12:34:00"),
+            S::object_pointer("fLabels", "List of labels", "THashList*"),
+            S::object_pointer("fModLabs", "List of modified labels", "TList*"),
+        ]),
+        "TAtt3D" => TStreamerInfo::new(name, "", 1, vec![]),
+        "TH1" => TStreamerInfo::new(name, "", 8, vec![
+            base("TNamed", "The basis for a named object (name, title)"),
+            base("TAttLine", "Line attributes"),
+            base("TAttFill", "Fill area attributes"),
+            base("TAttMarker", "Marker attributes"),
+            S::basic("fNcells", "number of bins(1D), cells (2D) +U/Overflows", p(3), "int"),
+            S::object("fXaxis", "X axis descriptor", "TAxis", 216),
+            S::object("fYaxis", "Y axis descriptor", "TAxis", 216),
+            S::object("fZaxis", "Z axis descriptor", "TAxis", 216),
+            S::basic("fBarOffset", "(1000*offset) for bar charts or legos", p(2), "short"),
+            S::basic("fBarWidth", "(1000*width) for bar charts or legos", p(2), "short"),
+            S::basic("fEntries", "Number of entries", p(8), "double"),
+            S::basic("fTsumw", "Total Sum of weights", p(8), "double"),
+            S::basic("fTsumw2", "Total Sum of squares of weights", p(8), "double"),
+            S::basic("fTsumwx", "Total Sum of weight*X", p(8), "double"),
+            S::basic("fTsumwx2", "Total Sum of weight*X*X", p(8), "double"),
+            S::basic("fMaximum", "Maximum value for plotting", p(8), "double"),
+            S::basic("fMinimum", "Minimum value for plotting", p(8), "double"),
+            S::basic("fNormFactor", "Normalization factor", p(8), "double"),
+            S::object_any("fContour", "Array to display contour levels", "TArrayD", 24),
+            S::object_any("fSumw2", "Array of sum of squares of weights", "TArrayD", 24),
+            S::string("fOption", "histogram options"),
+            S::object_pointer("fFunctions", "->Pointer to list of functions (fits and user)",
+                              "TList*"),
+            S::basic("fBufferSize", "fBuffer size", p(3), "int"),
+            S::basic_pointer("fBuffer", "[fBufferSize] entry buffer", p(8), "double*",
+                             "fBufferSize", "TH1", 8),
+            S::basic("fBinStatErrOpt", "option for bin statistical errors", p(3),
+                     "TH1::EBinErrorOpt"),
+            S::basic("fStatOverflows", "per object flag to use under/overflows in statistics",
+                     p(3), "TH1::EStatOverflows"),
+        ]),
+        "TH2" => TStreamerInfo::new(name, "", 5, vec![
+            base("TH1", "1-Dim histogram base class"),
+            S::basic("fScalefactor", "Scale factor", p(8), "double"),
+            S::basic("fTsumwy", "Total Sum of weight*Y", p(8), "double"),
+            S::basic("fTsumwy2", "Total Sum of weight*Y*Y", p(8), "double"),
+            S::basic("fTsumwxy", "Total Sum of weight*X*Y", p(8), "double"),
+        ]),
+        "TH3" => TStreamerInfo::new(name, "", 6, vec![
+            base("TH1", "1-Dim histogram base class"),
+            base("TAtt3D", "3D attributes"),
+            S::basic("fTsumwy", "Total Sum of weight*Y", p(8), "double"),
+            S::basic("fTsumwy2", "Total Sum of weight*Y*Y", p(8), "double"),
+            S::basic("fTsumwxy", "Total Sum of weight*X*Y", p(8), "double"),
+            S::basic("fTsumwz", "Total Sum of weight*Z", p(8), "double"),
+            S::basic("fTsumwz2", "Total Sum of weight*Z*Z", p(8), "double"),
+            S::basic("fTsumwxz", "Total Sum of weight*X*Z", p(8), "double"),
+            S::basic("fTsumwyz", "Total Sum of weight*Y*Z", p(8), "double"),
+        ]),
+        "TH1D" => TStreamerInfo::new(name, "", 3, vec![
+            base("TH1", "1-Dim histogram base class"),
+            base("TArrayD", "Array of doubles"),
+        ]),
+        "TH2D" => TStreamerInfo::new(name, "", 4, vec![
+            base("TH2", "2-Dim histogram base class"),
+            base("TArrayD", "Array of doubles"),
+        ]),
+        "TH3D" => TStreamerInfo::new(name, "", 4, vec![
+            base("TH3", "3-Dim histogram base class"),
+            base("TArrayD", "Array of doubles"),
+        ]),
+        _ => return None,
+    };
+    Some(info)
+}
+
+/// The `TStreamerInfo`s needed to describe the given classes. This
+/// includes the classes they depend on (e.g. base classes). Classes
+/// for which no `TStreamerInfo` is known are skipped.
+pub(crate) fn streamer_infos_for<'a, I>(classes: I) -> Vec<TStreamerInfo>
+    where I: IntoIterator<Item=&'a str>
+{
+    let mut infos: Vec<TStreamerInfo> = vec![];
+    let mut todo: Vec<String> = classes.into_iter().map(|s| s.to_string()).collect();
+    while let Some(name) = todo.pop() {
+        if infos.iter().any(|i| i.name() == name) {
+            continue;
+        }
+        if let Some(info) = streamer_info(&name) {
+            for m in info.data_members() {
+                match *m {
+                    S::Base{ref el, ..} => todo.push(el.name.name.clone()),
+                    S::Object{ref el} | S::ObjectAny{ref el} => todo.push(el.type_name.clone()),
+                    // Non-null pointers are streamed in place
+                    S::ObjectPointer{ref el} => if let TypeID::Objectp = el.el_type {
+                        todo.push(el.type_name.trim_end_matches('*').to_string())
+                    },
+                    _ => {},
+                }
+            }
+            infos.push(info);
+        }
+    }
+    infos
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use core::RootFile;
+    use super::*;
+
+    #[test]
+    fn checksums_match_root() {
+        // These are the checksums of the ROOT 6 classes
+        let checksums = [
+            ("TObject", 2_417_737_773), ("TNamed", 3_753_331_260),
+            ("TCollection", 1_474_546_588), ("TSeqCollection", 4_234_951_622),
+            ("TList", 1_774_568_379), ("TObjArray", 2_845_730_130),
+            ("TAttLine", 2_483_504_457), ("TAttFill", 4_292_422_290),
+            ("TAttMarker", 689_802_220), ("TTree", 1_487_116_011),
+            ("TBranch", 1_494_256_824), ("TLeaf", 1_830_715_730),
+            ("TLeafB", 253_643_614), ("TLeafS", 353_169_103), ("TLeafI", 2_120_920_601),
+            ("TLeafL", 3_727_820_898), ("TLeafF", 987_602_290), ("TLeafD", 294_553_462),
+            ("TLeafO", 44_976_339),
+        ];
+        for &(name, checksum) in checksums.iter() {
+            assert_eq!(streamer_info(name).unwrap().checksum(), checksum, "{}", name);
+        }
+    }
+
+    #[test]
+    fn known_streamers_agree_with_file() {
+        let path = PathBuf::from("./src/test_data/simple.root");
+        let f = RootFile::new_from_file(&path).unwrap();
+        let mut n_known = 0;
+        for info in f.streamers().unwrap() {
+            if let Some(known) = streamer_info(info.name()) {
+                n_known += 1;
+                assert_eq!(known.checksum(), info.checksum(), "{}", info.name());
+                assert_eq!(known.version(), info.version(), "{}", info.name());
+                assert_eq!(known.data_members().len(), info.data_members().len());
+            }
+        }
+        assert!(n_known > 0);
+    }
+
+    #[test]
+    fn dependencies_are_resolved() {
+        let infos = streamer_infos_for(vec!["TList"]);
+        let mut names: Vec<_> = infos.iter().map(|i| i.name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["TCollection", "TList", "TObject", "TSeqCollection"]);
+    }
+}
@@ -4,19 +4,32 @@
 
 pub mod types;
 pub mod parsers;
+pub mod writers;
 mod tstreamer;
 mod tstreamerinfo;
 mod file;
 mod tkey;
 mod typeid;
 mod file_item;
+mod file_writer;
+mod root_object;
+mod known_streamers;
+mod read_rule;
+mod class_registry;
 
 pub(crate) use self::tstreamer::{tstreamer, TStreamer};
 pub(crate) use self::tstreamerinfo::{tstreamerinfo, TStreamerInfo};
 pub(crate) use self::types::*;
 pub(crate) use self::parsers::*;
+pub(crate) use self::writers::*;
+pub(crate) use self::known_streamers::*;
 pub(crate) use self::tkey::*;
 pub(crate) use self::typeid::*;
+pub(crate) use self::root_object::root_object;
 
 pub use self::file::RootFile;
 pub use self::file_item::FileItem;
+pub use self::file_writer::RootFileWriter;
+pub use self::root_object::RootObject;
+pub use self::class_registry::{ClassRegistry, MemberSource};
+pub use self::read_rule::ReadRule;
@@ -13,7 +13,7 @@ use std::str;
 use std::io::Read;
 
 use failure::Error;
-use nom::{self, be_i32, be_u16, be_u32, be_u8, be_f64, rest};
+use nom::{self, be_i8, be_i16, be_i32, be_i64, be_u16, be_u32, be_u8, be_f32, be_f64, rest};
 use flate2::bufread::ZlibDecoder;
 use xz2::read::XzDecoder;
 
@@ -161,6 +161,31 @@ named!(
     pub tarrayd<&[u8], Vec<f64>>,
     length_count!(be_i32, be_f64)
 );
+named!(
+    #[doc="Parse a so-called `TArrayC`"],
+    pub tarrayc<&[u8], Vec<i8>>,
+    length_count!(be_i32, be_i8)
+);
+named!(
+    #[doc="Parse a so-called `TArrayS`"],
+    pub tarrays<&[u8], Vec<i16>>,
+    length_count!(be_i32, be_i16)
+);
+named!(
+    #[doc="Parse a so-called `TArrayL`"],
+    pub tarrayl<&[u8], Vec<i64>>,
+    length_count!(be_i32, be_i64)
+);
+named!(
+    #[doc="Parse a so-called `TArrayL64`"],
+    pub tarrayl64<&[u8], Vec<i64>>,
+    length_count!(be_i32, be_i64)
+);
+named!(
+    #[doc="Parse a so-called `TArrayF`"],
+    pub tarrayf<&[u8], Vec<f32>>,
+    length_count!(be_i32, be_f32)
+);
 
 
 fn decode_reader(bytes: &[u8], magic: &str) -> Result<Vec<u8>, Error> {
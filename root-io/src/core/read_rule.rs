@@ -0,0 +1,232 @@
+use failure::Error;
+
+/// Inclusive range of class versions; open ends are `None`
+type VersionRange = (Option<i32>, Option<i32>);
+
+/// A schema evolution rule describing how members of an object
+/// written with an older layout are read into the current one. These
+/// are ROOT's I/O customization rules of `type=read` as they are
+/// stored in the streamer info of a file; see `RootFile::read_rules`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadRule {
+    pub(crate) source_class: String,
+    pub(crate) target_class: String,
+    /// Inclusive ranges of the class versions to which this rule
+    /// applies; open ends are `None`
+    pub(crate) versions: Vec<VersionRange>,
+    /// Checksums of the layouts to which this rule applies
+    pub(crate) checksums: Vec<u32>,
+    /// Type and name of the members read from the streamed object
+    pub(crate) source: Vec<(String, String)>,
+    /// Members set by this rule
+    pub(crate) target: Vec<String>,
+    /// Headers needed by `code`
+    pub(crate) include: Vec<String>,
+    /// C++ code setting the `target` members
+    pub(crate) code: String,
+}
+
+/// Split a rule string such as `type=read sourceClass="TTree"
+/// code="{ fN = 0; }"` into its key-value pairs. Code in braces may
+/// contain quotes
+fn key_values(s: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut pairs = vec![];
+    let mut rest = s.trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or_else(|| format_err!("Expected `=` in rule {}", s))?;
+        let key = rest[..eq].trim().to_string();
+        rest = &rest[eq + 1..];
+        let (value, len) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = if quoted.starts_with('{') {
+                quoted.rfind("}\"").map(|i| i + 1)
+                    .ok_or_else(|| format_err!("Unterminated code in rule {}", s))?
+            } else {
+                quoted.find('"')
+                    .ok_or_else(|| format_err!("Unterminated value in rule {}", s))?
+            };
+            (&quoted[..end], end + 2)
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+        pairs.push((key, value.to_string()));
+        rest = rest[len..].trim_start();
+    }
+    Ok(pairs)
+}
+
+/// Parse a version range such as `[-16]`, `[3-5,8]`, or `[10-]`
+fn versions(s: &str) -> Result<Vec<VersionRange>, Error> {
+    let bound = |b: &str| -> Result<Option<i32>, Error> {
+        let b = b.trim();
+        if b.is_empty() { Ok(None) } else { Ok(Some(b.parse()?)) }
+    };
+    s.trim().trim_start_matches('[').trim_end_matches(']')
+        .split(',')
+        .filter(|range| !range.trim().is_empty())
+        .map(|range| match range.find('-') {
+            Some(i) => Ok((bound(&range[..i])?, bound(&range[i + 1..])?)),
+            None => {
+                let v = bound(range)?;
+                Ok((v, v))
+            },
+        })
+        .collect()
+}
+
+/// Parse a list of checksums such as `[1234, 5678]`
+fn checksums(s: &str) -> Result<Vec<u32>, Error> {
+    s.trim().trim_start_matches('[').trim_end_matches(']')
+        .split(',')
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty())
+        .map(|c| Ok(c.parse()?))
+        .collect()
+}
+
+impl ReadRule {
+    /// Parse a rule as it is stored in the `TObjString`s of a file's
+    /// streamer info. Returns `None` for rules which are not read rules
+    pub(crate) fn parse(s: &str) -> Result<Option<ReadRule>, Error> {
+        let pairs = key_values(s)?;
+        let get = |key: &str| pairs.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or("");
+        if get("type") != "read" {
+            return Ok(None);
+        }
+        let source = get("source").split(';')
+            .map(|m| m.trim())
+            .filter(|m| !m.is_empty())
+            .map(|m| match m.rfind(char::is_whitespace) {
+                Some(i) => (m[..i].trim().to_string(), m[i + 1..].to_string()),
+                None => (String::new(), m.to_string()),
+            })
+            .collect();
+        let target = get("target").split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|m| !m.is_empty())
+            .map(|m| m.to_string())
+            .collect();
+        Ok(Some(ReadRule {
+            source_class: get("sourceClass").to_string(),
+            target_class: get("targetClass").to_string(),
+            versions: versions(get("version"))?,
+            checksums: checksums(get("checksum"))?,
+            source,
+            target,
+            include: get("include").split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+                .filter(|i| !i.is_empty())
+                .map(|i| i.to_string())
+                .collect(),
+            code: get("code").to_string(),
+        }))
+    }
+
+    /// Name of the class as it was written
+    pub fn source_class(&self) -> &str {
+        &self.source_class
+    }
+
+    /// Name of the class which is read
+    pub fn target_class(&self) -> &str {
+        &self.target_class
+    }
+
+    /// Inclusive ranges of the versions of the source class to which
+    /// this rule applies. Open ends are `None`; e.g. `[-16]` is
+    /// `(None, Some(16))`. Empty if the rule is selected by checksum
+    pub fn versions(&self) -> &[VersionRange] {
+        &self.versions
+    }
+
+    /// Checksums of the layouts of the source class to which this
+    /// rule applies
+    pub fn checksums(&self) -> &[u32] {
+        &self.checksums
+    }
+
+    /// Type and name of the members of the streamed object which are
+    /// used by this rule
+    pub fn source(&self) -> &[(String, String)] {
+        &self.source
+    }
+
+    /// Names of the members of the target class set by this rule
+    pub fn target(&self) -> &[String] {
+        &self.target
+    }
+
+    /// Headers needed to compile `code`
+    pub fn include(&self) -> &[String] {
+        &self.include
+    }
+
+    /// The C++ code setting the target members. Members of the
+    /// streamed object are accessed as `onfile.<name>`
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Does this rule apply to objects of the source class written with `version`?
+    pub fn applies_to(&self, version: i32) -> bool {
+        // Rules without a version range or checksums apply to all versions
+        (self.versions.is_empty() && self.checksums.is_empty()) || self.versions.iter()
+            .any(|&(lo, hi)| lo.unwrap_or(i32::MIN) <= version && version <= hi.unwrap_or(i32::MAX))
+    }
+
+    /// If this rule merely copies the member `source` of the streamed
+    /// object into `target` (i.e. renames it), return the name of the
+    /// source member
+    pub fn renamed_from(&self, target: &str) -> Option<&str> {
+        let code = self.code.trim().trim_start_matches('{').trim_end_matches('}').trim();
+        let mut assignment = code.trim_end_matches(';').splitn(2, '=');
+        let lhs = assignment.next()?.trim();
+        let rhs = assignment.next()?.trim();
+        if lhs != target || !rhs.starts_with("onfile.") {
+            return None;
+        }
+        let src = &rhs["onfile.".len()..];
+        self.source.iter()
+            .map(|(_, name)| name.as_str())
+            .find(|name| *name == src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rules() {
+        let s = "type=read sourceClass=\"TTree\" targetClass=\"TTree\" version=\"[-16]\" \
+                 source=\"\" target=\"fDefaultEntryOffsetLen\" code=\"{ fDefaultEntryOffsetLen = 1000; }\" ";
+        let rule = ReadRule::parse(s).unwrap().unwrap();
+        assert_eq!(rule.source_class, "TTree");
+        assert_eq!(rule.versions, vec![(None, Some(16))]);
+        assert!(rule.source.is_empty());
+        assert_eq!(rule.target, vec!["fDefaultEntryOffsetLen"]);
+        assert_eq!(rule.code, "{ fDefaultEntryOffsetLen = 1000; }");
+        assert!(rule.applies_to(5));
+        assert!(!rule.applies_to(17));
+        assert_eq!(rule.renamed_from("fDefaultEntryOffsetLen"), None);
+
+        let s = "type=read sourceClass=\"A\" targetClass=\"A\" version=\"[2-3,5]\" \
+                 source=\"Int_t fOld\" target=\"fNew\" code=\"{ fNew = onfile.fOld; }\"";
+        let rule = ReadRule::parse(s).unwrap().unwrap();
+        assert_eq!(rule.versions, vec![(Some(2), Some(3)), (Some(5), Some(5))]);
+        assert_eq!(rule.source, vec![("Int_t".to_string(), "fOld".to_string())]);
+        assert!(rule.applies_to(3) && rule.applies_to(5) && !rule.applies_to(4));
+        assert_eq!(rule.renamed_from("fNew"), Some("fOld"));
+
+        let s = "type=read sourceClass=\"B\" targetClass=\"B\" checksum=\"[12, 34]\" \
+                 source=\"\" target=\"fX fY\" include=\"TMath.h\" code=\"{ fX = TMath::Pi(); fY = 0; }\"";
+        let rule = ReadRule::parse(s).unwrap().unwrap();
+        assert_eq!(rule.checksums(), &[12, 34]);
+        assert_eq!(rule.target(), &["fX".to_string(), "fY".to_string()]);
+        assert_eq!(rule.include(), &["TMath.h".to_string()]);
+        assert!(!rule.applies_to(1));
+
+        assert_eq!(ReadRule::parse("type=readraw sourceClass=\"A\"").unwrap(), None);
+    }
+}
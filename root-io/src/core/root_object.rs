@@ -0,0 +1,75 @@
+use nom::*;
+
+use core::*;
+use histograms::{tgraph, th1, tprofile, TGraph, TH1, TProfile};
+use tree_reader::{ttree, Tree};
+
+/// An object stored in a ROOT file as determined by its class name;
+/// see `FileItem::object`
+#[derive(Debug)]
+pub enum RootObject {
+    /// A `TTree`
+    Tree(Tree),
+    /// The items in a (sub-)directory
+    Directory(Vec<FileItem>),
+    /// A histogram (`TH1`, `TH2`, or `TH3` and their subclasses)
+    Histogram(TH1),
+    /// A `TProfile` or `TProfile2D`
+    Profile(TProfile),
+    /// A `TGraph`, `TGraphErrors`, or `TGraphAsymmErrors`
+    Graph(TGraph),
+    /// The string of a `TObjString`
+    ObjString(String),
+    /// The elements of a `TList`
+    List(Vec<RootObject>),
+    /// A `TNamed`
+    Named(TNamed),
+    /// An object of a class which cannot be parsed (yet)
+    Unknown {
+        class_name: String,
+        /// The streamed object starting at its version (i.e. without
+        /// the leading byte count)
+        data: Vec<u8>,
+        /// Where each member of the newest known layout of the class
+        /// comes from in `data`; see `ClassRegistry::evolve_object`.
+        /// Empty if the layout of `data` is not known
+        members: Vec<(String, MemberSource)>,
+    },
+}
+
+/// Parse an object of the given class into the matching `RootObject`.
+/// Directories are not streamed like other objects and are read by
+/// `FileItem::object` instead. The layouts in `registry` describe
+/// objects of unknown classes
+pub(crate) fn root_object<'s>(input: &'s [u8], context: &'s Context<'s>, class_name: &str,
+                              registry: &ClassRegistry)
+                              -> IResult<&'s [u8], RootObject> {
+    match class_name {
+        "TTree" => map!(input, apply!(ttree, context), RootObject::Tree),
+        "TProfile" | "TProfile2D" =>
+            map!(input, apply!(tprofile, context, class_name), RootObject::Profile),
+        "TGraph" | "TGraphErrors" | "TGraphAsymmErrors" =>
+            map!(input, apply!(tgraph, context, class_name), RootObject::Graph),
+        "TObjString" => map!(input, tobjstring, RootObject::ObjString),
+        "TNamed" => map!(input, tnamed, RootObject::Named),
+        "TList" => {
+            let (rest, list) = try_parse!(input, apply!(tlist, context));
+            let objs: Option<Vec<_>> = list.objs.iter()
+                .map(|raw| root_object(raw.obj, context, &raw.classinfo, registry).to_result().ok())
+                .collect();
+            match objs {
+                Some(objs) => IResult::Done(rest, RootObject::List(objs)),
+                None => IResult::Error(ErrorKind::Custom(0)),
+            }
+        },
+        _ if class_name.starts_with("TH1")
+            || class_name.starts_with("TH2")
+            || class_name.starts_with("TH3") =>
+            map!(input, apply!(th1, context, class_name), RootObject::Histogram),
+        _ => IResult::Done(&input[input.len()..], RootObject::Unknown {
+            class_name: class_name.to_string(),
+            data: input.to_vec(),
+            members: registry.evolve_object(class_name, input).unwrap_or_default(),
+        }),
+    }
+}
@@ -5,16 +5,16 @@ use ::core::*;
 #[derive(Debug, Clone)]
 pub struct TKeyHeader {
     pub(crate) total_size: u32,
-    version: u16,
+    pub(crate) version: u16,
     pub(crate) uncomp_len: u32,
-    datime: u32,
+    pub(crate) datime: u32,
     pub(crate) key_len: i16,
-    cycle: i16,
+    pub(crate) cycle: i16,
     pub(crate) seek_key: SeekFrom,
-    seek_pdir: SeekFrom,
+    pub(crate) seek_pdir: SeekFrom,
     pub(crate) class_name: String,
     pub(crate) obj_name: String,
-    obj_title: String,
+    pub(crate) obj_title: String,
 }
 
 /// A `TKey` wraps a streamed oject. The object is decompress when
@@ -89,3 +89,59 @@ named!(
 pub(crate) fn tkey_headers(input: &[u8]) -> IResult<&[u8], Vec<TKeyHeader>> {
     length_count!(input, be_i32, tkey_header)
 }
+
+impl TKeyHeader {
+    /// Header of a new key at `seek_key` in the directory at
+    /// `seek_pdir`. The sizes of the key's payload are still to be set
+    pub(crate) fn new(class_name: &str, obj_name: &str, obj_title: &str, cycle: i16,
+                      seek_key: u64, seek_pdir: u64, datime: u32) -> TKeyHeader {
+        let mut hdr = TKeyHeader {
+            total_size: 0,
+            version: 4,
+            uncomp_len: 0,
+            datime,
+            key_len: 0,
+            cycle,
+            seek_key: SeekFrom::Start(seek_key),
+            seek_pdir: SeekFrom::Start(seek_pdir),
+            class_name: class_name.to_string(),
+            obj_name: obj_name.to_string(),
+            obj_title: obj_title.to_string(),
+        };
+        let mut buf = WBuffer::new(0);
+        hdr.stream(&mut buf);
+        hdr.key_len = buf.len() as i16;
+        hdr
+    }
+
+    /// Set the size of the (possibly compressed) payload following
+    /// this key and the size of the uncompressed object
+    pub(crate) fn set_sizes(&mut self, n_bytes: usize, uncomp_len: usize) {
+        self.total_size = self.key_len as u32 + n_bytes as u32;
+        self.uncomp_len = uncomp_len as u32;
+    }
+
+    /// Write this header to the given buffer. Counterpart to the `tkey_header` parser
+    pub(crate) fn stream(&self, buf: &mut WBuffer) {
+        let seek = |s: &SeekFrom| match *s {
+            SeekFrom::Start(v) => v,
+            _ => unreachable!("Seek points are always absolute"),
+        };
+        buf.put_u32(self.total_size);
+        buf.put_u16(self.version);
+        buf.put_u32(self.uncomp_len);
+        buf.put_u32(self.datime);
+        buf.put_i16(self.key_len);
+        buf.put_i16(self.cycle);
+        if self.version > 1000 {
+            buf.put_u64(seek(&self.seek_key));
+            buf.put_u64(seek(&self.seek_pdir));
+        } else {
+            buf.put_u32(seek(&self.seek_key) as u32);
+            buf.put_u32(seek(&self.seek_pdir) as u32);
+        }
+        buf.put_string(&self.class_name);
+        buf.put_string(&self.obj_name);
+        buf.put_string(&self.obj_title);
+    }
+}
@@ -2,8 +2,8 @@ use quote::*;
 use nom::*;
 
 use ::core::*;
-use ::code_gen::utils::{sanitize, alias_or_lifetime, type_is_core};
-use ::code_gen::rust::{ToRustType, ToRustParser};
+use ::code_gen::utils::sanitize;
+use ::code_gen::rust::{Classes, ToRustType, ToRustParser};
 
 
 /// Union of all posible `TStreamers`. See figure at
@@ -63,25 +63,36 @@ pub(crate) enum TStreamer {
 	vtype: StlTypeID,
         /// STL contained type
 	ctype: TypeID,
-    }
+    },
+    /// A `TStreamer` of a class not listed above (e.g. `TStreamerArtificial`)
+    Unknown {
+        el: TStreamerElement,
+        /// Name of the `TStreamer` class
+        class_name: String,
+        /// The streamed object starting at its version
+        data: Vec<u8>,
+    },
 }
 
 
 /// Every `TStreamer` inherits from `TStreamerElement`
 #[derive(Debug)]
 pub(crate) struct TStreamerElement {
-    ver: u16,
-    name: TNamed,
-    el_type: TypeID,
-    size: i32,
-    array_len: i32,
-    array_dim: i32,
-    max_idx: Vec<u32>,
-    type_name: String,
-    // For ver == 3
-    // pub(crate) xmin: f32,
-    // pub(crate) xmax: f32,
-    // pub(crate) factor: f32,
+    pub(crate) ver: u16,
+    pub(crate) name: TNamed,
+    pub(crate) el_type: TypeID,
+    pub(crate) size: i32,
+    pub(crate) array_len: i32,
+    pub(crate) array_dim: i32,
+    pub(crate) max_idx: Vec<u32>,
+    pub(crate) type_name: String,
+    /// Lower limit of the range of a compressed float; only streamed
+    /// in version 3, later versions give it in the title
+    pub(crate) xmin: f64,
+    /// Upper limit of the range of a compressed float
+    pub(crate) xmax: f64,
+    /// Conversion factor of a compressed float
+    pub(crate) factor: f64,
 }
 
 
@@ -99,7 +110,19 @@ pub(crate) fn tstreamer<'c>(raw: &Raw<'c>) -> IResult<&'c[u8], TStreamer>
         "TStreamerBasicType" => do_parse!(raw.obj,
                                           _ver: be_u16 >>
                                           el: wrapped_tstreamerelem >>
-                                          (TStreamer::BasicType {el})),
+                                          ({
+                                              let mut el = el;
+                                              // Up to version 2, the size is the one of
+                                              // the type rather than of the member
+                                              let type_size = match el.el_type {
+                                                  TypeID::Primitive(ref id) => id.size(),
+                                                  _ => el.size,
+                                              };
+                                              if el.ver <= 2 && el.array_len > 0 {
+                                                  el.size = el.array_len * type_size;
+                                              }
+                                              TStreamer::BasicType {el}
+                                          })),
         "TStreamerBasicPointer" => do_parse!(raw.obj,
                                              _ver: be_u16 >>
                                              el: wrapped_tstreamerelem >>
@@ -141,14 +164,24 @@ pub(crate) fn tstreamer<'c>(raw: &Raw<'c>) -> IResult<&'c[u8], TStreamer>
                                     ctype: map_res!(be_i32, TypeID::new) >>
                                     (TStreamer::Stl {el, vtype, ctype})),
         "TStreamerSTLstring" => do_parse!(raw.obj,
-                                          // Two version bcs `stlstring` derives from `stl`
-                                          _ver: be_u16 >>
                                           _ver: be_u16 >>
-                                          el: wrapped_tstreamerelem >>
-                                          vtype: map!(be_i32, StlTypeID::new) >>
-                                          ctype: map_res!(be_i32, TypeID::new) >>
-                                          (TStreamer::StlString {el, vtype, ctype})),
-        ci => unimplemented!("Unknown TStreamer {}", ci)
+                                          // `TStreamerSTLstring` derives from `TStreamerSTL`
+                                          stl: length_value!(checked_byte_count,
+                                                             do_parse!(_ver: be_u16 >>
+                                                                       el: wrapped_tstreamerelem >>
+                                                                       vtype: map!(be_i32, StlTypeID::new) >>
+                                                                       ctype: map_res!(be_i32, TypeID::new) >>
+                                                                       (el, vtype, ctype))) >>
+                                          ({
+                                              let (el, vtype, ctype) = stl;
+                                              TStreamer::StlString {el, vtype, ctype}
+                                          })),
+        // Every `TStreamer` derives from `TStreamerElement`
+        ci => do_parse!(raw.obj,
+                        _ver: be_u16 >>
+                        el: wrapped_tstreamerelem >>
+                        _rest: rest >>
+                        (TStreamer::Unknown {el, class_name: ci.to_string(), data: raw.obj.to_vec()})),
     }
 }
 
@@ -166,14 +199,19 @@ named!(
                                1 => length_count!(be_i32, be_u32) |
                                _ => count!(be_u32, 5)) >>
               type_name: string >>
+              range: cond!(ver == 3, tuple!(be_f64, be_f64, be_f64)) >>
               _eof: eof!() >>
               ({
-                  if ver <= 3 {
-                      unimplemented!();
-                  }
+                  // Old versions used the type of `unsigned char` for `bool`
+                  let el_type = match (el_type, type_name.as_str()) {
+                      (TypeID::Primitive(PrimitiveID(11)), "Bool_t") | (TypeID::Primitive(PrimitiveID(11)), "bool") =>
+                          TypeID::Primitive(PrimitiveID(18)),
+                      (el_type, _) => el_type,
+                  };
+                  let (xmin, xmax, factor) = range.unwrap_or((0.0, 0.0, 0.0));
                   TStreamerElement {
                       ver, name, el_type, size, array_len,
-                      array_dim, max_idx, type_name
+                      array_dim, max_idx, type_name, xmin, xmax, factor
                   }
               })
     )
@@ -186,7 +224,7 @@ impl TStreamer {
         match self {
             &Base{ref el, ..} | &BasicType{ref el} | &BasicPointer{ref el, ..} | &Loop{ref el, ..}
             | &Object{ref el} | &ObjectPointer{ref el} | &ObjectAny{ref el} | &ObjectAnyPointer{ref el}
-            | &String{ref el} | &Stl{ref el, ..} | &StlString{ref el, ..} => el,
+            | &String{ref el} | &Stl{ref el, ..} | &StlString{ref el, ..} | &Unknown{ref el, ..} => el,
         }
     }
 
@@ -202,192 +240,383 @@ impl TStreamer {
     }
 }
 
-impl ToTokens for TStreamer {
-    /// Converts TStreamer to "\n///comment \n name: type"
-    fn to_tokens(&self, tokens: &mut Tokens) {
-        // insert a new line befor and after the comment!
-        tokens.append("\n/// ");
-        self.member_comment().to_tokens(tokens);
-        tokens.append("\n");
-        self.member_name().to_tokens(tokens);
-        tokens.append(": ");
-        self.type_name().to_tokens(tokens);
-    }
-}
-
-impl ToRustType for TStreamer {
-    fn type_name(&self) -> Tokens {
+impl TStreamer {
+    /// The class of the object described by this member if it is
+    /// streamed in place
+    fn class(&self) -> Option<&str> {
         use self::TypeID::*;
-        let name = Ident::new(alias_or_lifetime(&self.elem().name.name.to_owned()));
-        match self {
-            &TStreamer::Base {ref el, ..} => {
-                match el.el_type {
-                    Object | Base | Named | TObject => quote!{#name},
-                    // Not sure about the following branch...
-                    InvalidOrCounter(-1) => quote!{#name},
-                    _ => panic!("{:#?}", self),
-                }
-            },
-            &TStreamer::BasicType {ref el} => {
-                match el.el_type {
-                    Primitive(ref id) => id.type_name(),
-                    Offset(ref id) => {
-                        let s = Ident::new(format!("[{}; {}]", id.type_name().to_string(), el.array_len));
-                        quote!{#s}
-                    },
-                    _ => panic!("{:#?}", self),
-                }
+        match *self {
+            TStreamer::Base {ref el, ..} => match el.el_type {
+                Object | Base | Named | TObject => Some(&el.name.name),
+                _ => None,
             },
-            &TStreamer::BasicPointer {ref el, ..} => {
-                match el.el_type {
-                    Array(ref id) => {
-                        // Arrays are preceeded by a byte and then have a length given by a
-                        // previous member
-                        let s = Ident::new(format!("Vec<{}>", id.type_name().to_string()));
-                        quote!{#s}
-                    },
-                    _ => panic!("{:#?}", self),
-                }
+            TStreamer::Object {ref el} | TStreamer::ObjectAny {ref el} => match el.el_type {
+                Object | Any => Some(&el.type_name),
+                _ => None,
             },
-            &TStreamer::Object {ref el} => {
-                match el.el_type {
-                    Object => quote!{#name},
-                    _ => panic!("{:#?}", self),
-                }
+            // Pointers which are never null (`//->`)
+            TStreamer::ObjectPointer {ref el} | TStreamer::ObjectAnyPointer {ref el} => match el.el_type {
+                Objectp | Unknown(68) => Some(el.type_name.trim_end_matches('*')),
+                _ => None,
             },
-            &TStreamer::ObjectPointer {ref el} => {
-                match el.el_type {
-                    // Pointers may be null!
-                    ObjectP => quote!{Option<#name>},
-                    _ => panic!("{:#?}", self),
-                }
+            _ => None,
+        }
+    }
+
+    /// Is this member a pointer which may be null? Such pointers are
+    /// streamed with a class tag and are kept as `Raw` objects
+    pub(crate) fn is_tagged_pointer(&self) -> bool {
+        use self::TypeID::*;
+        match *self {
+            TStreamer::ObjectPointer {ref el} | TStreamer::ObjectAnyPointer {ref el} =>
+                matches!(el.el_type, ObjectP | AnyP),
+            _ => false,
+        }
+    }
+
+    /// Does the Rust type of this member borrow from the parsed buffer?
+    pub(crate) fn needs_lifetime(&self, classes: &Classes) -> bool {
+        self.is_tagged_pointer() || self.class().is_some_and(|c| classes.needs_lifetime(c))
+    }
+
+    /// The field of the generated struct holding this member
+    pub(crate) fn to_field(&self, classes: &Classes) -> Tokens {
+        let doc = self.elem().name.title.trim();
+        let name = self.member_name();
+        let t = self.field_type(classes);
+        quote!{
+            #[doc = #doc]
+            pub #name: #t
+        }
+    }
+
+    /// The Rust type of this member
+    pub(crate) fn field_type(&self, classes: &Classes) -> Tokens {
+        use self::TypeID::*;
+        if self.is_tagged_pointer() {
+            return quote!{Option<Raw<'s>>};
+        }
+        if let Some(class) = self.class() {
+            return classes.type_of(class);
+        }
+        let bytes = quote!{Vec<u8>};
+        match *self {
+            TStreamer::Base {ref el, ..} => match el.el_type {
+                InvalidOrCounter(-1) => bytes,
+                _ => panic!("{:#?}", self),
             },
-            &TStreamer::ObjectAny {ref el} | &TStreamer::ObjectAnyPointer {ref el} => {
-                match el.el_type {
-                    Any => quote!{#name},
-                    AnyP => quote!{#name},
-                    // No idea what this is; probably an array of custom type? Found in AliESDs
-                    Unknown(82) => quote!{Vec<u8>},
-                    _ => panic!("{:#?}", self),
-                }
+            TStreamer::BasicType {ref el} => match el.el_type {
+                Primitive(ref id) => id.type_name(),
+                Offset(ref id) => {
+                    let t = id.type_name();
+                    quote!{Vec<#t>}
+                },
+                _ => panic!("{:#?}", self),
             },
-            &TStreamer::String {ref el} => {
-                match el.el_type {
-                    String => quote!{String},
-                    _ => panic!("{:#?}", self),
-                }
+            TStreamer::BasicPointer {ref el, ..} => match el.el_type {
+                Array(ref id) => {
+                    let t = id.type_name();
+                    quote!{Vec<#t>}
+                },
+                _ => panic!("{:#?}", self),
             },
-            &TStreamer::Stl {ref vtype, ..} => {
-                match vtype {
-                    &StlTypeID::Vector => {
-                        quote!{Stl_vec}
-                    },
-                    &StlTypeID::Bitset => {
-                        quote!{Stl_bitset}
-                    }
-                }
+            TStreamer::Loop {..} | TStreamer::Unknown {..} => bytes,
+            // No idea what this is; probably an array of custom type? Found in AliESDs
+            TStreamer::ObjectAny {ref el} if el.el_type.code() == 82 => bytes,
+            TStreamer::String {..} | TStreamer::StlString {..} => quote!{String},
+            TStreamer::Stl {ref vtype, ref ctype, ..} => match (vtype, ctype) {
+                (StlTypeID::Vector, Primitive(id)) => {
+                    let t = id.type_name();
+                    quote!{Vec<#t>}
+                },
+                _ => bytes,
             },
             _ => panic!("{:#?}", self),
         }
     }
-}
 
-impl ToRustParser for TStreamer {
-    fn to_inline_parser(&self) -> Tokens {
+    /// Parser of this member as a member of the given class
+    pub(crate) fn field_parser(&self, class: &str, classes: &Classes) -> Tokens {
         use self::TypeID::*;
-        let name = match self {
-            //  `Base` types, i.e. types from which the current object inherited;
-            // In that case the name is actually the type
-            &TStreamer::Base{..} => &self.elem().name.name,
-            _ => &self.elem().type_name,
-        };
-        // Most core-types do not need the context, but some do
-        let name =
-            if type_is_core(name.as_str()) && name != "TObjArray" {
-                name.to_lowercase()
-            } else {
-                format!("apply!({}, &context)", name.to_lowercase())
-            };
-        
-        let name = Ident::new(name);
-
-        match self {
-            &TStreamer::Base {ref el, ..} => {
-                match el.el_type {
-                    Object | Base | Named => quote!{length_value!(checked_byte_count, #name)},
-                    TObject => quote!{#name},
-                    InvalidOrCounter(-1) => {
-                        let size = el.size;
-                        quote!{map!(take!(#size), |v| v.to_vec())}
-                    },
-                    _ => panic!("{:#?}", self),
-                }
+        if self.is_tagged_pointer() {
+            return quote!{switch!(peek!(be_u32),
+                                  0 => map!(call!(be_u32), |_| None) |
+                                  _ => map!(call!(_curried_raw), Some))};
+        }
+        if let Some(class) = self.class() {
+            return classes.parser_of(class);
+        }
+        let bytes = quote!{map!(length_data!(checked_byte_count), |v| v.to_vec())};
+        match *self {
+            TStreamer::Base {ref el, ..} => match el.el_type {
+                InvalidOrCounter(-1) => {
+                    let size = el.size as usize;
+                    quote!{map!(take!(#size), |v| v.to_vec())}
+                },
+                _ => panic!("{:#?}", self),
             },
-            &TStreamer::BasicType {ref el} => {
-                match el.el_type {
-                    Primitive(ref id) => id.to_inline_parser(),
-                    // Offsets are floating points with a custom mantissa
-                    // By default, parse as Vec<u8>
-                    Offset(_) => {
-                        let size = el.size;
-                        quote!{map!(take!(#size), |v| v.to_vec())}
-                    },
-                    _ => panic!("{:#?}", self),
-                }
+            TStreamer::BasicType {ref el} => match el.el_type {
+                Primitive(ref id) => id.to_inline_parser(),
+                Offset(ref id) => {
+                    let p = id.to_inline_parser();
+                    let len = el.array_len as usize;
+                    quote!{count!(#p, #len)}
+                },
+                _ => panic!("{:#?}", self),
             },
-            &TStreamer::BasicPointer {ref el, ref cname, ..} => {
-                let n_entries_array = Ident::new(cname.to_lowercase());
+            TStreamer::BasicPointer {ref el, ref cname, ..} => {
+                // The counter may be a member of a base class
+                let counter = classes.member_path(class, cname)
+                    .map(|path| path.iter().map(|p| p.to_string()).collect::<Vec<_>>().join("."))
+                    .unwrap_or_else(|| sanitize(&cname.to_lowercase()));
+                let counter = Ident::new(counter);
                 match el.el_type {
                     Array(ref id) => {
                         // Arrays are preceeded by a byte and then have a length given by a
                         // previous member
-                        let b_par = id.to_inline_parser();
-                        quote!{preceded!(be_u8, count!(#b_par, #n_entries_array as usize))}
+                        let p = id.to_inline_parser();
+                        quote!{preceded!(be_u8, count!(#p, #counter as usize))}
                     },
                     _ => panic!("{:#?}", self),
                 }
             },
-            &TStreamer::Object {ref el} => {
-                match el.el_type {
-                    Object => quote!{length_value!(checked_byte_count, #name)},
-                    _ => panic!("{:#?}", self),
-                }
-            },
-            &TStreamer::ObjectPointer {ref el} => {
-                match el.el_type {
-                    // Pointers may be null!
-                    ObjectP => quote!{switch!(peek!(be_u32),
-                                      0 => map!(call!(be_u32), |_| None) |
-                                      _ => map!(call!(_curried_raw), Some))},
-                    _ => panic!("{:#?}", self),
-                }
-            },
-            &TStreamer::ObjectAny {ref el} | &TStreamer::ObjectAnyPointer {ref el} => {
-                match el.el_type {
-                    Any => quote!{#name},
-                    AnyP => quote!{#name},
-                    // No idea what this is; probably an array of custom type? Found in AliESDs
-                    Unknown(82) => quote!{map!(eof!(), |o| o.to_vec())},
-                    _ => panic!("{:#?}", self),
-                }
-            },
-            &TStreamer::String {ref el} => {
-                match el.el_type {
-                    String => quote!{string},
-                    _ => panic!("{:#?}", self),
-                }
-            },
-            &TStreamer::Stl {ref vtype, ..} => {
-                match vtype {
-                    &StlTypeID::Vector => {
-                        quote!{stl_vec}
-                    },
-                    &StlTypeID::Bitset => {
-                        quote!{stl_bitset}
-                    }
-                }
+            // Not parsed, yet
+            TStreamer::Loop {..} | TStreamer::Unknown {..} => bytes,
+            TStreamer::ObjectAny {ref el} if el.el_type.code() == 82 => quote!{map!(eof!(), |o| o.to_vec())},
+            TStreamer::String {..} => quote!{string},
+            TStreamer::StlString {..} => quote!{length_value!(checked_byte_count, preceded!(be_u16, string))},
+            TStreamer::Stl {ref vtype, ref ctype, ..} => match (vtype, ctype) {
+                (StlTypeID::Vector, Primitive(id)) => {
+                    let p = id.to_inline_parser();
+                    quote!{length_value!(checked_byte_count, preceded!(be_u16, length_count!(be_i32, #p)))}
+                },
+                _ => bytes,
             },
             _ => panic!("{:#?}", self),
         }
     }
 }
+
+impl TStreamerElement {
+    /// A new element of the given type. The size is derived from the
+    /// type where possible
+    fn new(name: &str, title: &str, el_type: TypeID, size: i32, type_name: &str) -> TStreamerElement {
+        TStreamerElement {
+            ver: 4,
+            name: TNamed {name: name.to_string(), title: title.to_string()},
+            el_type,
+            size,
+            array_len: 0,
+            array_dim: 0,
+            max_idx: vec![0; 5],
+            type_name: type_name.to_string(),
+            xmin: 0.0,
+            xmax: 0.0,
+            factor: 0.0,
+        }
+    }
+}
+
+/// Constructors of `TStreamer`s which are used to describe the
+/// classes written by this crate
+impl TStreamer {
+    /// A base class of the given `version` and `checksum`. The type
+    /// of the element depends on the base class
+    pub(crate) fn base(name: &str, title: &str, version: i32, checksum: u32) -> TStreamer {
+        let el_type = match name {
+            "TObject" => TypeID::TObject,
+            "TNamed" => TypeID::Named,
+            _ => TypeID::Base,
+        };
+        let mut el = TStreamerElement::new(name, title, el_type, 0, "BASE");
+        // The checksum of the base class is stored in place of the
+        // second max index
+        el.max_idx[1] = checksum;
+        TStreamer::Base {el, version_base: version}
+    }
+
+    /// A member of primitive type
+    pub(crate) fn basic(name: &str, title: &str, id: PrimitiveID, type_name: &str) -> TStreamer {
+        let size = id.size();
+        let el = TStreamerElement::new(name, title, TypeID::Primitive(id), size, type_name);
+        TStreamer::BasicType {el}
+    }
+
+    /// A pointer to an array of primitive types whose length is given
+    /// by the member `cname` of the class `ccls`
+    pub(crate) fn basic_pointer(name: &str, title: &str, id: PrimitiveID, type_name: &str,
+                                cname: &str, ccls: &str, cvers: i32) -> TStreamer {
+        let size = id.size();
+        let el = TStreamerElement::new(name, title, TypeID::Array(id), size, type_name);
+        TStreamer::BasicPointer {el, cvers, cname: cname.to_string(), ccls: ccls.to_string()}
+    }
+
+    /// A member which is an object deriving from `TObject`
+    pub(crate) fn object(name: &str, title: &str, type_name: &str, size: i32) -> TStreamer {
+        let el = TStreamerElement::new(name, title, TypeID::Object, size, type_name);
+        TStreamer::Object {el}
+    }
+
+    /// A pointer to an object deriving from `TObject`. Pointers
+    /// marked with `->` in their title are never null and the object
+    /// is streamed in place
+    pub(crate) fn object_pointer(name: &str, title: &str, type_name: &str) -> TStreamer {
+        let el_type = if title.starts_with("->") { TypeID::Objectp } else { TypeID::ObjectP };
+        let el = TStreamerElement::new(name, title, el_type, 8, type_name);
+        TStreamer::ObjectPointer {el}
+    }
+
+    /// A member which is an object not deriving from `TObject`
+    pub(crate) fn object_any(name: &str, title: &str, type_name: &str, size: i32) -> TStreamer {
+        let el = TStreamerElement::new(name, title, TypeID::Any, size, type_name);
+        TStreamer::ObjectAny {el}
+    }
+
+    /// A `TString` member
+    pub(crate) fn string(name: &str, title: &str) -> TStreamer {
+        let el = TStreamerElement::new(name, title, TypeID::String, 24, "TString");
+        TStreamer::String {el}
+    }
+}
+
+impl Streamable for TStreamerElement {
+    fn class_name(&self) -> &str {
+        "TStreamerElement"
+    }
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_version(self.ver, |w| {
+            self.name.stream(w);
+            w.put_i32(self.el_type.code());
+            w.put_i32(self.size);
+            w.put_i32(self.array_len);
+            w.put_i32(self.array_dim);
+            if self.ver == 1 {
+                w.put_i32(self.max_idx.len() as i32);
+            }
+            for idx in &self.max_idx {
+                w.put_u32(*idx);
+            }
+            w.put_string(&self.type_name);
+            if self.ver == 3 {
+                w.put_f64(self.xmin);
+                w.put_f64(self.xmax);
+                w.put_f64(self.factor);
+            }
+        });
+    }
+}
+
+impl Streamable for TStreamer {
+    fn class_name(&self) -> &str {
+        use self::TStreamer::*;
+        match *self {
+            Base{..} => "TStreamerBase",
+            BasicType{..} => "TStreamerBasicType",
+            BasicPointer{..} => "TStreamerBasicPointer",
+            Loop{..} => "TStreamerLoop",
+            Object{..} => "TStreamerObject",
+            ObjectPointer{..} => "TStreamerObjectPointer",
+            ObjectAny{..} => "TStreamerObjectAny",
+            ObjectAnyPointer{..} => "TStreamerObjectAnyPointer",
+            String{..} => "TStreamerString",
+            Stl{..} => "TStreamerSTL",
+            StlString{..} => "TStreamerSTLstring",
+            Unknown{ref class_name, ..} => class_name,
+        }
+    }
+
+    /// Counterpart to the `tstreamer` parser
+    fn stream(&self, buf: &mut WBuffer) {
+        use self::TStreamer::*;
+        if let Unknown{ref data, ..} = *self {
+            buf.put_byte_count(|w| w.put_bytes(data));
+            return;
+        }
+        let version = match *self {
+            Base{..} | Stl{..} => 3,
+            _ => 2,
+        };
+        buf.put_version(version, |w| {
+            match *self {
+                Base{ref el, version_base} => {
+                    el.stream(w);
+                    w.put_i32(version_base);
+                },
+                BasicPointer{ref el, cvers, ref cname, ref ccls}
+                | Loop{ref el, cvers, ref cname, ref ccls} => {
+                    el.stream(w);
+                    w.put_i32(cvers);
+                    w.put_string(cname);
+                    w.put_string(ccls);
+                },
+                Stl{ref el, ref vtype, ref ctype} => {
+                    el.stream(w);
+                    w.put_i32(vtype.code());
+                    w.put_i32(ctype.code());
+                },
+                StlString{ref el, ref vtype, ref ctype} => {
+                    // `TStreamerSTLstring` derives from `TStreamerSTL`
+                    w.put_version(3, |w| {
+                        el.stream(w);
+                        w.put_i32(vtype.code());
+                        w.put_i32(ctype.code());
+                    });
+                },
+                BasicType{ref el} | Object{ref el} | ObjectPointer{ref el}
+                | ObjectAny{ref el} | ObjectAnyPointer{ref el} | String{ref el} => {
+                    el.stream(w);
+                },
+                Unknown{..} => unreachable!(),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stream `s` and parse it back as a `TStreamer` of class `class_name`
+    fn reparse(s: &TStreamer, class_name: &str) -> TStreamer {
+        let mut buf = WBuffer::new(0);
+        s.stream(&mut buf);
+        // Skip the byte count
+        let raw = Raw {classinfo: class_name.to_string(), obj: &buf.as_slice()[4..]};
+        tstreamer(&raw).unwrap().1
+    }
+
+    #[test]
+    fn element_version_3() {
+        let mut s = TStreamer::basic("fPt", "[0,100,16]", PrimitiveID(9), "Double32_t");
+        if let TStreamer::BasicType {ref mut el} = s {
+            el.ver = 3;
+            el.xmin = 0.0;
+            el.xmax = 100.0;
+            el.factor = 655.35;
+        }
+        let s = reparse(&s, "TStreamerBasicType");
+        let el = s.elem();
+        assert_eq!(el.ver, 3);
+        assert_eq!(el.name.name, "fPt");
+        assert_eq!((el.xmin, el.xmax, el.factor), (0.0, 100.0, 655.35));
+    }
+
+    #[test]
+    fn unknown_streamer() {
+        let s = TStreamer::object("fOther", "", "TOther", 16);
+        let unknown = reparse(&s, "TStreamerArtificial");
+        match unknown {
+            TStreamer::Unknown {ref el, ref class_name, ..} => {
+                assert_eq!(class_name, "TStreamerArtificial");
+                assert_eq!(el.type_name, "TOther");
+            },
+            ref s => panic!("Unexpected streamer {:?}", s),
+        }
+        // Unknown streamers are written as they were read
+        let mut original = WBuffer::new(0);
+        s.stream(&mut original);
+        let mut rewritten = WBuffer::new(0);
+        unknown.stream(&mut rewritten);
+        assert_eq!(original.as_slice(), rewritten.as_slice());
+    }
+}
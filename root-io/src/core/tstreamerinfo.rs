@@ -2,8 +2,8 @@ use quote::*;
 use nom::*;
 
 use ::core::*;
-use ::code_gen::rust::{ToRustType, ToRustParser, ToNamedRustParser, ToRustStruct};
-use ::code_gen::utils::{type_is_core};
+use ::code_gen::rust::{parser_ident, Classes, ToRustType, ToNamedRustParser, ToRustStruct};
+use ::code_gen::utils::{class_ident, type_is_core};
 
 
 #[derive(Debug)]
@@ -52,51 +52,48 @@ pub(crate) fn tstreamerinfo<'s, 'c>(input: &'s[u8], context: &'c Context) -> IRe
 }
 
 
-impl ToRustParser for TStreamerInfo {
-    /// Generate a parser that can parse an an object described by this TStreamer
-    fn to_inline_parser(&self) -> Tokens {
-        if type_is_core(self.named.name.as_str()) {
-            // Don't generate a parser if its a core type
-            return quote!(#(self.named.name.to_lowercase()));
-        }
-        let struct_name = Ident::new(self.named.name.as_str());
-        let member_names: &Vec<Ident> = &self.data_members.iter()
-            .map(|m| m.member_name())
-            .collect();
-        let member_parsers: &Vec<Tokens> = &self.data_members.iter()
-            .map(|m| m.to_inline_parser())
-            .collect();
-        quote!{
-            do_parse!(ver: be_u16 >>
-                      #(#member_names : #member_parsers >> )*
-                      ({let phantom = PhantomData;
-                        #struct_name {
-                            phantom,
-                            ver,
-                            #(#member_names),*
-                        }})
-            )}
-    }
-}
-
 impl ToNamedRustParser for TStreamerInfo {
     fn parser_name(&self) -> Tokens {
-        let ret = Ident::new(self.named.name.to_lowercase());
+        let ret = Ident::new(parser_ident(&self.named.name));
         quote!(#ret)
-    }    
+    }
 
-    fn to_named_parser(&self) -> Tokens {
+    fn to_named_parser(&self, classes: &Classes) -> Tokens {
         if type_is_core(self.named.name.as_str()) {
             // Don't generate a parser if its a core type
             return quote!{};
         }
         let parser_name = self.parser_name();
-        let parser = self.to_inline_parser();
         let struct_name = self.type_name();
+        let ret = if classes.needs_lifetime(&self.named.name) {
+            quote!{#struct_name<'s>}
+        } else {
+            quote!{#struct_name}
+        };
+        let member_names: &Vec<Ident> = &self.data_members.iter()
+            .map(|m| m.member_name())
+            .collect();
+        let member_parsers: &Vec<Tokens> = &self.data_members.iter()
+            .map(|m| m.field_parser(&self.named.name, classes))
+            .collect();
+        // Pointers which may be null are parsed as `Raw` objects
+        let curried_raw = if self.data_members.iter().any(|m| m.is_tagged_pointer()) {
+            quote!{let _curried_raw = |i| raw(i, context);}
+        } else {
+            quote!{}
+        };
         quote!{
-            pub fn #parser_name<'s>(input: &'s[u8], context: &'s Context<'s>)
-                                    -> IResult<&'s[u8], #struct_name<'s>> {
-                value!(input, #parser)
+            #[allow(unused_variables)]
+            pub fn #parser_name<'s>(input: &'s [u8], context: &'s Context<'s>)
+                                    -> IResult<&'s [u8], #ret> {
+                #curried_raw
+                do_parse!(input,
+                          ver: be_u16 >>
+                          #(#member_names : #member_parsers >> )*
+                          (#struct_name {
+                              ver,
+                              #(#member_names),*
+                          }))
             }
         }
     }
@@ -104,21 +101,26 @@ impl ToNamedRustParser for TStreamerInfo {
 
 impl ToRustStruct for TStreamerInfo {
     /// Generate a struct corresponding to this TStreamerInfo
-    fn to_struct(&self) -> Tokens {
+    fn to_struct(&self, classes: &Classes) -> Tokens {
         if type_is_core(self.named.name.as_str()) {
             return quote!{};
         }
         let name = self.type_name();
-        let fields = &self.data_members;
+        let name = if classes.needs_lifetime(&self.named.name) {
+            quote!{#name<'s>}
+        } else {
+            quote!{#name}
+        };
+        let fields: &Vec<Tokens> = &self.data_members.iter()
+            .map(|m| m.to_field(classes))
+            .collect();
         let ver_comment = self.type_doc();
         quote! {
             #[derive(Debug)]
-            pub struct #name<'s> {
-                /// Gurantee that this object does not outlive its underlying slice
-                phantom: PhantomData<&'s[u8]>,
+            pub struct #name {
                 #ver_comment
-                ver: u16,
-                #(#fields), *
+                pub ver: u16,
+                #(#fields),*
             }
         }
     }
@@ -126,17 +128,16 @@ impl ToRustStruct for TStreamerInfo {
 
 impl ToRustType for TStreamerInfo {
     fn type_doc(&self) -> Tokens {
-        let ret = Ident::new("\n/// Version of the read layout\n");
-        quote!(#ret)
+        quote!(#[doc = "Version of the read layout"])
     }
     fn type_name(&self) -> Tokens {
-        let ret = Ident::new(self.named.name.as_str());
+        let ret = Ident::new(class_ident(&self.named.name));
         quote!(#ret)
     }
 }
 
 impl TStreamerInfo {
-    pub(crate) fn to_yaml(&self) -> String {
+    pub(crate) fn to_yaml(&self, classes: &Classes) -> String {
         if type_is_core(self.named.name.as_str()) {
             return "".to_string();
         };
@@ -148,9 +149,106 @@ impl TStreamerInfo {
             s += format!("      # {}\n", obj.member_comment()).as_str();
             s += format!("      {}: {}\n",
                          obj.member_name().to_string(),
-                         obj.type_name().to_string()).as_str();
+                         obj.field_type(classes)).as_str();
         }
         s += "\n";
         s
     }
 }
+
+impl TStreamerInfo {
+    /// Describe the class `name` of the given version by its
+    /// members. The checksum is computed from the members
+    pub(crate) fn new(name: &str, title: &str, version: u32, data_members: Vec<TStreamer>) -> TStreamerInfo {
+        let mut info = TStreamerInfo {
+            tstreamerinfo_ver: 9,
+            named: TNamed {name: name.to_string(), title: title.to_string()},
+            checksum: 0,
+            new_class_version: version,
+            data_members,
+        };
+        info.checksum = info.compute_checksum();
+        info
+    }
+
+    /// Use the given checksum rather than the computed one
+    pub(crate) fn with_checksum(mut self, checksum: u32) -> TStreamerInfo {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Name of the described class
+    pub fn name(&self) -> &str {
+        &self.named.name
+    }
+
+    /// Checksum of this layout of the class
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Version of the class described by this layout
+    pub fn version(&self) -> u32 {
+        self.new_class_version
+    }
+
+    /// Names of the members in the order in which they are streamed.
+    /// Base classes are members named after the class
+    pub fn member_names(&self) -> Vec<&str> {
+        self.data_members.iter().map(|m| m.elem().name.name.as_str()).collect()
+    }
+
+    pub(crate) fn data_members(&self) -> &[TStreamer] {
+        &self.data_members
+    }
+
+    /// Compute the checksum of this class the way `TStreamerInfo::GetCheckSum`
+    /// does it. This does not cover the special treatment of enums
+    /// by ROOT.
+    fn compute_checksum(&self) -> u32 {
+        fn add(id: u32, s: &str) -> u32 {
+            s.bytes().fold(id, |id, c| id.wrapping_mul(3).wrapping_add(u32::from(c)))
+        }
+        let mut id = add(0, &self.named.name);
+        let (bases, members): (Vec<_>, Vec<_>) = self.data_members.iter()
+            .partition(|m| matches!(**m, TStreamer::Base{..}));
+        for el in bases.iter().map(|m| m.elem()) {
+            id = add(id, &el.name.name);
+            id = id.wrapping_mul(3).wrapping_add(el.max_idx[1]);
+        }
+        for el in members.iter().map(|m| m.elem()) {
+            id = add(id, &el.name.name);
+            id = add(id, &el.type_name);
+            for idx in el.max_idx.iter().take(el.array_dim as usize) {
+                id = id.wrapping_mul(3).wrapping_add(*idx);
+            }
+            // A leading `[...]` in the title specifies the length of an array
+            let title = el.name.title.trim_start_matches(|c: char| c == '*' || c.is_whitespace());
+            if title.starts_with('[') {
+                if let Some(end) = title.find(']') {
+                    id = add(id, &title[1..end]);
+                }
+            }
+        }
+        id
+    }
+}
+
+impl Streamable for TStreamerInfo {
+    fn class_name(&self) -> &str {
+        "TStreamerInfo"
+    }
+
+    /// Counterpart to the `tstreamerinfo` parser
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_version(self.tstreamerinfo_ver, |w| {
+            self.named.stream(w);
+            w.put_u32(self.checksum);
+            w.put_u32(self.new_class_version);
+            let members = &self.data_members;
+            w.put_any("TObjArray", |w| {
+                put_tobjarray(w, members.len(), |w, i| w.put_object(&members[i]))
+            });
+        });
+    }
+}
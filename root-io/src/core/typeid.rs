@@ -15,6 +15,8 @@ pub(crate) enum TypeID {
     Named,
     TObject,
     Any,
+    /// Pointer to an object which is never null (`//->`); streamed in place
+    Objectp,
     ObjectP,
     String,
     AnyP,
@@ -33,6 +35,9 @@ pub(crate) struct PrimitiveID(pub(crate) i32);
 pub(crate) enum StlTypeID {
     Vector,
     Bitset,
+    String,
+    /// Any other container such as `std::map`
+    Unknown(i32),
 }
 
 
@@ -43,6 +48,16 @@ impl PrimitiveID {
             id => Err(format_err!("Invalid base type id {}", id)),
         }
     }
+
+    /// Size in bytes of this type in memory
+    pub(crate) fn size(&self) -> i32 {
+        match self.0 {
+            1 | 11 | 18 => 1,
+            2 | 12 => 2,
+            4 | 14 | 8 | 16 | 17 => 8,
+            _ => 4,
+        }
+    }
 }
 
 impl TypeID {
@@ -57,6 +72,7 @@ impl TypeID {
             id@41 ... 59 => Array(PrimitiveID::new(id - 40)?),
             61 => Object,
             62 => Any,
+            63 => Objectp,
             64 => ObjectP,
             65 => String,
             66 => TObject,
@@ -68,6 +84,30 @@ impl TypeID {
             id => Unknown(id)
         })
     }
+
+    /// The integer code of this type as it is written to disc; the
+    /// inverse of `TypeID::new`
+    pub(crate) fn code(&self) -> i32 {
+        use self::TypeID::*;
+        match *self {
+            InvalidOrCounter(id) | Unknown(id) => id,
+            Base => 0,
+            Primitive(ref id) => id.0,
+            Offset(ref id) => id.0 + 20,
+            Array(ref id) => id.0 + 40,
+            Object => 61,
+            Any => 62,
+            Objectp => 63,
+            ObjectP => 64,
+            String => 65,
+            TObject => 66,
+            Named => 67,
+            AnyP => 69,
+            STL => 300,
+            STLString => 365,
+            Streamer => 500,
+        }
+    }
 }
 
 impl StlTypeID {
@@ -76,7 +116,18 @@ impl StlTypeID {
         match id {
             1 => Vector,
             8 => Bitset,
-            _ => unimplemented!("`StlTypeID` {} not implemented.", id)
+            365 => String,
+            id => Unknown(id),
+        }
+    }
+
+    /// The integer code of this type as it is written to disc
+    pub(crate) fn code(&self) -> i32 {
+        match *self {
+            StlTypeID::Vector => 1,
+            StlTypeID::Bitset => 8,
+            StlTypeID::String => 365,
+            StlTypeID::Unknown(id) => id,
         }
     }
 }
@@ -92,7 +143,7 @@ impl ToRustType for TypeID {
                 &ObjectP => "Option<Raw<'s>>".to_string(),
                 &String => "String".to_string(),
                 // Some funky things which we just treat as byte strings for now
-                &Object | &STL | &STLString | &Streamer | &Unknown(82) => "Vec<u8>".to_string(),
+                &Object | &Objectp | &STL | &STLString | &Streamer | &Unknown(82) => "Vec<u8>".to_string(),
                 &Any => "Vec<u8>".to_string(),
                 &AnyP => "Vec<u8>".to_string(),
                 &InvalidOrCounter(-1) => "u32".to_string(),
@@ -115,8 +166,9 @@ impl ToRustParser for PrimitiveID {
             // "kCharStar"
             7 => unimplemented!("{:?}: type not implemented, yet", self),
             8 => "be_f64", //"kDouble",
-            // "kDouble32"; This one is nasty! Check the TFileBuffer.cxx sources in ROOT
-            9 => "custom_float",
+            // "kDouble32"; This one is nasty! Check the TFileBuffer.cxx sources in ROOT.
+            // Without a range given in the comment, it is stored as a float
+            9 => "be_f32",
             // "kLegacyChar"
             10 => unimplemented!("{:?}: type not implemented, yet", self),
             11 => "be_u8", //"kUChar",
@@ -127,7 +179,8 @@ impl ToRustParser for PrimitiveID {
             16 => "be_i64", //"kLong64",
             17 => "be_u64", //"kULong64",
             18 => "be_u8", //"kBool",
-            19 => "be_f16", //"kFloat16",
+            // "kFloat16"; stored as a float if no range is given
+            19 => "be_f32",
             id => panic!("Invalid base type id {} which should not be possible here", id),
         };
         let t = Ident::new(t);
@@ -138,7 +191,15 @@ impl ToRustParser for PrimitiveID {
 
 impl ToRustType for PrimitiveID {
     fn type_name(&self) -> Tokens {
-        let t = match self.0 {
+        let t = Ident::new(self.rust_type_name());
+        quote!(#t)
+    }
+}
+
+impl PrimitiveID {
+    /// Name of the Rust type of this primitive
+    pub(crate) fn rust_type_name(&self) -> &'static str {
+        match self.0 {
             1 => "i8", //"kChar",
             2 => "i16", //"kShort",
             3 | 6 => "i32", //"kInt", "kCounter",
@@ -159,10 +220,33 @@ impl ToRustType for PrimitiveID {
             16 => "i64", //"kLong64",
             17 => "u64", //"kULong64",
             18 => "u8", //"kBool",
-            19 => "f16", //"kFloat16",
+            19 => "f32", //"kFloat16",
             id => panic!("Invalid base type id {} which should not be possible here", id),
-        };
-        let t = Ident::new(t);
-        quote!(#t)
+        }
+    }
+
+    /// Name of the C++ type of this primitive as it is used in ROOT
+    pub(crate) fn cpp_type_name(&self) -> &'static str {
+        match self.0 {
+            1 => "Char_t",
+            2 => "Short_t",
+            3 | 6 => "Int_t",
+            4 => "Long_t",
+            5 => "Float_t",
+            7 => "char*",
+            8 => "Double_t",
+            9 => "Double32_t",
+            10 => "char",
+            11 => "UChar_t",
+            12 => "UShort_t",
+            13 => "UInt_t",
+            14 => "ULong_t",
+            15 => "UInt_t",
+            16 => "Long64_t",
+            17 => "ULong64_t",
+            18 => "Bool_t",
+            19 => "Float16_t",
+            id => panic!("Invalid base type id {} which should not be possible here", id),
+        }
     }
 }
@@ -14,6 +14,7 @@ bitflags! {
 bitflags! {
     pub(crate) struct TObjectFlags: u32 {
         const IS_ON_HEAP = 0x0100_0000;
+        const NOT_DELETED = 0x0200_0000;
         const IS_REFERENCED = 1 << 4;
     }
 }
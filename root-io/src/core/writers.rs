@@ -0,0 +1,497 @@
+//! Writers of the ROOT core types. This is the counterpart to the
+//! `parsers` module: Objects are written into a `WBuffer` in the
+//! same layout in which the parsers expect them. As in the parsers,
+//! objects are usually preceeded by their byte count and version,
+//! which is taken care of by `WBuffer::put_version`.
+
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+use MAP_OFFSET;
+use core::*;
+
+/// Largest chunk which fits into one compression block; the sizes in
+/// the block header are stored in 3 bytes
+const MAX_BLOCK_SIZE: usize = 0xFF_FFFF;
+
+/// Types which can be written into a ROOT file
+pub trait Streamable {
+    /// Name of the ROOT class of this object as it appears in `TKey`s
+    /// and class tags
+    fn class_name(&self) -> &str;
+
+    /// Write this object to the end of `buf`. This includes the
+    /// object's byte count and version if it has any.
+    fn stream(&self, buf: &mut WBuffer);
+}
+
+/// A buffer into which objects are streamed before being written to
+/// disc. ROOT does not write the name of a class more than once per
+/// buffer; instead later occurences are referencing the absolute
+/// position of the first one. The `WBuffer` keeps track of these
+/// positions.
+#[derive(Debug)]
+pub struct WBuffer {
+    buf: Vec<u8>,
+    /// Length of the `TKey` in front of this buffer. Positions stored
+    /// in the buffer are relative to the start of the key
+    key_len: u32,
+    /// Classes written to this buffer and the tag referencing them
+    classes: HashMap<String, u32>,
+    /// Objects written to this buffer and the tag referencing them
+    objects: HashMap<usize, u32>,
+}
+
+impl WBuffer {
+    /// Create an empty buffer which will be written after a `TKey` of
+    /// length `key_len`
+    pub fn new(key_len: u32) -> WBuffer {
+        WBuffer {
+            buf: Vec::new(),
+            key_len,
+            classes: HashMap::new(),
+            objects: HashMap::new(),
+        }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// The names of all classes which were written to this buffer
+    pub fn classes(&self) -> Vec<String> {
+        self.classes.keys().cloned().collect()
+    }
+
+    /// The written bytes
+    pub fn as_slice(&self) -> &[u8] {
+        self.buf.as_slice()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub fn put_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+    pub fn put_i8(&mut self, v: i8) {
+        self.buf.push(v as u8);
+    }
+    pub fn put_bool(&mut self, v: bool) {
+        self.buf.push(v as u8);
+    }
+    pub fn put_u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&[(v >> 8) as u8, v as u8]);
+    }
+    pub fn put_i16(&mut self, v: i16) {
+        self.put_u16(v as u16);
+    }
+    pub fn put_u32(&mut self, v: u32) {
+        self.put_u16((v >> 16) as u16);
+        self.put_u16(v as u16);
+    }
+    pub fn put_i32(&mut self, v: i32) {
+        self.put_u32(v as u32);
+    }
+    pub fn put_u64(&mut self, v: u64) {
+        self.put_u32((v >> 32) as u32);
+        self.put_u32(v as u32);
+    }
+    pub fn put_i64(&mut self, v: i64) {
+        self.put_u64(v as u64);
+    }
+    pub fn put_f32(&mut self, v: f32) {
+        self.put_u32(v.to_bits());
+    }
+    pub fn put_f64(&mut self, v: f64) {
+        self.put_u64(v.to_bits());
+    }
+    pub fn put_bytes(&mut self, v: &[u8]) {
+        self.buf.extend_from_slice(v);
+    }
+
+    /// Write ROOT's version of a string (preceeded by its length).
+    /// Counterpart to the `string` parser
+    pub fn put_string(&mut self, s: &str) {
+        let len = s.len();
+        if len < 255 {
+            self.put_u8(len as u8);
+        } else {
+            self.put_u8(255);
+            self.put_u32(len as u32);
+        }
+        self.put_bytes(s.as_bytes());
+    }
+
+    /// Write a null terminated string
+    pub fn put_c_string(&mut self, s: &str) {
+        self.put_bytes(s.as_bytes());
+        self.put_u8(0);
+    }
+
+    /// Write whatever `f` writes and prepend it with its byte count
+    pub fn put_byte_count<F>(&mut self, f: F)
+        where F: FnOnce(&mut WBuffer)
+    {
+        let pos = self.buf.len();
+        self.put_u32(0);
+        f(self);
+        let cnt = (self.buf.len() - pos - 4) as u32 | Flags::BYTE_COUNT_MASK.bits() as u32;
+        self.set_u32(pos, cnt);
+    }
+
+    /// Overwrite the four bytes at `pos` with `v`
+    pub fn set_u32(&mut self, pos: usize, v: u32) {
+        self.buf[pos..pos + 4].copy_from_slice(&[(v >> 24) as u8, (v >> 16) as u8,
+                                                 (v >> 8) as u8, v as u8]);
+    }
+
+    /// Write the byte count and `version` followed by whatever `f`
+    /// writes. This is the usual preamble of a streamed object.
+    pub fn put_version<F>(&mut self, version: u16, f: F)
+        where F: FnOnce(&mut WBuffer)
+    {
+        self.put_byte_count(|w| {
+            w.put_u16(version);
+            f(w);
+        });
+    }
+
+    /// Write a class tag. The name of the class is written on its
+    /// first occurence; later ones reference the first one. This is
+    /// the counterpart to the `classinfo` parser
+    pub fn put_class_tag(&mut self, class_name: &str) {
+        if let Some(&tag) = self.classes.get(class_name) {
+            self.put_u32(tag | Flags::CLASS_MASK.bits() as u32);
+        } else {
+            let tag = self.position() + MAP_OFFSET as u32;
+            self.classes.insert(class_name.to_string(), tag);
+            self.put_u32(Flags::NEW_CLASSTAG.bits() as u32);
+            self.put_c_string(class_name);
+        }
+    }
+
+    /// Write an object preceeded by its class tag, as it is done for
+    /// the elements of a `TList` or for pointers to objects
+    pub fn put_object(&mut self, obj: &dyn Streamable) {
+        self.put_any(obj.class_name(), |w| obj.stream(w));
+    }
+
+    /// Write whatever `f` writes as an object of class `class_name`
+    /// preceeded by its class tag. See `put_object`
+    pub fn put_any<F>(&mut self, class_name: &str, f: F)
+        where F: FnOnce(&mut WBuffer)
+    {
+        self.put_byte_count(|w| {
+            w.put_class_tag(class_name);
+            f(w);
+        });
+    }
+
+    /// Like `put_object`, but if an object with the same `id` was
+    /// already written to this buffer, only a reference to it is
+    /// written. The `id` is chosen by the caller and has to be unique
+    /// within this buffer.
+    pub fn put_object_with_id(&mut self, id: usize, obj: &dyn Streamable) {
+        self.put_object_ref(id, |w| w.put_object(obj));
+    }
+
+    /// Write a reference to the object with the given `id` if it was
+    /// already written to this buffer. Otherwise, `f` is called to
+    /// write the object including its byte count and class tag.
+    pub fn put_object_ref<F>(&mut self, id: usize, f: F)
+        where F: FnOnce(&mut WBuffer)
+    {
+        if let Some(&tag) = self.objects.get(&id) {
+            self.put_u32(tag);
+        } else {
+            let tag = self.position() + MAP_OFFSET as u32;
+            self.objects.insert(id, tag);
+            f(self);
+        }
+    }
+
+    /// Write a null pointer in place of an object
+    pub fn put_null(&mut self) {
+        self.put_u32(0);
+    }
+
+    /// Position of the end of this buffer relative to the beginning of the `TKey`
+    fn position(&self) -> u32 {
+        self.key_len + self.buf.len() as u32
+    }
+}
+
+impl Default for TObject {
+    fn default() -> TObject {
+        TObject {
+            ver: 1,
+            id: 0,
+            bits: TObjectFlags::IS_ON_HEAP | TObjectFlags::NOT_DELETED,
+        }
+    }
+}
+
+impl Streamable for TObject {
+    fn class_name(&self) -> &str {
+        "TObject"
+    }
+    /// `TObject`s are written without byte count
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_u16(self.ver);
+        buf.put_u32(self.id);
+        buf.put_u32((self.bits - TObjectFlags::IS_REFERENCED).bits());
+    }
+}
+
+impl Streamable for TNamed {
+    fn class_name(&self) -> &str {
+        "TNamed"
+    }
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_version(1, |w| {
+            TObject::default().stream(w);
+            w.put_string(&self.name);
+            w.put_string(&self.title);
+        });
+    }
+}
+
+/// Note that the `Raw` elements are written as they are. This is
+/// only valid if they do not reference other parts of the buffer they
+/// were read from.
+impl<'a> Streamable for TList<'a> {
+    fn class_name(&self) -> &str {
+        "TList"
+    }
+    fn stream(&self, buf: &mut WBuffer) {
+        let objs: Vec<&dyn Streamable> = self.objs.iter().map(|raw| raw as _).collect();
+        put_tlist(buf, self.ver, &self.tobj, &self.name, &objs);
+    }
+}
+
+impl<'s> Streamable for Raw<'s> {
+    fn class_name(&self) -> &str {
+        &self.classinfo
+    }
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_byte_count(|w| w.put_bytes(self.obj));
+    }
+}
+
+/// Write a `TList` of the given objects, each of which is followed by
+/// an empty option string. This is the counterpart to the `tlist`
+/// parser
+pub(crate) fn put_tlist(buf: &mut WBuffer, ver: u16, tobj: &TObject, name: &str,
+                        objs: &[&dyn Streamable]) {
+    buf.put_version(ver, |w| {
+        tobj.stream(w);
+        w.put_string(name);
+        w.put_i32(objs.len() as i32);
+        for obj in objs {
+            w.put_object(*obj);
+            // Empty option string
+            w.put_u8(0);
+        }
+    });
+}
+
+/// Write a `TObjArray` of `len` objects. Each element is written by
+/// `f` which is given the index of the element. This is the
+/// counterpart to the `tobjarray` parser
+pub(crate) fn put_tobjarray<F>(buf: &mut WBuffer, len: usize, f: F)
+    where F: Fn(&mut WBuffer, usize)
+{
+    buf.put_version(3, |w| {
+        TObject::default().stream(w);
+        w.put_string("");
+        w.put_i32(len as i32);
+        // Lower bound
+        w.put_i32(0);
+        for i in 0..len {
+            f(w, i);
+        }
+    });
+}
+
+/// Write ROOT's default line attributes (`TAttLine`)
+pub(crate) fn put_tattline(buf: &mut WBuffer) {
+    buf.put_version(2, |w| {
+        w.put_i16(602);
+        w.put_i16(1);
+        w.put_i16(1);
+    });
+}
+
+/// Write ROOT's default fill attributes (`TAttFill`)
+pub(crate) fn put_tattfill(buf: &mut WBuffer) {
+    buf.put_version(2, |w| {
+        w.put_i16(0);
+        w.put_i16(1001);
+    });
+}
+
+/// Write ROOT's default marker attributes (`TAttMarker`)
+pub(crate) fn put_tattmarker(buf: &mut WBuffer) {
+    buf.put_version(2, |w| {
+        w.put_i16(1);
+        w.put_i16(1);
+        w.put_f32(1.0);
+    });
+}
+
+/// Write a `TArrayD`; these are not preceeded by a byte count or
+/// version. Counterpart to the `tarrayd` parser
+pub(crate) fn put_tarrayd(buf: &mut WBuffer, vals: &[f64]) {
+    buf.put_i32(vals.len() as i32);
+    for v in vals {
+        buf.put_f64(*v);
+    }
+}
+
+/// Compress the given buffer with zlib at the given level (1-9) and
+/// prepend ROOT's compression header. Returns `None` if compression
+/// did not reduce the size, in which case the data should be stored
+/// uncompressed. Counterpart to the `decompress` parser
+pub(crate) fn compress(buf: &[u8], level: u32) -> Result<Option<Vec<u8>>, Error> {
+    let level = match level {
+        0 => return Ok(None),
+        1..=3 => Compression::Fast,
+        4..=6 => Compression::Default,
+        _ => Compression::Best,
+    };
+    let mut ret = Vec::new();
+    for chunk in buf.chunks(MAX_BLOCK_SIZE) {
+        let mut encoder = ZlibEncoder::new(Vec::new(), level);
+        encoder.write_all(chunk)?;
+        let comp = encoder.finish()?;
+        let (c_len, u_len) = (comp.len(), chunk.len());
+        // Magic, method, and the compressed and uncompressed size as
+        // 3 byte little endian numbers
+        ret.extend_from_slice(b"ZL\x08");
+        ret.extend_from_slice(&[c_len as u8, (c_len >> 8) as u8, (c_len >> 16) as u8]);
+        ret.extend_from_slice(&[u_len as u8, (u_len >> 8) as u8, (u_len >> 16) as u8]);
+        ret.extend_from_slice(&comp);
+    }
+    if ret.len() < buf.len() {
+        Ok(Some(ret))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The given time in ROOT's `TDatime` format. `TDatime` packs the
+/// date and time (UTC) into an `u32` with 1995 as year zero
+pub(crate) fn datime(t: SystemTime) -> u32 {
+    let secs = t.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, secs) = ((secs / 86_400) as i64, (secs % 86_400) as u32);
+    // Convert days since epoch to a civil date; see
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as u32;
+    ((year - 1995) << 26) | (month << 22) | (day << 17)
+        | ((secs / 3600) << 12) | ((secs % 3600 / 60) << 6) | (secs % 60)
+}
+
+/// A random UUID as it is stored in the header of every ROOT file
+pub(crate) fn new_uuid() -> [u8; 16] {
+    // `RandomState` is seeded randomly for each instance
+    let mut uuid = [0; 16];
+    for chunk in uuid.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(datime(SystemTime::now()) as u64);
+        let v = hasher.finish();
+        for (i, b) in chunk.iter_mut().enumerate() {
+            *b = (v >> (8 * i)) as u8;
+        }
+    }
+    uuid
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use nom::IResult;
+    use super::*;
+
+    #[test]
+    fn tnamed_round_trip() {
+        let named = TNamed {name: "name".to_string(), title: "a title".to_string()};
+        let mut buf = WBuffer::new(0);
+        named.stream(&mut buf);
+        match length_value!(buf.as_slice(), checked_byte_count, tnamed) {
+            IResult::Done(i, n) => {
+                assert!(i.is_empty());
+                assert_eq!(n.name, named.name);
+                assert_eq!(n.title, named.title);
+            },
+            _ => panic!("Failed to parse written TNamed"),
+        }
+    }
+
+    #[test]
+    fn class_tags_are_referenced() {
+        let named = TNamed {name: "name".to_string(), title: "".to_string()};
+        let key_len = 64;
+        let mut buf = WBuffer::new(key_len);
+        put_tobjarray(&mut buf, 2, |w, _| w.put_object(&named));
+        // The class name is only written once
+        let s = buf.as_slice();
+        assert_eq!(s.windows(6).filter(|w| w == b"TNamed").count(), 1);
+        let context = Context {
+            path: PathBuf::from(""),
+            offset: key_len as u64 + MAP_OFFSET,
+            s,
+        };
+        match length_value!(s, checked_byte_count, apply!(tobjarray, &context)) {
+            IResult::Done(_, objs) => {
+                assert_eq!(objs.len(), 2);
+                assert!(objs.iter().all(|o| o.classinfo == "TNamed"));
+            },
+            _ => panic!("Failed to parse written TObjArray"),
+        }
+    }
+
+    #[test]
+    fn datime_format() {
+        // 2014-12-16 22:20:12
+        let t = UNIX_EPOCH + ::std::time::Duration::from_secs(1_418_768_412);
+        let d = datime(t);
+        assert_eq!(d >> 26, 2014 - 1995);
+        assert_eq!(d >> 22 & 0xF, 12);
+        assert_eq!(d >> 17 & 0x1F, 16);
+        assert_eq!(d >> 12 & 0x1F, 22);
+        assert_eq!(d >> 6 & 0x3F, 20);
+        assert_eq!(d & 0x3F, 12);
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        let buf: Vec<u8> = (0..100_000).map(|i| (i % 7) as u8).collect();
+        let comp = compress(&buf, 1).unwrap().unwrap();
+        assert!(comp.len() < buf.len());
+        assert_eq!(decompress(&comp).unwrap().1, buf);
+        // Incompressible data is stored as is
+        assert!(compress(&[1, 2, 3], 1).unwrap().is_none());
+    }
+}
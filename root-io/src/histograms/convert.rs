@@ -0,0 +1,114 @@
+use failure::Error;
+use histogram::{Dimension, Histogram, HistogramBuilder};
+use histogram::num::{Num, NumCast, ToPrimitive};
+
+use histograms::{TAxis, TH1};
+
+const AXIS_NAMES: [&str; 3] = ["xaxis", "yaxis", "zaxis"];
+
+/// Histograms which can be written to a ROOT file as `TH1D`, `TH2D`, or `TH3D`
+pub trait ToTH1 {
+    /// Convert to a ROOT histogram of the given name and title. Fails
+    /// if the histogram has no bins or a bin content cannot be
+    /// represented as `f64`.
+    fn to_th1(&self, name: &str, title: &str) -> Result<TH1, Error>;
+}
+
+/// Histograms which can be created from a ROOT histogram
+pub trait FromTH1: Sized {
+    /// Convert the given ROOT histogram. Since ROOT histograms always
+    /// have under- and overflow bins, so does the converted one. Axes
+    /// without explicit bin edges become equal width axes. Fails if
+    /// the dimensions do not match or if a bin content cannot be
+    /// represented by the element type.
+    fn from_th1(th: &TH1) -> Result<Self, Error>;
+}
+
+/// Convert `v` to the element type of a histogram
+fn element<A: NumCast>(v: f64, th: &TH1) -> Result<A, Error> {
+    A::from(v).ok_or_else(|| format_err!("Bin content {} of histogram {} is out of range",
+                                         v, th.name()))
+}
+
+/// Convert the bin content `v` of the histogram `name` to `f64`
+fn content<A: ToPrimitive>(v: &A, name: &str) -> Result<f64, Error> {
+    v.to_f64().ok_or_else(|| format_err!("Bin content of histogram {} is not a number", name))
+}
+
+macro_rules! impl_th1_conversion {
+    ($N:expr) => {
+        /// The under- and overflow bins of the `TH1` are empty unless
+        /// the histogram has such bins
+        impl<A> ToTH1 for Histogram<A, [usize; $N]>
+            where A: ToPrimitive
+        {
+            fn to_th1(&self, name: &str, title: &str) -> Result<TH1, Error> {
+                let axes = (0..$N)
+                    .map(|axis| TAxis::new(AXIS_NAMES[axis], self.label(axis), self.edges(axis)))
+                    .collect();
+                let mut th = TH1::new(name, title, axes)?;
+                // Global bin in `th` of the bin `idx` into `counts`;
+                // skips the underflow bin of each axis without one
+                let global_bin = |th: &TH1, idx: &[usize]| {
+                    let idx: Vec<_> = idx.iter().enumerate()
+                        .map(|(axis, i)| if self.has_flow_bins(axis) { *i } else { i + 1 })
+                        .collect();
+                    th.global_bin(&idx)
+                };
+                for (idx, c) in self.counts.indexed_iter() {
+                    let global = global_bin(&th, idx.slice());
+                    th.contents_mut()[global] = content(c, name)?;
+                }
+                if let Some(sumw2) = self.sumw2() {
+                    for (idx, w2) in sumw2.indexed_iter() {
+                        let global = global_bin(&th, idx.slice());
+                        th.sumw2_mut()[global] = content(w2, name)?;
+                    }
+                }
+                th.set_entries(self.entries() as f64);
+                Ok(th)
+            }
+        }
+
+        impl<A> FromTH1 for Histogram<A, [usize; $N]>
+            where A: Clone + Num + NumCast
+        {
+            fn from_th1(th: &TH1) -> Result<Self, Error> {
+                if th.dim() != $N {
+                    return Err(format_err!("Cannot convert {}-dimensional histogram {} into {} dimensions",
+                                           th.dim(), th.name(), $N));
+                }
+                let mut builder = HistogramBuilder::<[usize; $N]>::new();
+                for ax in th.axes() {
+                    let edges = ax.edges();
+                    if ax.is_equal_width() {
+                        builder.add_equal_width_axis(ax.nbins(), edges[0], edges[ax.nbins()]);
+                    } else {
+                        builder.add_variable_width_axis(edges);
+                    }
+                    builder.with_flow_bins().with_label(ax.title());
+                }
+                let mut h = builder.build::<A>()
+                    .ok_or_else(|| format_err!("Invalid axes in histogram {}", th.name()))?;
+                // Both include the under- and overflow bins of each axis
+                for (idx, c) in h.counts.indexed_iter_mut() {
+                    *c = element(th.contents()[th.global_bin(idx.slice())], th)?;
+                }
+                if !th.sumw2().is_empty() {
+                    h.enable_sumw2();
+                    if let Some(sumw2) = h.sumw2_mut() {
+                        for (idx, w2) in sumw2.indexed_iter_mut() {
+                            *w2 = element(th.sumw2()[th.global_bin(idx.slice())], th)?;
+                        }
+                    }
+                }
+                h.set_entries(th.entries().max(0.0).round() as u64);
+                Ok(h)
+            }
+        }
+    }
+}
+
+impl_th1_conversion!(1);
+impl_th1_conversion!(2);
+impl_th1_conversion!(3);
@@ -0,0 +1,21 @@
+//! Histograms and graphs as they are stored in ROOT files (`TH1`,
+//! `TH2`, and `TH3` and their subclasses, `TProfile`, and
+//! `TGraph`). With the `histogram` feature enabled, histograms can be
+//! converted to and from `histogram::Histogram`s.
+
+mod taxis;
+mod tgraph;
+mod th1;
+mod tprofile;
+#[cfg(feature = "histogram")]
+mod convert;
+
+pub use self::taxis::TAxis;
+pub use self::tgraph::TGraph;
+pub use self::th1::TH1;
+pub use self::tprofile::{ErrorMode, TProfile};
+pub(crate) use self::tgraph::tgraph;
+pub(crate) use self::th1::th1;
+pub(crate) use self::tprofile::tprofile;
+#[cfg(feature = "histogram")]
+pub use self::convert::{FromTH1, ToTH1};
@@ -0,0 +1,123 @@
+use nom::*;
+
+use core::*;
+
+/// The binning of one dimension of a histogram (ROOT's `TAxis`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TAxis {
+    name: String,
+    title: String,
+    /// Lower edges of all bins followed by the upper edge of the last bin
+    edges: Vec<f64>,
+    /// Are the bins stored as a range and a number of bins rather
+    /// than explicit edges?
+    equal_width: bool,
+}
+
+impl TAxis {
+    /// A new axis with the given bin edges. The axis is stored with
+    /// equal width bins if these reproduce `edges` exactly
+    pub fn new(name: &str, title: &str, edges: Vec<f64>) -> TAxis {
+        let equal_width = edges.len() > 1
+            && equal_width_edges(edges.len() - 1, edges[0], edges[edges.len() - 1]) == edges;
+        TAxis {name: name.to_string(), title: title.to_string(), edges, equal_width}
+    }
+
+    /// Name of this axis, e.g. `xaxis`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Title of this axis as shown when plotting it
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Number of bins (without under- and overflow bins)
+    pub fn nbins(&self) -> usize {
+        self.edges.len() - 1
+    }
+
+    /// The bin edges, including the upper edge of the last bin
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+
+    /// Is this axis stored as a range divided into bins of equal
+    /// width, i.e. without explicit bin edges?
+    pub fn is_equal_width(&self) -> bool {
+        self.equal_width
+    }
+}
+
+/// The edges of `nbins` bins of equal width from `min` to `max` as
+/// computed by ROOT and `histogram::HistogramBuilder::add_equal_width_axis`
+fn equal_width_edges(nbins: usize, min: f64, max: f64) -> Vec<f64> {
+    let width = (max - min) / nbins as f64;
+    (0..nbins + 1).map(|i| min + width * i as f64).collect()
+}
+
+impl Streamable for TAxis {
+    fn class_name(&self) -> &str {
+        "TAxis"
+    }
+
+    /// Counterpart to the `taxis` parser
+    fn stream(&self, buf: &mut WBuffer) {
+        buf.put_version(10, |w| {
+            TNamed {name: self.name.clone(), title: self.title.clone()}.stream(w);
+            // TAttAxis
+            w.put_version(4, |w| {
+                w.put_i32(510);
+                w.put_i16(1);
+                w.put_i16(1);
+                w.put_i16(42);
+                w.put_f32(0.005);
+                w.put_f32(0.035);
+                w.put_f32(0.03);
+                w.put_f32(1.0);
+                w.put_f32(0.035);
+                w.put_i16(1);
+                w.put_i16(42);
+            });
+            w.put_i32(self.nbins() as i32);
+            w.put_f64(self.edges[0]);
+            w.put_f64(self.edges[self.nbins()]);
+            // Variable bin edges are only stored if needed
+            put_tarrayd(w, if self.is_equal_width() { &[] } else { &self.edges });
+            // First and last bin to display, second bit field, time display
+            w.put_i32(0);
+            w.put_i32(0);
+            w.put_u16(0);
+            w.put_bool(false);
+            // Time format
+            w.put_string("");
+            // Labels and modified labels
+            w.put_null();
+            w.put_null();
+        });
+    }
+}
+
+/// Parse a `TAxis`. Labels of the axis are ignored
+#[allow(unused_variables)]
+pub(crate) fn taxis(input: &[u8]) -> IResult<&[u8], TAxis> {
+    do_parse!(input,
+              _ver: be_u16 >>
+              tnamed: length_value!(checked_byte_count, tnamed) >>
+              _tattaxis: length_data!(checked_byte_count) >>
+              nbins: be_i32 >>
+              xmin: be_f64 >>
+              xmax: be_f64 >>
+              xbins: tarrayd >>
+              _rest: rest >>
+              ({
+                  let equal_width = xbins.is_empty();
+                  let edges = if equal_width {
+                      equal_width_edges(nbins.max(0) as usize, xmin, xmax)
+                  } else {
+                      xbins
+                  };
+                  TAxis {name: tnamed.name, title: tnamed.title, edges, equal_width}
+              }))
+}
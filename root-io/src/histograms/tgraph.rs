@@ -0,0 +1,220 @@
+use nom::*;
+
+use core::*;
+use histograms::th1::th1;
+
+/// A set of points with optional (asymmetric) errors; ROOT's
+/// `TGraph`, `TGraphErrors`, and `TGraphAsymmErrors`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TGraph {
+    name: String,
+    title: String,
+    x: Vec<f64>,
+    y: Vec<f64>,
+    ex_low: Vec<f64>,
+    ex_high: Vec<f64>,
+    ey_low: Vec<f64>,
+    ey_high: Vec<f64>,
+    x_title: String,
+    y_title: String,
+}
+
+impl TGraph {
+    /// Name of this graph
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Title of this graph
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The x-coordinate of each point
+    pub fn x(&self) -> &[f64] {
+        &self.x
+    }
+
+    /// The y-coordinate of each point
+    pub fn y(&self) -> &[f64] {
+        &self.y
+    }
+
+    /// Lower and upper error of each point along x. These are zero
+    /// if the graph does not have errors
+    pub fn x_errors(&self) -> (&[f64], &[f64]) {
+        (&self.ex_low, &self.ex_high)
+    }
+
+    /// Lower and upper error of each point along y. These are zero
+    /// if the graph does not have errors
+    pub fn y_errors(&self) -> (&[f64], &[f64]) {
+        (&self.ey_low, &self.ey_high)
+    }
+
+    /// Title of the x-axis; empty if not set
+    pub fn x_title(&self) -> &str {
+        &self.x_title
+    }
+
+    /// Title of the y-axis; empty if not set
+    pub fn y_title(&self) -> &str {
+        &self.y_title
+    }
+}
+
+/// Parse an array of `n` doubles stored behind a pointer; the
+/// array is preceeded by a byte which is 0 if the pointer is null
+fn double_array(input: &[u8], n: usize) -> IResult<&[u8], Vec<f64>> {
+    switch!(input, be_u8,
+            0 => value!(vec![]) |
+            _ => count!(be_f64, n))
+}
+
+/// Parse the `TGraph` part of a graph
+#[allow(unused_variables)]
+fn tgraph_base<'s>(input: &'s [u8], context: &'s Context<'s>) -> IResult<&'s [u8], TGraph> {
+    let _curried_raw = |i| raw(i, context);
+    let pointer = |i: &'s [u8]| switch!(i, peek!(be_u32),
+                                        0 => map!(call!(be_u32), |_| None) |
+                                        _ => map!(call!(_curried_raw), Some));
+    do_parse!(input,
+              _ver: be_u16 >>
+              tnamed: length_value!(checked_byte_count, tnamed) >>
+              _tattline: length_data!(checked_byte_count) >>
+              _tattfill: length_data!(checked_byte_count) >>
+              _tattmarker: length_data!(checked_byte_count) >>
+              npoints: be_i32 >>
+              x: apply!(double_array, npoints as usize) >>
+              y: apply!(double_array, npoints as usize) >>
+              _functions: pointer >>
+              histogram: pointer >>
+              _min_max: count!(be_f64, 2) >>
+              ({
+                  // The axis titles are stored in the histogram used for drawing
+                  let axes = histogram
+                      .and_then(|r| th1(r.obj, context, &r.classinfo).to_result().ok())
+                      .map(|h| h.axes().iter().map(|ax| ax.title().to_string()).collect())
+                      .unwrap_or_else(Vec::new);
+                  let title = |i: usize| axes.get(i).cloned().unwrap_or_default();
+                  let zeros = vec![0.0; x.len()];
+                  TGraph {
+                      name: tnamed.name,
+                      title: tnamed.title,
+                      x_title: title(0),
+                      y_title: title(1),
+                      x,
+                      y,
+                      ex_low: zeros.clone(),
+                      ex_high: zeros.clone(),
+                      ey_low: zeros.clone(),
+                      ey_high: zeros,
+                  }
+              }))
+}
+
+/// Parse a `TGraph`, `TGraphErrors`, or `TGraphAsymmErrors`
+pub(crate) fn tgraph<'s>(input: &'s [u8], context: &'s Context<'s>, class_name: &str)
+                         -> IResult<&'s [u8], TGraph> {
+    let wrapped_base = |i| length_value!(i, checked_byte_count, apply!(tgraph_base, context));
+    match class_name {
+        "TGraph" => tgraph_base(input, context),
+        "TGraphErrors" => do_parse!(input,
+                                    _ver: be_u16 >>
+                                    g: wrapped_base >>
+                                    ex: apply!(double_array, g.x.len()) >>
+                                    ey: apply!(double_array, g.x.len()) >>
+                                    (TGraph {
+                                        ex_low: ex.clone(), ex_high: ex,
+                                        ey_low: ey.clone(), ey_high: ey,
+                                        ..g
+                                    })),
+        "TGraphAsymmErrors" => do_parse!(input,
+                                         _ver: be_u16 >>
+                                         g: wrapped_base >>
+                                         ex_low: apply!(double_array, g.x.len()) >>
+                                         ex_high: apply!(double_array, g.x.len()) >>
+                                         ey_low: apply!(double_array, g.x.len()) >>
+                                         ey_high: apply!(double_array, g.x.len()) >>
+                                         (TGraph {ex_low, ex_high, ey_low, ey_high, ..g})),
+        _ => IResult::Error(ErrorKind::Custom(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use core::*;
+    use histograms::{TAxis, TH1};
+    use super::*;
+
+    /// Write a graph the way ROOT's `TGraphAsymmErrors` does
+    struct Graph {
+        x: Vec<f64>,
+        y: Vec<f64>,
+        errors: Vec<Vec<f64>>,
+    }
+
+    impl Streamable for Graph {
+        fn class_name(&self) -> &str {
+            "TGraphAsymmErrors"
+        }
+        fn stream(&self, buf: &mut WBuffer) {
+            let put_array = |w: &mut WBuffer, vals: &[f64]| {
+                w.put_u8(1);
+                for v in vals {
+                    w.put_f64(*v);
+                }
+            };
+            let hist = TH1::new("g", "Graph", vec![TAxis::new("xaxis", "p_{T}", vec![0., 5.]),
+                                                  TAxis::new("yaxis", "Ratio", vec![0., 1.])])
+                .unwrap();
+            let functions = TList {ver: 5, tobj: TObject::default(), name: "".to_string(),
+                                   len: 0, objs: vec![]};
+            buf.put_version(3, |w| {
+                w.put_version(4, |w| {
+                    TNamed {name: "g".to_string(), title: "Graph".to_string()}.stream(w);
+                    put_tattline(w);
+                    put_tattfill(w);
+                    put_tattmarker(w);
+                    w.put_i32(self.x.len() as i32);
+                    put_array(w, &self.x);
+                    put_array(w, &self.y);
+                    w.put_object(&functions);
+                    w.put_object(&hist);
+                    w.put_f64(-1111.0);
+                    w.put_f64(-1111.0);
+                });
+                for e in &self.errors {
+                    put_array(w, e);
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn read_graph() {
+        let path = env::temp_dir().join("root-io-tgraph.root");
+        let g = Graph {
+            x: vec![1., 2., 3.],
+            y: vec![0.5, 0.6, 0.7],
+            errors: vec![vec![0.5; 3], vec![0.5; 3], vec![0.1, 0.2, 0.3], vec![0.01, 0.02, 0.03]],
+        };
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.write_object("g", "Graph", &g).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+        let read = f.items()[0].as_graph().unwrap();
+        assert_eq!(read.name(), "g");
+        assert_eq!(read.x(), g.x.as_slice());
+        assert_eq!(read.y(), g.y.as_slice());
+        assert_eq!(read.x_errors(), (g.errors[0].as_slice(), g.errors[1].as_slice()));
+        assert_eq!(read.y_errors(), (g.errors[2].as_slice(), g.errors[3].as_slice()));
+        assert_eq!(read.x_title(), "p_{T}");
+        assert_eq!(read.y_title(), "Ratio");
+    }
+}
@@ -0,0 +1,401 @@
+use failure::Error;
+use nom::*;
+
+use core::*;
+use histograms::taxis::{taxis, TAxis};
+
+/// Default value of `fMaximum` and `fMinimum`; ROOT's marker for "not set"
+const UNSET_MIN_MAX: f64 = -1111.0;
+
+/// A one, two, or three dimensional ROOT histogram such as `TH1D`,
+/// `TH2F`, or `TH3D`. The bin contents include the under- and
+/// overflow bins of each axis and are stored in ROOT's order,
+/// i.e. the global bin of the bin `(ix, iy, iz)` is `ix + (nx + 2) *
+/// (iy + (ny + 2) * iz)` with the underflow bin at index 0 along each
+/// axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TH1 {
+    name: String,
+    title: String,
+    axes: Vec<TAxis>,
+    entries: f64,
+    contents: Vec<f64>,
+    /// Sum of the squares of the weights in each bin; empty if not tracked
+    sumw2: Vec<f64>,
+}
+
+impl TH1 {
+    /// A new histogram with the given axes and all bins set to zero
+    pub fn new(name: &str, title: &str, axes: Vec<TAxis>) -> Result<TH1, Error> {
+        if axes.is_empty() || axes.len() > 3 {
+            return Err(format_err!("Histograms must have 1 to 3 dimensions; got {}", axes.len()));
+        }
+        if let Some(ax) = axes.iter().find(|ax| ax.edges().len() < 2) {
+            return Err(format_err!("Axis {} has no bins", ax.name()));
+        }
+        let n_cells = axes.iter().map(|ax| ax.nbins() + 2).product();
+        Ok(TH1 {
+            name: name.to_string(),
+            title: title.to_string(),
+            axes,
+            entries: 0.0,
+            contents: vec![0.0; n_cells],
+            sumw2: vec![],
+        })
+    }
+
+    /// Name of this histogram
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Title of this histogram
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Number of dimensions (1 to 3)
+    pub fn dim(&self) -> usize {
+        self.axes.len()
+    }
+
+    /// The axes of this histogram
+    pub fn axes(&self) -> &[TAxis] {
+        &self.axes
+    }
+
+    /// Number of times this histogram was filled
+    pub fn entries(&self) -> f64 {
+        self.entries
+    }
+
+    /// Content of all bins including under- and overflow bins
+    pub fn contents(&self) -> &[f64] {
+        &self.contents
+    }
+
+    /// Sum of the squared weights of all bins including under- and
+    /// overflow bins. Empty if the histogram does not keep track of it
+    pub fn sumw2(&self) -> &[f64] {
+        &self.sumw2
+    }
+
+    /// Statistical error of each bin including under- and overflow
+    /// bins. This is the square root of the sum of squared weights if
+    /// it is tracked and the square root of the content otherwise.
+    pub fn errors(&self) -> Vec<f64> {
+        if self.sumw2.is_empty() {
+            self.contents.iter().map(|c| c.abs().sqrt()).collect()
+        } else {
+            self.sumw2.iter().map(|w2| w2.sqrt()).collect()
+        }
+    }
+
+    /// Global index of the bin with the given index along each
+    /// axis. Index 0 is the underflow bin of an axis and `nbins + 1`
+    /// the overflow bin
+    pub fn global_bin(&self, idxs: &[usize]) -> usize {
+        self.axes.iter().zip(idxs.iter()).rev()
+            .fold(0, |global, (ax, idx)| global * (ax.nbins() + 2) + idx)
+    }
+
+    /// Set the number of times this histogram was filled
+    pub fn set_entries(&mut self, entries: f64) {
+        self.entries = entries;
+    }
+
+    /// Mutable access to the content of all bins including under- and overflow bins
+    pub fn contents_mut(&mut self) -> &mut [f64] {
+        &mut self.contents
+    }
+
+    /// Mutable access to the sum of squared weights of all bins. If
+    /// they were not tracked so far, they are initialized to zero
+    pub fn sumw2_mut(&mut self) -> &mut [f64] {
+        if self.sumw2.is_empty() {
+            self.sumw2 = vec![0.0; self.contents.len()];
+        }
+        &mut self.sumw2
+    }
+
+    /// The index along each axis of the given global bin
+    fn bin_indices(&self, global: usize) -> Vec<usize> {
+        let mut rest = global;
+        self.axes.iter()
+            .map(|ax| {
+                let idx = rest % (ax.nbins() + 2);
+                rest /= ax.nbins() + 2;
+                idx
+            })
+            .collect()
+    }
+
+    /// Center of the bin `idx` of axis `axis`. `idx` includes the underflow bin
+    fn center(&self, axis: usize, idx: usize) -> f64 {
+        let edges = self.axes[axis].edges();
+        0.5 * (edges[idx - 1] + edges[idx])
+    }
+
+    /// Sums of `w`, `w^2`, `w*x`, `w*x^2`, ... over all bins which are
+    /// not under- or overflow bins; ROOT stores these for computing
+    /// statistics. Returns `[w, w2, wx, wx2, wy, wy2, wxy, wz, wz2, wxz, wyz]`
+    fn stats(&self) -> [f64; 11] {
+        let mut s = [0.0; 11];
+        for (global, w) in self.contents.iter().enumerate() {
+            let idxs = self.bin_indices(global);
+            if idxs.iter().zip(self.axes.iter()).any(|(i, ax)| *i == 0 || *i > ax.nbins()) {
+                continue;
+            }
+            let x: Vec<_> = (0..self.dim()).map(|axis| self.center(axis, idxs[axis])).collect();
+            let coord = |axis: usize| x.get(axis).cloned().unwrap_or(0.0);
+            let (x, y, z) = (coord(0), coord(1), coord(2));
+            s[0] += w;
+            s[1] += if self.sumw2.is_empty() { *w } else { self.sumw2[global] };
+            s[2] += w * x;
+            s[3] += w * x * x;
+            s[4] += w * y;
+            s[5] += w * y * y;
+            s[6] += w * x * y;
+            s[7] += w * z;
+            s[8] += w * z * z;
+            s[9] += w * x * z;
+            s[10] += w * y * z;
+        }
+        s
+    }
+
+    /// Write the part of this histogram belonging to ROOT's `TH1` base class
+    fn stream_th1(&self, buf: &mut WBuffer, stats: &[f64; 11]) {
+        let default_axis = |name: &str| TAxis::new(name, "", vec![0.0, 1.0]);
+        buf.put_version(8, |w| {
+            TNamed {name: self.name.clone(), title: self.title.clone()}.stream(w);
+            put_tattline(w);
+            put_tattfill(w);
+            put_tattmarker(w);
+            w.put_i32(self.contents.len() as i32);
+            for (i, name) in ["xaxis", "yaxis", "zaxis"].iter().enumerate() {
+                match self.axes.get(i) {
+                    Some(ax) => ax.stream(w),
+                    None => default_axis(name).stream(w),
+                }
+            }
+            // Bar offset and width
+            w.put_i16(0);
+            w.put_i16(1000);
+            w.put_f64(self.entries);
+            for s in &stats[..4] {
+                w.put_f64(*s);
+            }
+            w.put_f64(UNSET_MIN_MAX);
+            w.put_f64(UNSET_MIN_MAX);
+            // Normalization factor
+            w.put_f64(0.0);
+            // Contour levels
+            put_tarrayd(w, &[]);
+            put_tarrayd(w, &self.sumw2);
+            // Options
+            w.put_string("");
+            // List of functions; streamed in place
+            TList {ver: 5, tobj: TObject::default(), name: "".to_string(), len: 0, objs: vec![]}
+                .stream(w);
+            // Buffer size and (empty) buffer
+            w.put_i32(0);
+            w.put_u8(0);
+            // Bin error option (normal) and statistics overflow option (neutral)
+            w.put_i32(0);
+            w.put_i32(2);
+        });
+    }
+}
+
+impl Streamable for TH1 {
+    /// Histograms are always written with double precision
+    fn class_name(&self) -> &str {
+        ["TH1D", "TH2D", "TH3D"][self.dim() - 1]
+    }
+
+    /// Counterpart to the `th1` parser
+    fn stream(&self, buf: &mut WBuffer) {
+        let stats = self.stats();
+        let ver = if self.dim() == 1 { 3 } else { 4 };
+        buf.put_version(ver, |w| {
+            match self.dim() {
+                1 => self.stream_th1(w, &stats),
+                2 => w.put_version(5, |w| {
+                    self.stream_th1(w, &stats);
+                    // Scale factor
+                    w.put_f64(1.0);
+                    for s in &stats[4..7] {
+                        w.put_f64(*s);
+                    }
+                }),
+                _ => w.put_version(6, |w| {
+                    self.stream_th1(w, &stats);
+                    // TAtt3D has no members
+                    w.put_version(1, |_| {});
+                    for s in &stats[4..] {
+                        w.put_f64(*s);
+                    }
+                }),
+            }
+            put_tarrayd(w, &self.contents);
+        });
+    }
+}
+
+/// Parse the `TH1` base class of a histogram
+#[allow(unused_variables)]
+fn th1_base(input: &[u8], dim: usize) -> IResult<&[u8], TH1> {
+    do_parse!(input,
+              _ver: be_u16 >>
+              tnamed: length_value!(checked_byte_count, tnamed) >>
+              _tattline: length_data!(checked_byte_count) >>
+              _tattfill: length_data!(checked_byte_count) >>
+              _tattmarker: length_data!(checked_byte_count) >>
+              _ncells: be_i32 >>
+              axes: count!(length_value!(checked_byte_count, taxis), 3) >>
+              _bar_offset: be_i16 >>
+              _bar_width: be_i16 >>
+              entries: be_f64 >>
+              _stats: count!(be_f64, 4) >>
+              _max_min_norm: count!(be_f64, 3) >>
+              _contour: tarrayd >>
+              sumw2: tarrayd >>
+              _option: string >>
+              _functions: length_data!(checked_byte_count) >>
+              _rest: rest >>
+              ({
+                  TH1 {
+                      name: tnamed.name,
+                      title: tnamed.title,
+                      axes: axes.into_iter().take(dim).collect(),
+                      entries,
+                      contents: vec![],
+                      sumw2,
+                  }
+              }))
+}
+
+/// Parse the base class of a histogram of the given dimension;
+/// `TH2` and `TH3` wrap `TH1`
+fn thn_base(input: &[u8], dim: usize) -> IResult<&[u8], TH1> {
+    if dim == 1 {
+        th1_base(input, dim)
+    } else {
+        do_parse!(input,
+                  _ver: be_u16 >>
+                  th1: length_value!(checked_byte_count, apply!(th1_base, dim)) >>
+                  // Additional statistics which are recomputed when writing
+                  _rest: rest >>
+                  (th1))
+    }
+}
+
+/// Parse the `TArray` holding the bin contents of a histogram. `t` is
+/// the last letter of the histogram's class name, e.g. `F` for `TH1F`
+fn tarray_as_f64(input: &[u8], t: char) -> IResult<&[u8], Vec<f64>> {
+    match t {
+        'D' => tarrayd(input),
+        'F' => length_count!(input, be_i32, map!(be_f32, f64::from)),
+        'I' => length_count!(input, be_i32, map!(be_i32, f64::from)),
+        'S' => length_count!(input, be_i32, map!(be_i16, f64::from)),
+        'C' => length_count!(input, be_i32, map!(be_i8, f64::from)),
+        _ => IResult::Error(ErrorKind::Custom(0)),
+    }
+}
+
+/// Parse a histogram of the class `class_name`, e.g. `TH2F`. The
+/// context is not used, but allows to use this function with
+/// `FileItem::parse_with`.
+pub(crate) fn th1<'s>(input: &'s [u8], _context: &'s Context<'s>, class_name: &str)
+                      -> IResult<&'s [u8], TH1> {
+    if !class_name.starts_with("TH") {
+        return IResult::Error(ErrorKind::Custom(0));
+    }
+    let (dim, t) = {
+        let mut chars = class_name.chars().skip(2);
+        match (chars.next().and_then(|c| c.to_digit(10)), chars.next()) {
+            (Some(dim @ 1..=3), Some(t)) => (dim as usize, t),
+            _ => return IResult::Error(ErrorKind::Custom(0)),
+        }
+    };
+    do_parse!(input,
+              _ver: be_u16 >>
+              th: length_value!(checked_byte_count, apply!(thn_base, dim)) >>
+              contents: apply!(tarray_as_f64, t) >>
+              (TH1 {contents, ..th}))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use core::*;
+    use super::*;
+
+    fn axis(name: &str, edges: &[f64]) -> TAxis {
+        TAxis::new(name, &format!("{} title", name), edges.to_vec())
+    }
+
+    #[test]
+    fn global_bins() {
+        let h = TH1::new("h", "", vec![axis("xaxis", &[0., 1., 2.]), axis("yaxis", &[0., 1.])])
+            .unwrap();
+        assert_eq!(h.contents().len(), 4 * 3);
+        assert_eq!(h.global_bin(&[0, 0]), 0);
+        assert_eq!(h.global_bin(&[1, 0]), 1);
+        assert_eq!(h.global_bin(&[1, 1]), 5);
+        assert_eq!(h.bin_indices(5), vec![1, 1]);
+        assert!(TH1::new("h", "", vec![]).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let mut h = TH1::new("h", "", vec![axis("xaxis", &[0., 1.])]).unwrap();
+        h.contents_mut().copy_from_slice(&[4., 9., 0.]);
+        assert_eq!(h.errors(), vec![2., 3., 0.]);
+        h.sumw2_mut().copy_from_slice(&[1., 4., 0.]);
+        assert_eq!(h.errors(), vec![1., 2., 0.]);
+    }
+
+    #[test]
+    fn write_and_read_th1() {
+        let path = env::temp_dir().join("root-io-th1.root");
+        let mut hists = vec![
+            TH1::new("h1", "1D", vec![axis("xaxis", &[0., 1., 2., 3.])]).unwrap(),
+            TH1::new("h2", "2D", vec![axis("xaxis", &[0., 1., 5.]),
+                                      axis("yaxis", &[-1., 0., 1.])]).unwrap(),
+            TH1::new("h3", "3D", vec![axis("xaxis", &[0., 1.]),
+                                      axis("yaxis", &[0., 0.5, 1.]),
+                                      axis("zaxis", &[1., 10., 100., 1000.])]).unwrap(),
+        ];
+        for h in &mut hists {
+            for (i, c) in h.contents_mut().iter_mut().enumerate() {
+                *c = i as f64;
+            }
+            for (i, c) in h.sumw2_mut().iter_mut().enumerate() {
+                *c = 2.0 * i as f64;
+            }
+            h.set_entries(42.0);
+        }
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            for h in &hists {
+                f.write_object(h.name(), h.title(), h).unwrap();
+            }
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+        let streamers: Vec<_> = f.streamers().unwrap().iter().map(|s| s.name().to_string()).collect();
+        for class in &["TH1D", "TH2D", "TH3D", "TH1", "TAxis", "TAttAxis", "TArrayD", "TList"] {
+            assert!(streamers.contains(&class.to_string()), "{}", class);
+        }
+        for (item, h) in f.items().iter().zip(hists.iter()) {
+            let class = item.tkey_hdr.class_name.clone();
+            assert_eq!(class, h.class_name());
+            let read = item.parse_with(|i, c| th1(i, c, &class)).unwrap();
+            assert_eq!(&read, h);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use nom::*;
+
+use core::*;
+use histograms::th1::{th1, TH1};
+
+/// How the error of the mean in each bin of a profile is computed;
+/// See ROOT's `TProfile::SetErrorOption`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorMode {
+    /// Error of the mean (default)
+    Mean,
+    /// Spread of the values
+    Spread,
+    /// Spread of the values; `1/sqrt(12 * n)` for bins with zero spread
+    SpreadI,
+    /// The values were filled with weights `1/sigma^2`
+    SpreadG,
+}
+
+/// A profile histogram (`TProfile` or `TProfile2D`), i.e. the mean
+/// of some value in each bin
+#[derive(Debug, Clone, PartialEq)]
+pub struct TProfile {
+    /// Sums of the weighted values in each bin; the sums of their
+    /// squares are stored as `sumw2`
+    hist: TH1,
+    /// Sum of the weights in each bin
+    bin_entries: Vec<f64>,
+    error_mode: ErrorMode,
+    /// Sum of the squared weights in each bin; empty if not tracked
+    bin_sumw2: Vec<f64>,
+}
+
+impl TProfile {
+    /// The underlying histogram. Its contents are the (weighted) sums
+    /// of the values in each bin and not their mean
+    pub fn hist(&self) -> &TH1 {
+        &self.hist
+    }
+
+    /// Sum of the weights in each bin including under- and overflow bins
+    pub fn bin_entries(&self) -> &[f64] {
+        &self.bin_entries
+    }
+
+    /// How the errors of the means are computed
+    pub fn error_mode(&self) -> ErrorMode {
+        self.error_mode
+    }
+
+    /// Effective number of entries in the given global bin
+    fn effective_entries(&self, bin: usize) -> f64 {
+        let sum = self.bin_entries[bin];
+        match self.bin_sumw2.get(bin) {
+            Some(&sumw2) if sumw2 > 0.0 => sum * sum / sumw2,
+            _ => sum,
+        }
+    }
+
+    /// Mean value in each bin including under- and overflow bins
+    pub fn means(&self) -> Vec<f64> {
+        self.hist.contents().iter().zip(self.bin_entries.iter())
+            .map(|(c, n)| if *n == 0.0 { 0.0 } else { c / n })
+            .collect()
+    }
+
+    /// Error of each bin according to the profile's `ErrorMode`. This
+    /// follows ROOT's `TProfile::GetBinError`
+    pub fn errors(&self) -> Vec<f64> {
+        let sumw2 = self.hist.sumw2();
+        (0..self.bin_entries.len())
+            .map(|bin| {
+                let sum = self.bin_entries[bin];
+                if sum == 0.0 {
+                    return 0.0;
+                }
+                if self.error_mode == ErrorMode::SpreadG {
+                    return 1.0 / sum.sqrt();
+                }
+                let mean = self.hist.contents()[bin] / sum;
+                let spread = (sumw2.get(bin).cloned().unwrap_or(0.0) / sum - mean * mean).abs().sqrt();
+                let neff = self.effective_entries(bin);
+                match self.error_mode {
+                    ErrorMode::Spread => spread,
+                    ErrorMode::SpreadI if spread == 0.0 => 1.0 / (12.0 * neff).sqrt(),
+                    _ => spread / neff.sqrt(),
+                }
+            })
+            .collect()
+    }
+
+    /// A histogram of the mean in each bin; the squared errors of the
+    /// means are stored as its `sumw2`. This is the histogram which
+    /// ROOT draws for a profile and may be converted to a
+    /// `histogram::Histogram`
+    pub fn to_th1(&self) -> TH1 {
+        let mut h = self.hist.clone();
+        h.contents_mut().copy_from_slice(&self.means());
+        for (w2, e) in h.sumw2_mut().iter_mut().zip(self.errors().iter()) {
+            *w2 = e * e;
+        }
+        h
+    }
+}
+
+/// Parse a `TProfile` or `TProfile2D`
+pub(crate) fn tprofile<'s>(input: &'s [u8], context: &'s Context<'s>, class_name: &str)
+                           -> IResult<&'s [u8], TProfile> {
+    let base = match class_name {
+        "TProfile" => "TH1D",
+        "TProfile2D" => "TH2D",
+        _ => return IResult::Error(ErrorKind::Custom(0)),
+    };
+    do_parse!(input,
+              _ver: be_u16 >>
+              hist: length_value!(checked_byte_count, apply!(th1, context, base)) >>
+              bin_entries: tarrayd >>
+              error_mode: be_i32 >>
+              // Limits of the filled values, sum of `w*y` and `w*y^2`
+              _limits_and_sums: count!(be_f64, 4) >>
+              bin_sumw2: tarrayd >>
+              ({
+                  let error_mode = match error_mode {
+                      1 => ErrorMode::Spread,
+                      2 => ErrorMode::SpreadI,
+                      3 => ErrorMode::SpreadG,
+                      _ => ErrorMode::Mean,
+                  };
+                  TProfile {hist, bin_entries, error_mode, bin_sumw2}
+              }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use core::*;
+    use histograms::TAxis;
+    use super::*;
+
+    /// Write a profile the way ROOT's `TProfile` does
+    struct Profile(TProfile, i32);
+
+    impl Streamable for Profile {
+        fn class_name(&self) -> &str {
+            "TProfile"
+        }
+        fn stream(&self, buf: &mut WBuffer) {
+            let p = &self.0;
+            buf.put_version(7, |w| {
+                p.hist.stream(w);
+                put_tarrayd(w, &p.bin_entries);
+                w.put_i32(self.1);
+                for _ in 0..4 {
+                    w.put_f64(0.0);
+                }
+                put_tarrayd(w, &p.bin_sumw2);
+            });
+        }
+    }
+
+    #[test]
+    fn read_profile() {
+        let path = env::temp_dir().join("root-io-tprofile.root");
+        // One bin filled with the values 1 and 3, the other with 5
+        let mut hist = TH1::new("p", "Profile", vec![TAxis::new("xaxis", "x", vec![0., 1., 2.])])
+            .unwrap();
+        hist.contents_mut().copy_from_slice(&[0., 4., 5., 0.]);
+        hist.sumw2_mut().copy_from_slice(&[0., 10., 25., 0.]);
+        let p = TProfile {
+            hist,
+            bin_entries: vec![0., 2., 1., 0.],
+            error_mode: ErrorMode::Mean,
+            bin_sumw2: vec![],
+        };
+        {
+            let mut f = RootFileWriter::create(&path).unwrap();
+            f.write_object("p", "Profile", &Profile(p.clone(), 0)).unwrap();
+            f.write_object("p_spread", "Profile", &Profile(p.clone(), 1)).unwrap();
+            f.close().unwrap();
+        }
+        let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+        let read = f.items()[0].as_tprofile().unwrap();
+        assert_eq!(read, p);
+        assert_eq!(read.means(), vec![0., 2., 5., 0.]);
+        assert_eq!(read.errors(), vec![0., 1. / 2f64.sqrt(), 0., 0.]);
+        assert_eq!(read.to_th1().contents(), &[0., 2., 5., 0.]);
+        assert_eq!(read.hist().axes()[0].title(), "x");
+
+        let spread = f.items()[1].as_tprofile().unwrap();
+        assert_eq!(spread.error_mode(), ErrorMode::Spread);
+        assert_eq!(spread.errors(), vec![0., 1., 0., 0.]);
+        assert!(f.items()[0].as_th1().is_err());
+    }
+}
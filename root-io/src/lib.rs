@@ -25,14 +25,26 @@ extern crate quote;
 extern crate failure;
 extern crate flate2;
 extern crate xz2;
+#[cfg(feature = "histogram")]
+extern crate histogram;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_buffer;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "npy")]
+extern crate npy_writer;
 
 // pub mod core_types;
 pub mod core;
 mod code_gen;
 pub mod tree_reader;
+pub mod tree_writer;
+pub mod histograms;
 mod tests;
 
-pub use core::{RootFile, FileItem};
+pub use core::{RootFile, FileItem, RootFileWriter, RootObject};
 
 /// Offset when using Context; should be in `Context`, maybe?
 const MAP_OFFSET: u64 = 2;
@@ -0,0 +1,140 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use core::*;
+
+/// Helpers of the generated binary; `main` is appended to it
+const MAIN_HEAD: &str = r#"extern crate failure;
+#[macro_use]
+extern crate nom;
+extern crate root_io;
+
+use root_io::{FileItem, RootFile, RootObject};
+use root_io::tree_reader::Tree;
+
+/// Apply `f` to the first `TTree` in `items`, looking into directories
+fn parse_tree<F>(items: &[FileItem], f: &F) -> Option<i64>
+    where F: Fn(&FileItem) -> i64
+{
+    for item in items {
+        if item.name().ends_with("`TTree`") {
+            return Some(f(item));
+        }
+        if let Ok(RootObject::Directory(items)) = item.object() {
+            if let Some(n) = parse_tree(&items, f) {
+                return Some(n);
+            }
+        }
+    }
+    None
+}
+
+fn check<F: Fn(&FileItem) -> i64>(path: &str, f: F) {
+    let file = RootFile::new_from_file(path.as_ref()).unwrap();
+    let entries = parse_tree(file.items(), &f).expect("No tree found");
+    assert!(entries > 0, "{} has no entries", path);
+}
+
+/// Check that the model iterator `count` yields as many entries as
+/// the first `TTree` of the file claims to have
+fn check_model<F, G>(path: &str, entries: F, count: G)
+    where F: Fn(&FileItem) -> i64,
+          G: Fn(&Tree) -> usize
+{
+    let file = RootFile::new_from_file(path.as_ref()).unwrap();
+    let item = file.items().iter().find(|i| i.name().ends_with("`TTree`")).unwrap();
+    assert_eq!(count(&item.as_tree().unwrap()) as i64, entries(item), "{}", path);
+}
+"#;
+
+/// Generate the code for the streamers of every test file and
+/// compile it into a binary which parses the first `TTree` of each
+/// file with the generated parser. A model of all branches of the
+/// first top-level tree is generated as well and iterated over.
+#[test]
+fn compile_generated_code() {
+    let crate_dir = env::temp_dir().join("root-io-generated-code");
+    let src_dir = crate_dir.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+
+    let mut paths: Vec<_> = fs::read_dir("./src/test_data").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "root"))
+        .collect();
+    paths.sort();
+    let mut main = MAIN_HEAD.to_string();
+    let mut checks = String::new();
+    for p in &paths {
+        let module = p.file_stem().unwrap().to_str().unwrap()
+            .replace(|c: char| !c.is_alphanumeric(), "_")
+            .to_lowercase();
+        let module = format!("f_{}", module);
+        let f = RootFile::new_from_file(p).unwrap();
+        let mut code = String::new();
+        f.streamer_info_as_rust(&mut code).unwrap();
+        File::create(src_dir.join(format!("{}.rs", module))).unwrap()
+            .write_all(code.as_bytes()).unwrap();
+        main += &format!("#[allow(dead_code, non_camel_case_types, non_snake_case)]\nmod {};\n", module);
+        // LZ4 compression is not supported
+        if module.contains("lz4") {
+            continue;
+        }
+        let path = fs::canonicalize(p).unwrap();
+        let entries = format!("|i| i.parse_with(|i, c| map!(i, apply!({}::ttree, c), |t| t.fentries)).unwrap()",
+                              module);
+        checks += &format!("    check({:?}, {});\n", path, entries);
+
+        // Trees of some old files cannot be read, yet
+        let model = f.items().iter()
+            .find(|i| i.tkey_hdr.class_name == "TTree")
+            .and_then(|i| {
+                let mut code = String::new();
+                f.tree_model_as_rust(&i.tkey_hdr.obj_name, &["*"], "Model", &mut code).ok()?;
+                Some(code)
+            });
+        if let Some(code) = model {
+            let model_module = format!("m{}", &module[1..]);
+            File::create(src_dir.join(format!("{}.rs", model_module))).unwrap()
+                .write_all(code.as_bytes()).unwrap();
+            main += &format!("#[allow(dead_code)]\nmod {};\n", model_module);
+            checks += &format!("    check_model({:?}, {}, |t| {}::ModelIntoIter::new(t).unwrap().count());\n",
+                               path, entries, model_module);
+        }
+    }
+    main += &format!("\nfn main() {{\n{}}}\n", checks);
+    File::create(src_dir.join("main.rs")).unwrap()
+        .write_all(main.as_bytes()).unwrap();
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let manifest = format!(r#"[package]
+name = "root-io-generated-code"
+version = "0.1.0"
+authors = []
+
+[workspace]
+
+[dependencies]
+nom = "3"
+failure = "0.1"
+root-io = {{ path = {:?} }}
+"#, manifest_dir);
+    File::create(crate_dir.join("Cargo.toml")).unwrap()
+        .write_all(manifest.as_bytes()).unwrap();
+    // Use the same versions of the dependencies as this crate
+    let lock = manifest_dir.join("../Cargo.lock");
+    if lock.exists() {
+        fs::copy(lock, crate_dir.join("Cargo.lock")).unwrap();
+    }
+
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .args(["run", "--quiet"])
+        .current_dir(&crate_dir)
+        .env("CARGO_TARGET_DIR", crate_dir.join("target"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -6,3 +6,11 @@ mod read_esd;
 mod basic_io;
 #[cfg(test)]
 mod high_level_io;
+#[cfg(test)]
+mod write_tree;
+#[cfg(all(test, feature = "histogram"))]
+mod write_histogram;
+#[cfg(test)]
+mod generated_code;
+#[cfg(test)]
+mod read_histograms;
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use core::RootFile;
+
+/// `histograms.root` is not part of the repository; it is created
+/// with ROOT by running `src/test_data/make_histograms.C` in
+/// `src/test_data`
+fn open() -> RootFile {
+    let path = PathBuf::from("./src/test_data/histograms.root");
+    RootFile::new_from_file(&path).expect("Failed to open file")
+}
+
+#[test]
+#[ignore = "needs src/test_data/histograms.root made by make_histograms.C"]
+fn read_th1f() {
+    let f = open();
+    let h = f.items().iter().find(|i| i.tkey_hdr.obj_name == "h1f").unwrap().as_th1().unwrap();
+    assert_eq!(h.name(), "h1f");
+    assert_eq!(h.title(), "A TH1F");
+    assert_eq!(h.axes()[0].title(), "x");
+    assert_eq!(h.axes()[0].edges(), &[0., 1., 2., 3., 4.]);
+    assert!(h.axes()[0].is_equal_width());
+    assert_eq!(h.entries(), 6.);
+    assert_eq!(h.contents(), &[1., 1., 4., 0., 0.5, 1.]);
+    assert_eq!(h.sumw2(), &[1., 1., 8., 0., 0.25, 1.]);
+}
+
+#[test]
+#[ignore = "needs src/test_data/histograms.root made by make_histograms.C"]
+fn read_th2d() {
+    let f = open();
+    let h = f.items().iter().find(|i| i.tkey_hdr.obj_name == "h2d").unwrap().as_th1().unwrap();
+    assert_eq!(h.dim(), 2);
+    assert_eq!(h.axes()[1].edges(), &[-1.5, -0.5, 0.5, 1.5]);
+    assert_eq!(h.contents().len(), 4 * 5);
+    assert_eq!(h.contents()[h.global_bin(&[1, 2])], 1.);
+    assert_eq!(h.contents()[h.global_bin(&[2, 3])], 3.);
+    assert_eq!(h.contents().iter().sum::<f64>(), 4.);
+    assert_eq!(h.entries(), 2.);
+}
+
+#[test]
+#[ignore = "needs src/test_data/histograms.root made by make_histograms.C"]
+fn read_tprofile() {
+    let f = open();
+    let p = f.items().iter().find(|i| i.tkey_hdr.obj_name == "prof").unwrap().as_tprofile().unwrap();
+    assert_eq!(p.bin_entries(), &[0., 2., 1., 0.]);
+    assert_eq!(p.means(), vec![0., 2., 5., 0.]);
+    // Values 1 and 3 in the first bin have a spread of 1
+    assert!((p.errors()[1] - 1. / 2f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+#[ignore = "needs src/test_data/histograms.root made by make_histograms.C"]
+fn read_tgraphasymmerrors() {
+    let f = open();
+    let g = f.items().iter().find(|i| i.tkey_hdr.obj_name == "graph").unwrap().as_graph().unwrap();
+    assert_eq!(g.name(), "graph");
+    assert_eq!(g.title(), "A graph");
+    assert_eq!(g.x(), &[0., 1., 2.]);
+    assert_eq!(g.y(), &[0., 1., 4.]);
+    assert_eq!(g.x_errors(), (&[0.1, 0.1, 0.1][..], &[0.2, 0.2, 0.2][..]));
+    assert_eq!(g.y_errors(), (&[0., 1., 2.][..], &[0., 2., 4.][..]));
+}
@@ -0,0 +1,131 @@
+use std::env;
+use std::path::PathBuf;
+
+use histogram::{Histogram, HistogramBuilder};
+
+use {RootFile, RootFileWriter};
+
+#[test]
+fn write_and_read_histograms() {
+    let path = env::temp_dir().join("root-io-histograms.root");
+    let mut h1 = HistogramBuilder::<[usize; 1]>::new()
+        .add_equal_width_axis(10, -5., 5.)
+        .with_label("x")
+        .build::<u64>()
+        .unwrap();
+    let mut h2 = HistogramBuilder::<[usize; 2]>::new()
+        .add_variable_width_axis(&[0., 1., 10., 100.])
+        .add_equal_width_axis(4, 0., 1.)
+        .build::<f64>()
+        .unwrap();
+    let mut h3 = HistogramBuilder::<[usize; 3]>::new()
+        .add_equal_width_axis(2, 0., 1.)
+        .add_equal_width_axis(3, 0., 1.)
+        .add_variable_width_axis(&[-1., 0., 2.])
+        .build::<f32>()
+        .unwrap();
+    for i in 0..100 {
+        let x = f64::from(i) / 100.;
+        h1.fill(&[10. * x - 5.]);
+        h2.fill(&[100. * x, x]);
+        h3.fill(&[x, 1. - x, 3. * x - 1.]);
+    }
+    {
+        let mut f = RootFileWriter::create(&path).unwrap();
+        f.write_histogram("h1", "One dimension", &h1).unwrap();
+        f.write_histogram("h2", "Two dimensions", &h2).unwrap();
+        f.write_histogram("h3", "Three dimensions", &h3).unwrap();
+        f.close().unwrap();
+    }
+
+    let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+    let items = f.items();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].name(), "`h2` of type `TH2D`");
+
+    let th = items[0].as_th1().unwrap();
+    assert_eq!(th.title(), "One dimension");
+    assert_eq!(th.entries(), 100.);
+    assert_eq!(th.axes()[0].edges(), h1.edges(0).as_slice());
+    assert_eq!(th.axes()[0].title(), "x");
+
+    // Read back with under- and overflow bins, which are empty here
+    let r1: Histogram<u64, [usize; 1]> = items[0].as_histogram().unwrap();
+    assert_eq!(r1.edges(0), h1.edges(0));
+    assert_eq!(r1.label(0), "x");
+    assert_eq!(r1.in_range(), h1.counts);
+    assert_eq!(r1.entries(), 100);
+    assert!(r1.has_flow_bins(0));
+    let r2: Histogram<f64, [usize; 2]> = items[1].as_histogram().unwrap();
+    assert_eq!(r2.edges(0), h2.edges(0));
+    assert_eq!(r2.edges(1), h2.edges(1));
+    assert_eq!(r2.in_range(), h2.counts);
+    let r3: Histogram<f32, [usize; 3]> = items[2].as_histogram().unwrap();
+    assert_eq!(r3.edges(2), h3.edges(2));
+    assert_eq!(r3.in_range(), h3.counts);
+
+    // Wrong dimensions
+    assert!(items[0].as_histogram::<Histogram<f64, [usize; 2]>>().is_err());
+}
+
+#[test]
+fn write_flow_bins() {
+    let path = env::temp_dir().join("root-io-flow-bins.root");
+    let mut h = HistogramBuilder::<[usize; 1]>::new()
+        .add_equal_width_axis(2, 0., 1.)
+        .with_flow_bins()
+        .build::<f64>()
+        .unwrap();
+    h.extend(vec![[-1.], [0.25], [2.], [2.]]);
+    {
+        let mut f = RootFileWriter::create(&path).unwrap();
+        f.write_histogram("h", "Flow bins", &h).unwrap();
+        f.close().unwrap();
+    }
+    let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+    let th = f.items()[0].as_th1().unwrap();
+    assert_eq!(th.contents(), &[1., 1., 0., 2.]);
+    assert_eq!(th.entries(), 4.);
+    let r: Histogram<f64, [usize; 1]> = f.items()[0].as_histogram().unwrap();
+    assert_eq!(r.counts, h.counts);
+}
+
+#[test]
+fn round_trip_weighted() {
+    let path = env::temp_dir().join("root-io-round-trip.root");
+    let mut h = HistogramBuilder::<[usize; 2]>::new()
+        .add_equal_width_axis(3, -1.5, 1.5)
+        .with_flow_bins()
+        .add_variable_width_axis(&[0., 1., 10.])
+        .with_flow_bins()
+        .build::<f64>()
+        .unwrap();
+    h.enable_sumw2();
+    h.fill_weighted(&[0., 0.5], 2.);
+    h.fill_weighted(&[0., 0.5], 3.);
+    h.fill_weighted(&[-5., 20.], 0.5);
+    h.fill(&[1., 5.]);
+    {
+        let mut f = RootFileWriter::create(&path).unwrap();
+        f.write_histogram("h", "Weighted", &h).unwrap();
+        f.close().unwrap();
+    }
+    let f = RootFile::new_from_file(&PathBuf::from(&path)).unwrap();
+    let th = f.items()[0].as_th1().unwrap();
+    assert!(th.axes()[0].is_equal_width());
+    assert!(!th.axes()[1].is_equal_width());
+    assert_eq!(th.entries(), 4.);
+    // Bin (0, 0.5) is filled with weights 2 and 3
+    assert_eq!(th.errors()[th.global_bin(&[2, 1])], 13f64.sqrt());
+
+    let mut r: Histogram<f64, [usize; 2]> = f.items()[0].as_histogram().unwrap();
+    assert_eq!(r.counts, h.counts);
+    assert_eq!(r.sumw2(), h.sumw2());
+    assert_eq!(r.entries(), h.entries());
+    assert_eq!(r.errors(), h.errors());
+    // Same binning, so the histograms can be added up
+    r.add(&h).unwrap();
+    assert_eq!(r.counts, h.counts.mapv(|c| 2. * c));
+    assert_eq!(r.sumw2(), Some(&h.sumw2().unwrap().mapv(|w2| 2. * w2)));
+    assert_eq!(r.entries(), 8);
+}
@@ -0,0 +1,153 @@
+use std::env;
+use std::path::PathBuf;
+
+use nom::{be_f32, be_i32, be_i64, be_u16, be_u64, be_u8};
+
+use tree_reader::{ColumnFixedIntoIter, ColumnVarIntoIter, Tree};
+use tree_writer::TreeWriter;
+use {RootFile, RootFileWriter};
+
+/// A simplified event as it could be read from one of the ALICE files
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    run: i64,
+    multiplicity: u16,
+    trigger: u8,
+    vertex: [f32; 3],
+    pt: Vec<f32>,
+    flags: Vec<u64>,
+}
+
+fn events(n: usize) -> Vec<Event> {
+    (0..n)
+        .map(|i| {
+            let ntracks = (i * 7) % 13;
+            Event {
+                run: 244_918 + (i / 50) as i64,
+                multiplicity: (i % 500) as u16,
+                trigger: (i % 3) as u8,
+                vertex: [0.1 * i as f32, -0.2 * i as f32, i as f32],
+                pt: (0..ntracks).map(|j| (i + j) as f32 * 0.25).collect(),
+                flags: (0..ntracks).map(|j| ((i * j) as u64) << 33).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Write the events passing `filter` to a new file
+fn write_skim<F>(path: &PathBuf, events: &[Event], compression: u32, filter: F)
+    where F: Fn(&Event) -> bool
+{
+    let mut f = RootFileWriter::create(path).unwrap();
+    f.set_compression(compression);
+    let mut tree = TreeWriter::new("events", "Skimmed events");
+    // Small baskets to force several of them per branch
+    tree.set_basket_size(512);
+    let run = tree.add_branch::<i64>("run").unwrap();
+    let multiplicity = tree.add_branch::<u16>("multiplicity").unwrap();
+    let trigger = tree.add_branch::<u8>("trigger").unwrap();
+    let vertex = tree.add_array_branch::<f32>("vertex", 3).unwrap();
+    let ntracks = tree.add_branch::<i32>("ntracks").unwrap();
+    let pt = tree.add_jagged_branch::<f32, _>("pt", ntracks).unwrap();
+    let flags = tree.add_jagged_branch::<u64, _>("flags", ntracks).unwrap();
+    for ev in events.iter().filter(|ev| filter(ev)) {
+        tree.set(run, ev.run).unwrap();
+        tree.set(multiplicity, ev.multiplicity).unwrap();
+        tree.set(trigger, ev.trigger).unwrap();
+        tree.set_slice(vertex, &ev.vertex).unwrap();
+        tree.set(ntracks, ev.pt.len() as i32).unwrap();
+        tree.set_slice(pt, &ev.pt).unwrap();
+        tree.set_slice(flags, &ev.flags).unwrap();
+        tree.fill(&mut f).unwrap();
+    }
+    tree.write(&mut f).unwrap();
+    f.close().unwrap();
+}
+
+fn read_events(t: &Tree) -> Vec<Event> {
+    let counts: Vec<u32> = ColumnFixedIntoIter::new(t, "ntracks", be_i32).unwrap()
+        .map(|n| n as u32)
+        .collect();
+    let run = ColumnFixedIntoIter::new(t, "run", be_i64).unwrap();
+    let multiplicity = ColumnFixedIntoIter::new(t, "multiplicity", be_u16).unwrap();
+    let trigger = ColumnFixedIntoIter::new(t, "trigger", be_u8).unwrap();
+    let vertex = ColumnFixedIntoIter::new(t, "vertex", |i| map!(i, count!(be_f32, 3), |v| [v[0], v[1], v[2]])).unwrap();
+    let pt = ColumnVarIntoIter::new(t, "pt", be_f32, &counts).unwrap();
+    let flags = ColumnVarIntoIter::new(t, "flags", be_u64, &counts).unwrap();
+    run.zip(multiplicity).zip(trigger).zip(vertex).zip(pt).zip(flags)
+        .map(|(((((run, multiplicity), trigger), vertex), pt), flags)| {
+            Event {run, multiplicity, trigger, vertex, pt, flags}
+        })
+        .collect()
+}
+
+fn round_trip(file_name: &str, compression: u32) {
+    let path = env::temp_dir().join(file_name);
+    let events = events(1000);
+    let selected: Vec<_> = events.iter().filter(|ev| ev.pt.len() > 3).cloned().collect();
+    write_skim(&path, &events, compression, |ev| ev.pt.len() > 3);
+
+    let f = RootFile::new_from_file(&path).unwrap();
+    assert_eq!(f.items().len(), 1);
+    assert_eq!(f.items()[0].name(), "`events` of type `TTree`");
+    let t = f.items()[0].as_tree().unwrap();
+    // Branches without sub-branches are currently listed twice by the reader
+    let mut names_and_types = t.branch_names_and_types();
+    names_and_types.dedup();
+    assert_eq!(names_and_types,
+               vec![("run".to_string(), vec!["i64".to_string()]),
+                    ("multiplicity".to_string(), vec!["u16".to_string()]),
+                    ("trigger".to_string(), vec!["u8".to_string()]),
+                    ("vertex".to_string(), vec!["[f32; 3]".to_string()]),
+                    ("ntracks".to_string(), vec!["i32".to_string()]),
+                    ("pt".to_string(), vec!["Vec < f32 >".to_string()]),
+                    ("flags".to_string(), vec!["Vec < u64 >".to_string()])]);
+    // Several baskets were written for each branch
+    assert!(t.branches().iter().all(|b| b.n_events_per_basket().len() > 1));
+    assert_eq!(read_events(&t), selected);
+    assert!(f.streamers().unwrap().iter().any(|s| s.name() == "TTree"));
+}
+
+#[test]
+fn write_and_read_tree_uncompressed() {
+    round_trip("root-io-write-tree-uncompressed.root", 0);
+}
+
+#[test]
+fn write_and_read_tree_compressed() {
+    round_trip("root-io-write-tree-compressed.root", 1);
+}
+
+#[test]
+fn empty_tree() {
+    let path = env::temp_dir().join("root-io-write-tree-empty.root");
+    write_skim(&path, &events(10), 1, |_| false);
+    let f = RootFile::new_from_file(&path).unwrap();
+    let t = f.items()[0].as_tree().unwrap();
+    assert!(read_events(&t).is_empty());
+}
+
+#[test]
+fn invalid_entries_are_rejected() {
+    let path = env::temp_dir().join("root-io-write-tree-invalid.root");
+    let mut f = RootFileWriter::create(&path).unwrap();
+    let mut tree = TreeWriter::new("events", "");
+    let n = tree.add_branch::<i32>("n").unwrap();
+    let x = tree.add_branch::<f32>("x").unwrap();
+    let v = tree.add_jagged_branch::<f32, _>("v", n).unwrap();
+    assert!(tree.add_branch::<f32>("x").is_err());
+    assert!(tree.add_jagged_branch::<f32, _>("w", x).is_err());
+    assert!(tree.set_slice(x, &[1.0, 2.0]).is_err());
+
+    tree.set(n, 2).unwrap();
+    tree.set(x, 1.0).unwrap();
+    // Branch `v` not set
+    assert!(tree.fill(&mut f).is_err());
+    // Wrong number of elements
+    tree.set_slice(v, &[1.0]).unwrap();
+    assert!(tree.fill(&mut f).is_err());
+    tree.set_slice(v, &[1.0, 2.0]).unwrap();
+    tree.fill(&mut f).unwrap();
+    assert_eq!(tree.entries(), 1);
+    assert!(tree.add_branch::<f32>("y").is_err());
+}
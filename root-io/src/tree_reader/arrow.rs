@@ -0,0 +1,253 @@
+use std::fmt;
+use std::sync::Arc;
+use failure::Error;
+use nom::*;
+
+use arrow_array::{ArrayRef, BooleanArray, FixedSizeListArray, ListArray, PrimitiveArray,
+                  RecordBatch, RecordBatchReader, StringArray};
+use arrow_array::types::*;
+use arrow_buffer::{OffsetBuffer, ScalarBuffer};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+
+use core::parsers::string;
+use tree_reader::column_fixed_into_iter::ColumnFixedIntoIter;
+use tree_reader::column_var_into_iter::ColumnVarIntoIter;
+use tree_reader::model::select_branches;
+use tree_reader::schema::LeafSchema;
+use tree_reader::tree::Tree;
+
+/// A branch which is read into Arrow arrays
+struct ArrowColumn {
+    field: Field,
+    /// Build an array from the next `n` entries of this branch
+    next_array: Box<dyn FnMut(usize) -> ArrayRef>,
+}
+
+/// Iterator over Arrow `RecordBatch`es holding the selected branches
+/// of a `Tree`. Each batch covers a group of baskets which start at
+/// the same entry in all selected branches; usually, that is one
+/// cluster of the tree. Branches with a variable number of elements
+/// per entry become `ListArray`s and fixed size arrays become
+/// `FixedSizeListArray`s.
+/// # Example
+/// ```
+/// extern crate root_io;
+///
+/// use std::path::PathBuf;
+/// use root_io::RootFile;
+/// use root_io::tree_reader::RecordBatchIntoIter;
+///
+/// fn main() {
+///     let path = PathBuf::from("./src/test_data/HZZ.root");
+///     let tree = RootFile::new_from_file(&path).unwrap().tree("events").unwrap();
+///     let batches = RecordBatchIntoIter::new(&tree, &["NJet", "Jet_P*"]).unwrap();
+///     let n_rows: usize = batches.map(|b| b.unwrap().num_rows()).sum();
+///     assert_eq!(n_rows, 2421);
+/// }
+/// ```
+pub struct RecordBatchIntoIter {
+    schema: SchemaRef,
+    columns: Vec<ArrowColumn>,
+    /// Number of entries in each of the remaining batches
+    batch_sizes: ::std::vec::IntoIter<usize>,
+    /// Selected branches which cannot be read (yet)
+    skipped: Vec<String>,
+}
+
+fn be_bool(input: &[u8]) -> IResult<&[u8], bool> {
+    map!(input, be_u8, |v| v != 0)
+}
+
+fn primitive<A: ArrowPrimitiveType>(values: Vec<A::Native>) -> ArrayRef {
+    Arc::new(PrimitiveArray::<A>::new(ScalarBuffer::from(values), None))
+}
+
+/// Create the column for the branch `name` whose values are parsed
+/// with `p` and collected into an array with `to_array`
+fn column<T, F>(tree: &Tree, name: &str, leaf: &LeafSchema, counts: Option<&[u32]>,
+                p: fn(&[u8]) -> IResult<&[u8], T>, data_type: DataType, to_array: F)
+                -> Result<ArrowColumn, Error>
+where T: 'static + fmt::Debug + Clone,
+      F: 'static + Fn(Vec<T>) -> ArrayRef
+{
+    let flen = leaf.array_len;
+    let value_field = Arc::new(Field::new("item", data_type.clone(), false));
+    let element_type = if flen > 1 {
+        DataType::FixedSizeList(value_field.clone(), flen as i32)
+    } else {
+        data_type
+    };
+    // Values of all elements of several entries
+    let elements = move |values: Vec<T>| -> ArrayRef {
+        let values = to_array(values);
+        if flen > 1 {
+            Arc::new(FixedSizeListArray::new(value_field.clone(), flen as i32, values, None))
+        } else {
+            values
+        }
+    };
+    Ok(match counts {
+        None => {
+            let mut iter = ColumnFixedIntoIter::new(tree, name, move |i| count!(i, p, flen))?;
+            ArrowColumn {
+                field: Field::new(name, element_type, false),
+                next_array: Box::new(move |n| {
+                    elements(iter.by_ref().take(n).flatten().collect())
+                }),
+            }
+        },
+        Some(counts) => {
+            let mut iter = ColumnVarIntoIter::new(tree, name, move |i| count!(i, p, flen), counts)?;
+            let item = Arc::new(Field::new("item", element_type, false));
+            ArrowColumn {
+                field: Field::new(name, DataType::List(item.clone()), false),
+                next_array: Box::new(move |n| {
+                    let entries: Vec<_> = iter.by_ref().take(n).collect();
+                    let offsets = OffsetBuffer::from_lengths(entries.iter().map(|e| e.len()));
+                    let values = entries.into_iter()
+                        .flat_map(|e| e.into_iter().flatten())
+                        .collect();
+                    Arc::new(ListArray::new(item.clone(), offsets, elements(values), None))
+                }),
+            }
+        },
+    })
+}
+
+impl RecordBatchIntoIter {
+    /// Read the branches of `tree` matching any of the `globs` (which
+    /// may contain `*` and `?`). Branches with several leaves or of
+    /// unsupported types are skipped; see `skipped`
+    pub fn new(tree: &Tree, globs: &[&str]) -> Result<RecordBatchIntoIter, Error> {
+        let branches = select_branches(tree, globs)?;
+        let schema = tree.schema();
+        let mut columns = vec![];
+        let mut skipped = vec![];
+        // Entries at which a basket starts in all selected branches
+        let mut boundaries: Option<Vec<usize>> = None;
+        for br in branches {
+            let leaf = match schema.branch(&br.name) {
+                Some(b) if b.leaves.len() == 1 => b.leaves[0].clone(),
+                _ => {
+                    skipped.push(br.name.to_owned());
+                    continue;
+                },
+            };
+            let counts = match leaf.counter {
+                Some(ref counter) => Some(tree.counts(counter)?),
+                None => None,
+            };
+            let (name, counts) = (br.name.as_str(), counts.as_deref());
+            let col = match leaf.rust_type.as_deref() {
+                Some("bool") => column(tree, name, &leaf, counts, be_bool, DataType::Boolean,
+                                       |v| Arc::new(BooleanArray::from(v)) as ArrayRef)?,
+                Some("i8") => column(tree, name, &leaf, counts, be_i8, DataType::Int8, primitive::<Int8Type>)?,
+                Some("u8") => column(tree, name, &leaf, counts, be_u8, DataType::UInt8, primitive::<UInt8Type>)?,
+                Some("i16") => column(tree, name, &leaf, counts, be_i16, DataType::Int16, primitive::<Int16Type>)?,
+                Some("u16") => column(tree, name, &leaf, counts, be_u16, DataType::UInt16, primitive::<UInt16Type>)?,
+                Some("i32") => column(tree, name, &leaf, counts, be_i32, DataType::Int32, primitive::<Int32Type>)?,
+                Some("u32") => column(tree, name, &leaf, counts, be_u32, DataType::UInt32, primitive::<UInt32Type>)?,
+                Some("i64") => column(tree, name, &leaf, counts, be_i64, DataType::Int64, primitive::<Int64Type>)?,
+                Some("u64") => column(tree, name, &leaf, counts, be_u64, DataType::UInt64, primitive::<UInt64Type>)?,
+                Some("f32") => column(tree, name, &leaf, counts, be_f32, DataType::Float32, primitive::<Float32Type>)?,
+                Some("f64") => column(tree, name, &leaf, counts, be_f64, DataType::Float64, primitive::<Float64Type>)?,
+                Some("String") => column(tree, name, &leaf, counts, string, DataType::Utf8,
+                                         |v| Arc::new(StringArray::from(v)) as ArrayRef)?,
+                _ => {
+                    skipped.push(br.name.to_owned());
+                    continue;
+                },
+            };
+            columns.push(col);
+            let starts: Vec<usize> = br.n_events_per_basket().into_iter()
+                .scan(0, |start, n| { *start += n; Some(*start) })
+                .collect();
+            boundaries = Some(match boundaries {
+                None => starts,
+                Some(known) => known.into_iter().filter(|b| starts.contains(b)).collect(),
+            });
+        }
+        if columns.is_empty() {
+            return Err(format_err!("None of the selected branches can be read"));
+        }
+        let entries = tree.entries() as usize;
+        let mut boundaries = boundaries.unwrap_or_default();
+        if boundaries.last() != Some(&entries) {
+            boundaries.push(entries);
+        }
+        let batch_sizes: Vec<_> = boundaries.iter()
+            .scan(0, |start, &end| { let n = end - *start; *start = end; Some(n) })
+            .filter(|&n| n > 0)
+            .collect();
+        let schema = Arc::new(Schema::new(columns.iter().map(|c| c.field.clone()).collect::<Vec<_>>()));
+        Ok(RecordBatchIntoIter {
+            schema,
+            columns,
+            batch_sizes: batch_sizes.into_iter(),
+            skipped,
+        })
+    }
+
+    /// Names of the selected branches which are not part of the batches
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+}
+
+impl Iterator for RecordBatchIntoIter {
+    type Item = Result<RecordBatch, ArrowError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.batch_sizes.next()?;
+        let arrays = self.columns.iter_mut()
+            .map(|c| (c.next_array)(n))
+            .collect();
+        Some(RecordBatch::try_new(self.schema.clone(), arrays))
+    }
+}
+
+impl RecordBatchReader for RecordBatchIntoIter {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use arrow_array::{Array, Float32Array, Int32Array};
+    use core::RootFile;
+    use super::*;
+
+    #[test]
+    fn jagged_branches() {
+        let path = PathBuf::from("./src/test_data/HZZ.root");
+        let tree = RootFile::new_from_file(&path).unwrap().tree("events").unwrap();
+        let batches: Vec<_> = RecordBatchIntoIter::new(&tree, &["NJet", "Jet_Px"]).unwrap()
+            .map(|b| b.unwrap())
+            .collect();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2421);
+        for b in &batches {
+            let njet = b.column(0).as_any().downcast_ref::<Int32Array>().unwrap();
+            let px = b.column(1).as_any().downcast_ref::<ListArray>().unwrap();
+            for i in 0..b.num_rows() {
+                assert_eq!(px.value_length(i), njet.value(i));
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_size_arrays() {
+        let path = PathBuf::from("./src/test_data/sample-6.10.05-zlib.root");
+        let tree = RootFile::new_from_file(&path).unwrap().tree("sample").unwrap();
+        let iter = RecordBatchIntoIter::new(&tree, &["af4", "f4"]).unwrap();
+        assert_eq!(iter.schema().field(0).data_type(),
+                   &DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, false)), 3));
+        let batches: Vec<_> = iter.map(|b| b.unwrap()).collect();
+        let af4 = batches[0].column(0).as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+        let f4 = batches[0].column(1).as_any().downcast_ref::<Float32Array>().unwrap();
+        let first = af4.value(0);
+        let first = first.as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(f4.value(0), -14.9);
+        assert_eq!(first.values().as_ref(), &[-13.9, -12.9, -11.9]);
+    }
+}
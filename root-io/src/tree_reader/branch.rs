@@ -1,5 +1,6 @@
 use std::io::SeekFrom;
 use std::path::PathBuf;
+use std::sync::Arc;
 use nom::*;
 
 use core::parsers::*;
@@ -46,7 +47,7 @@ pub struct TBranch {
      fleaves: Vec<TLeaf>,
     /// Table of first entry in each basket
      fbasketentry: Vec<i64>,
-     containers: Vec<Container>
+     containers: Arc<[Container]>
 }
 
 impl TBranch {
@@ -62,9 +63,20 @@ impl TBranch {
         }
     }
 
-    /// Access to the `Containers` containing the data of this branch
-    pub(crate) fn containers(&self) -> &[Container] {
-        &self.containers
+    /// The direct sub-branches of this branch
+    pub(crate) fn sub_branches(&self) -> &[TBranch] {
+        &self.fbranches
+    }
+
+    /// The leaves of this branch
+    pub(crate) fn leaves(&self) -> &[TLeaf] {
+        &self.fleaves
+    }
+
+    /// Shared handle to the `Containers` containing the data of this
+    /// branch. Iterators keep it rather than copying the baskets
+    pub(crate) fn containers(&self) -> Arc<[Container]> {
+        self.containers.clone()
     }
 
     /// The name of this branch
@@ -91,6 +103,18 @@ impl TBranch {
             .map(|window| (window[1] - window[0]) as usize)
             .collect()
     }
+
+    /// Index of the basket holding `entry` and the index of the first
+    /// entry in that basket
+    pub(crate) fn basket_of_entry(&self, entry: usize) -> (usize, usize) {
+        let n_starting_before = self.fbasketentry.iter()
+            .take_while(|&&first| first as usize <= entry)
+            .count();
+        match n_starting_before {
+            0 => (0, 0),
+            n => (n - 1, self.fbasketentry[n - 1] as usize),
+        }
+    }
 }
 
 
@@ -162,7 +186,7 @@ fn tbranch<'s>(input: &'s [u8], context: & Context<'s>) -> IResult<&'s [u8], TBr
                   let containers_disk = fbasketseek
                       .zip(fbasketbytes)
                       .map(|(seek, len)| Container::OnDisk(ffilename.clone(), seek, len));
-                  let containers = fbaskets.chain(containers_disk).collect();
+                  let containers = fbaskets.chain(containers_disk).collect::<Vec<_>>().into();
                   TBranch {name,
                            fcompress,
                            fbasketsize,
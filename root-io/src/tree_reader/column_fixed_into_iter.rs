@@ -81,6 +81,16 @@ pub struct ColumnFixedIntoIter<T> {
 
 impl<T> ColumnFixedIntoIter<T> {
     pub fn new<P>(tr: &Tree, name: &str, p: P) -> Result<ColumnFixedIntoIter<T>, Error>
+    where P: 'static + Fn(&[u8]) -> IResult<&[u8], T>,
+          T: 'static
+    {
+        ColumnFixedIntoIter::new_from_entry(tr, name, p, 0)
+    }
+
+    /// Create an iterator starting at the entry `start`. Baskets
+    /// before the one holding `start` are not read at all
+    pub fn new_from_entry<P>(tr: &Tree, name: &str, p: P, start: usize)
+                             -> Result<ColumnFixedIntoIter<T>, Error>
     where P: 'static + Fn(&[u8]) -> IResult<&[u8], T>,
           T: 'static
     {
@@ -91,17 +101,20 @@ impl<T> ColumnFixedIntoIter<T> {
                                        tr.branches().iter()
                                        .map(|b| b.name.to_owned()).collect::<Vec<_>>())
             )?;
+        let (first_basket, first_entry) = br.basket_of_entry(start);
+        let baskets = br.containers();
         let containers = Box::new(
-            br.containers().to_owned().into_iter()
+            (first_basket..baskets.len())
                 // Read and decompress data into a vec
-                .flat_map(|c| c.raw_data())
+                .flat_map(move |i| baskets[i].raw_data())
                 .flat_map(move |(n_entries, raw_slice)| {
                     let s: &[u8] = raw_slice.as_slice(); 
                     match count!(s, p, n_entries as usize) {
                         IResult::Done(_, o) => o,
                         _ => panic!("Parser failed unexpectedly!"),
                     }
-                }));
+                })
+                .skip(start - first_entry));
         Ok(ColumnFixedIntoIter {
             containers: containers,
         })
@@ -0,0 +1,152 @@
+use failure::Error;
+use nom::*;
+
+use core::parsers::string;
+use tree_reader::column_fixed_into_iter::ColumnFixedIntoIter;
+use tree_reader::column_var_into_iter::ColumnVarIntoIter;
+use tree_reader::tree::Tree;
+
+/// A value of a branch whose type is only known at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    /// Any signed integer
+    Int(i64),
+    /// Any unsigned integer
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    /// The elements of an entry of a branch with a variable number
+    /// of elements per entry or a fixed size array
+    Array(Vec<Value>),
+}
+
+/// Parser of a single `Value`
+type ValueParser = fn(&[u8]) -> IResult<&[u8], Value>;
+
+/// Parser of a single value of the given Rust type
+fn value_parser(rust_type: &str) -> Option<ValueParser> {
+    fn bool_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_u8, |v| Value::Bool(v != 0)) }
+    fn i8_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_i8, |v| Value::Int(v.into())) }
+    fn u8_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_u8, |v| Value::UInt(v.into())) }
+    fn i16_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_i16, |v| Value::Int(v.into())) }
+    fn u16_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_u16, |v| Value::UInt(v.into())) }
+    fn i32_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_i32, |v| Value::Int(v.into())) }
+    fn u32_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_u32, |v| Value::UInt(v.into())) }
+    fn i64_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_i64, Value::Int) }
+    fn u64_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_u64, Value::UInt) }
+    fn f32_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_f32, Value::Float) }
+    fn f64_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, be_f64, Value::Double) }
+    fn string_(i: &[u8]) -> IResult<&[u8], Value> { map!(i, string, Value::String) }
+    Some(match rust_type {
+        "bool" => bool_,
+        "i8" => i8_,
+        "u8" => u8_,
+        "i16" => i16_,
+        "u16" => u16_,
+        "i32" => i32_,
+        "u32" => u32_,
+        "i64" => i64_,
+        "u64" => u64_,
+        "f32" => f32_,
+        "f64" => f64_,
+        "String" => string_,
+        _ => return None,
+    })
+}
+
+/// Iterator over the entries of a column (`TBranch`) whose type is
+/// determined from the metadata of its leaf. This is slower than
+/// `ColumnFixedIntoIter` and `ColumnVarIntoIter` but useful if the
+/// branches are only known at runtime, e.g. when converting a tree
+/// to another format. Branches with a variable number of elements
+/// and fixed size arrays yield `Value::Array`s.
+pub struct ColumnValueIntoIter {
+    values: Box<dyn Iterator<Item=Value>>,
+}
+
+impl ColumnValueIntoIter {
+    /// Create a new iterator over the branch `name` in the given
+    /// `Tree`. Fails if the branch has several leaves or if its type
+    /// is not supported
+    pub fn new(tr: &Tree, name: &str) -> Result<ColumnValueIntoIter, Error> {
+        ColumnValueIntoIter::new_from_entry(tr, name, 0)
+    }
+
+    /// Like `new` but starting at the entry `start`. Baskets before
+    /// the one holding `start` are not read at all
+    pub fn new_from_entry(tr: &Tree, name: &str, start: usize) -> Result<ColumnValueIntoIter, Error> {
+        let schema = tr.schema();
+        let leaf = match schema.branch(name) {
+            Some(br) if br.leaves.len() == 1 => br.leaves[0].clone(),
+            Some(_) => return Err(format_err!("Branch `{}` has several leaves", name)),
+            None => return Err(format_err!("Branch `{}` not found", name)),
+        };
+        let p = leaf.rust_type.as_ref()
+            .and_then(|t| value_parser(t))
+            .ok_or_else(|| format_err!("The type of branch `{}` is not supported", name))?;
+        let flen = leaf.array_len;
+        let values: Box<dyn Iterator<Item=Value>> = match (leaf.counter, flen > 1) {
+            (None, false) => Box::new(ColumnFixedIntoIter::new_from_entry(tr, name, p, start)?),
+            (None, true) => Box::new(ColumnFixedIntoIter::new_from_entry(
+                tr, name, move |i| map!(i, count!(p, flen), Value::Array), start)?),
+            (Some(counter), false) => Box::new(
+                ColumnVarIntoIter::new_from_entry(tr, name, p, &tr.counts(&counter)?, start)?
+                    .map(Value::Array)),
+            (Some(counter), true) => Box::new(
+                ColumnVarIntoIter::new_from_entry(
+                    tr, name, move |i| map!(i, count!(p, flen), Value::Array),
+                    &tr.counts(&counter)?, start)?
+                    .map(Value::Array)),
+        };
+        Ok(ColumnValueIntoIter { values })
+    }
+}
+
+impl Iterator for ColumnValueIntoIter {
+    type Item = Value;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.values.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use core::RootFile;
+    use super::*;
+
+    #[test]
+    fn values_of_any_type() {
+        let path = PathBuf::from("./src/test_data/sample-6.10.05-zlib.root");
+        let tree = RootFile::new_from_file(&path).unwrap().tree("sample").unwrap();
+        let first = |name| ColumnValueIntoIter::new(&tree, name).unwrap().next().unwrap();
+        assert_eq!(first("n"), Value::Int(0));
+        assert_eq!(first("u2"), Value::UInt(0));
+        assert_eq!(first("f4"), Value::Float(-14.9));
+        assert_eq!(first("str"), Value::String("hey-0".to_string()));
+        assert_eq!(first("ai4"), Value::Array(vec![Value::Int(-14), Value::Int(-13), Value::Int(-12)]));
+        assert_eq!(first("Ai8"), Value::Array(vec![]));
+        let second = ColumnValueIntoIter::new(&tree, "Af8").unwrap().nth(1).unwrap();
+        assert_eq!(second, Value::Array(vec![Value::Double(-15.0)]));
+        assert_eq!(ColumnValueIntoIter::new(&tree, "n").unwrap().count(), 30);
+    }
+
+    #[test]
+    fn start_at_entry() {
+        let path = PathBuf::from("./src/test_data/sample-6.10.05-zlib.root");
+        let tree = RootFile::new_from_file(&path).unwrap().tree("sample").unwrap();
+        // The branches are spread over several baskets
+        let n = tree.branches().into_iter().find(|b| b.name == "n").unwrap();
+        assert_ne!(n.basket_of_entry(29).0, 0);
+        for name in &["n", "str", "ai4", "Af8", "Ai8"] {
+            let all: Vec<_> = ColumnValueIntoIter::new(&tree, name).unwrap().collect();
+            for start in &[0, 1, 7, 29, 30, 31] {
+                let from: Vec<_> = ColumnValueIntoIter::new_from_entry(&tree, name, *start)
+                    .unwrap().collect();
+                assert_eq!(from.as_slice(), &all[(*start).min(all.len())..]);
+            }
+        }
+    }
+}
@@ -24,6 +24,16 @@ impl<T> ColumnVarIntoIter<T> {
     /// Create a new iterator over the branch `name` in the given
     /// `Tree`
     pub fn new<P>(tr: &Tree, name: &str, p: P, el_counter: &[u32]) -> Result<ColumnVarIntoIter<T>, Error>
+    where P: 'static + Fn(&[u8]) -> IResult<&[u8], T>,
+          T: 'static + ::std::fmt::Debug
+    {
+        ColumnVarIntoIter::new_from_entry(tr, name, p, el_counter, 0)
+    }
+
+    /// Create an iterator starting at the entry `start`. Baskets
+    /// before the one holding `start` are not read at all
+    pub fn new_from_entry<P>(tr: &Tree, name: &str, p: P, el_counter: &[u32], start: usize)
+                             -> Result<ColumnVarIntoIter<T>, Error>
     where P: 'static + Fn(&[u8]) -> IResult<&[u8], T>,
           T: 'static + ::std::fmt::Debug
     {
@@ -49,11 +59,13 @@ impl<T> ColumnVarIntoIter<T> {
                     .sum()
             })
             .collect();
+        let (first_basket, first_entry) = br.basket_of_entry(start);
+        let baskets = br.containers();
         let containers = Box::new(
-            br.containers().to_owned().into_iter()
+            (first_basket..baskets.len())
                 // Read and decompress data into a vec
-                .flat_map(|c| c.raw_data())
-                .zip(n_elems_per_basket.into_iter())
+                .flat_map(move |i| baskets[i].raw_data())
+                .zip(n_elems_per_basket.into_iter().skip(first_basket))
                 .flat_map(move |((n_entries_in_buf, raw_slice), n_elems)| {
                     let s: &[u8] = raw_slice.as_slice();
                     match count!(s, p, n_elems as usize) {
@@ -62,10 +74,14 @@ impl<T> ColumnVarIntoIter<T> {
                     }})
         );
 
-        Ok(ColumnVarIntoIter {
-            elems_per_entry: el_counter.to_vec().into_iter(),
+        let mut iter = ColumnVarIntoIter {
+            elems_per_entry: Vec::from(&el_counter[first_entry.min(el_counter.len())..]).into_iter(),
             containers: containers,
-        })
+        };
+        // Only the entries preceding `start` in its basket are left to skip
+        let n_skipped: u32 = iter.elems_per_entry.by_ref().take(start - first_entry).sum();
+        iter.containers.by_ref().take(n_skipped as usize).for_each(drop);
+        Ok(iter)
     }
 }
 
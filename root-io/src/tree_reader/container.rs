@@ -16,16 +16,16 @@ pub(crate) enum Container {
 
 impl Container {
     /// Return the number of entries and the data; reading it from disk if necessary
-    pub(crate) fn raw_data(self) -> Result<(u32, Vec<u8>), Error> {
-        match self {
-            Container::InMemory(buf) => {
+    pub(crate) fn raw_data(&self) -> Result<(u32, Vec<u8>), Error> {
+        match *self {
+            Container::InMemory(ref buf) => {
                 match tbasket2vec(buf.as_slice()) {
                     IResult::Done(_, v) => Ok(v),
                     _ => Err(format_err!("tbasket2vec parser failed"))
                 }
             },
-            Container::OnDisk(p, seek, len) => {
-                let f = File::open(&p)?;
+            Container::OnDisk(ref p, seek, len) => {
+                let f = File::open(p)?;
                 let mut reader = BufReader::new(f);
                 let mut buf = vec![0; len];
                 reader.seek(seek)?;
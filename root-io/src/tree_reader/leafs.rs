@@ -3,7 +3,7 @@ use nom::*;
 use quote::{Ident, Tokens};
 
 use core::*;
-use code_gen::rust::ToRustType;
+use code_gen::rust::{ToRustParser, ToRustType};
 
 #[derive(Debug, Clone)]
 pub struct TLeafBase {
@@ -66,11 +66,136 @@ impl fmt::Debug for TLeaf {
     }
 }
 
+impl TLeaf {
+    fn base(&self) -> &TLeafBase {
+        match *self {
+            TLeaf::Primitive(_, ref base) | TLeaf::String(ref base) | TLeaf::Object(_, ref base) => base,
+            TLeaf::Element(ref el) => &el.base,
+        }
+    }
+
+    /// The name of this leaf
+    pub(crate) fn name(&self) -> &str {
+        &self.base().tnamed.name
+    }
+
+    /// The leaf holding the number of elements in each entry if this
+    /// leaf has a variable number of elements per entry
+    pub(crate) fn counter(&self) -> Option<&TLeaf> {
+        self.base().fleafcount.as_deref()
+    }
+
+    /// The Rust type of a single (scalar) value of this leaf. `None` if
+    /// values of this leaf cannot be parsed
+    pub(crate) fn primitive_type(&self) -> Option<&'static str> {
+        match *self {
+            TLeaf::Primitive(ref leaf_name, ref leaf) => {
+                match (leaf_name.as_str(), leaf.fisunsigned == 1) {
+                    ("TLeafO", _) => Some("bool"),
+                    ("TLeafB", true) => Some("u8"),
+                    ("TLeafB", false) => Some("i8"),
+                    ("TLeafS", true) => Some("u16"),
+                    ("TLeafS", false) => Some("i16"),
+                    ("TLeafI", true) => Some("u32"),
+                    ("TLeafI", false) => Some("i32"),
+                    ("TLeafL", true) => Some("u64"),
+                    ("TLeafL", false) => Some("i64"),
+                    ("TLeafF", _) => Some("f32"),
+                    ("TLeafD", _) => Some("f64"),
+                    _ => None,
+                }
+            },
+            TLeaf::String(_) => Some("String"),
+            TLeaf::Element(ref el) => {
+                match el.type_id {
+                    // Types 7 and 10 are not implemented
+                    TypeID::Primitive(ref id) | TypeID::Offset(ref id) if id.0 != 7 && id.0 != 10 =>
+                        Some(id.rust_type_name()),
+                    TypeID::InvalidOrCounter(_) if el.id == 0 => Some("u32"),
+                    _ => None,
+                }
+            },
+            TLeaf::Object(_, _) => None,
+        }
+    }
+
+    /// The C++ type of a single value of this leaf as it would be
+    /// declared in ROOT (e.g. `Float_t`). `None` for leaves of objects
+    pub(crate) fn cpp_type(&self) -> Option<&'static str> {
+        match *self {
+            TLeaf::Primitive(ref leaf_name, ref leaf) => {
+                match (leaf_name.as_str(), leaf.fisunsigned == 1) {
+                    ("TLeafO", _) => Some("Bool_t"),
+                    ("TLeafB", true) => Some("UChar_t"),
+                    ("TLeafB", false) => Some("Char_t"),
+                    ("TLeafS", true) => Some("UShort_t"),
+                    ("TLeafS", false) => Some("Short_t"),
+                    ("TLeafI", true) => Some("UInt_t"),
+                    ("TLeafI", false) => Some("Int_t"),
+                    ("TLeafL", true) => Some("ULong64_t"),
+                    ("TLeafL", false) => Some("Long64_t"),
+                    ("TLeafF", _) => Some("Float_t"),
+                    ("TLeafD", _) => Some("Double_t"),
+                    _ => None,
+                }
+            },
+            TLeaf::String(_) => Some("Char_t*"),
+            TLeaf::Element(ref el) => {
+                match el.type_id {
+                    TypeID::Primitive(ref id) | TypeID::Offset(ref id) => Some(id.cpp_type_name()),
+                    TypeID::InvalidOrCounter(_) if el.id == 0 => Some("Int_t"),
+                    _ => None,
+                }
+            },
+            TLeaf::Object(_, _) => None,
+        }
+    }
+
+    /// Number of values of this leaf in one element. Elements are
+    /// fixed size arrays if this is larger than 1
+    pub(crate) fn array_len(&self) -> usize {
+        match *self {
+            TLeaf::String(_) | TLeaf::Object(_, _) => 1,
+            _ => ::std::cmp::max(self.base().flen, 1) as usize,
+        }
+    }
+
+    /// Rust type and parser of a single element of this leaf as they
+    /// are used with `ColumnFixedIntoIter` and
+    /// `ColumnVarIntoIter`. Fixed size arrays are one element. `None`
+    /// if elements of this leaf cannot be parsed
+    pub(crate) fn element_type_and_parser(&self) -> Option<(Tokens, Tokens)> {
+        let t = self.primitive_type()?;
+        let p = match *self {
+            TLeaf::Element(ref el) => match el.type_id {
+                TypeID::Primitive(ref id) | TypeID::Offset(ref id) => id.to_inline_parser().to_string(),
+                _ => "be_u32".to_string(),
+            },
+            _ => match t {
+                "bool" => "|i| map!(i, be_u8, |v| v != 0)".to_string(),
+                "String" => "string".to_string(),
+                t => format!("be_{}", t),
+            },
+        };
+        let flen = self.array_len();
+        let (t, p) = if flen > 1 {
+            // `count_fixed!` does not take closures directly
+            let p = if p.starts_with('|') { format!("call!({})", p) } else { p };
+            let p = format!("|i| count_fixed!(i, {}, {}, {})", t, p, flen);
+            (format!("[{}; {}]", t, flen), p)
+        } else {
+            (t.to_string(), p)
+        };
+        let (t, p) = (Ident::new(t), Ident::new(p));
+        Some((quote!(#t), quote!(#p)))
+    }
+}
+
 impl ToRustType for  TLeaf {
     fn type_name(&self) -> Tokens {
         match *self {
             TLeaf::Primitive(ref leaf_name, ref leaf) => {
-                if leaf.flen != 1 || leaf.foffset != 0 || leaf.fleafcount.is_some() {
+                if leaf.foffset != 0 {
                     panic!("Unexpected TLeaf: \n{:#?}", leaf);
                 }
                 let t = match leaf_name.as_str() {
@@ -108,13 +233,16 @@ impl ToRustType for  TLeaf {
                     },
                     name => panic!("Unexpected TLeaf type name {}", name),
                 };
-                // not an array
-                let t = Ident::new(t);
-                if leaf.flen == 1 {
+                if leaf.fleafcount.is_some() {
+                    // variable number of elements per entry
+                    let t = Ident::new(t);
+                    quote!{Vec<#t>}
+                } else if leaf.flen == 1 {
+                    let t = Ident::new(t);
                     quote!{#t}
                 } else { // array
-                    let s = format!("[{}; {}]", t, leaf.flen);
-                    quote!{#s}
+                    let t = Ident::new(format!("[{}; {}]", t, leaf.flen));
+                    quote!{#t}
                 }
             },
             TLeaf::String(_) => quote!(String),
@@ -211,8 +339,8 @@ fn tleafprimitive<'s>(input: &'s [u8], context: &'s Context<'s>)
     do_parse!(input,
               _ver: be_u16 >>
               base: length_value!(checked_byte_count, apply!(tleafbase, context)) >>
-              _fmaximum: be_f32 >>
-              _fminimum: be_f32 >>
+              // Minimum and maximum; their size depends on the leaf's type
+              _fminmax: rest >>
               (base)
     )
 }
@@ -3,18 +3,41 @@
 //! represents a particle collision. Each column may contain one or
 //! several elements per collision. This module provides two Iterator
 //! structs in order to iterate over these columns (`TBranches` in
-//! ROOT lingo).
+//! ROOT lingo). A model struct and an iterator built from them can
+//! be generated for a selection of branches with
+//! `RootFile::tree_model_as_rust`, e.g. from a build script. The
+//! structure of a tree's branches is described by `Tree::schema`.
+//! Branches whose types are only known at runtime can be read as
+//! `Value`s with `ColumnValueIntoIter`.
+//! With the `arrow` feature enabled, branches can be read into Apache
+//! Arrow `RecordBatch`es with `RecordBatchIntoIter`.
+//! With the `npy` feature enabled, branches can be exported to NumPy
+//! `.npz` archives with `write_npz`.
 
+#[cfg(feature = "arrow")]
+mod arrow;
 mod branch;
 mod column_fixed_into_iter;
+mod column_value_into_iter;
 mod column_var_into_iter;
 mod container;
 mod leafs;
+mod model;
+#[cfg(feature = "npy")]
+mod npy;
+mod schema;
 mod tree;
 
 pub use self::tree::{ttree, Tree};
+pub use self::schema::{write_json_string, BranchSchema, LeafSchema, TreeSchema};
 pub use self::column_var_into_iter::{ColumnVarIntoIter};
 pub use self::column_fixed_into_iter::{ColumnFixedIntoIter};
+pub use self::column_value_into_iter::{ColumnValueIntoIter, Value};
+pub(crate) use self::model::model_as_rust;
+#[cfg(feature = "arrow")]
+pub use self::arrow::RecordBatchIntoIter;
+#[cfg(feature = "npy")]
+pub use self::npy::write_npz;
 
 #[cfg(test)]
 mod tests {
@@ -66,22 +89,5 @@ mod tests {
             let tracks_table = build_table!(tree, "Tracks");
             let p: Vec<[5; f32]> = tracks_table.fP.itre().next();
         };
-
-        quote!{
-            // rootrs print-model file.root --tree=esdTree --branches=Tracks.fAlpha,PrimaryVertex.fCov[3]
-            use esd::MyModel; // Auto generated model for user selected branches
-            let f = RootFile::new_from_file(&path)?;
-            let tree = f.items[0].as_tree()?;
-            let model = MyModel::new(tree);
-            model.iter()
-                .map(|ev| {
-                    ev.tracks
-                        .iter()
-                        .map(|tr| tr.alpha)
-                });
-                
-                
-                
-        };
     }    
 }
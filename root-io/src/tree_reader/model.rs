@@ -0,0 +1,242 @@
+use std::fmt;
+use failure::Error;
+use quote::{Ident, Tokens};
+
+use code_gen::utils::sanitize;
+use tree_reader::branch::TBranch;
+use tree_reader::tree::Tree;
+
+/// A branch of the generated model
+struct Column {
+    /// Name of the branch
+    branch: String,
+    /// Name of the field in the model
+    field: Ident,
+    /// Type of one element
+    elem_type: Tokens,
+    /// Parser of one element
+    parser: Tokens,
+    /// Variable holding the number of elements per entry if this
+    /// branch has a variable number of elements
+    counter: Option<Ident>,
+}
+
+/// A branch holding the number of elements per entry of other branches
+struct Counter {
+    branch: String,
+    ident: Ident,
+    parser: Tokens,
+}
+
+/// Does `name` match the glob `pattern`? `*` matches any sequence
+/// of characters and `?` a single character
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some(&'*') => (0..name.len() + 1).any(|i| glob_match(&pattern[1..], &name[i..])),
+        Some(&c) => match name.first() {
+            Some(&n) if c == '?' || c == n => glob_match(&pattern[1..], &name[1..]),
+            _ => false,
+        },
+    }
+}
+
+/// Field name for a branch such as `PrimaryVertex.AliVertex.fPosition[3]`
+fn field_name(branch: &str) -> String {
+    let name = branch.split('[').next().unwrap_or(branch).to_lowercase();
+    sanitize(&name)
+}
+
+/// The counter of a branch with a variable number of elements per
+/// entry; `None` if the branch has a fixed number of elements or
+/// the counter cannot be read
+fn counter(tree: &Tree, br: &TBranch) -> Option<Option<Counter>> {
+    let counter_leaf = match br.leaves()[0].counter() {
+        Some(leaf) => leaf,
+        None => return Some(None),
+    };
+    let counter_branch = tree.branches().into_iter()
+        .find(|b| b.leaves().iter().any(|l| l.name() == counter_leaf.name()))?;
+    let (t, p) = counter_leaf.element_type_and_parser()?;
+    let parser = match t.as_str() {
+        "i32" | "u32" => quote!(be_u32),
+        "i8" | "u8" | "i16" | "u16" | "i64" | "u64" => quote!(|i| map!(i, #p, |v| v as u32)),
+        _ => return None,
+    };
+    Some(Some(Counter {
+        branch: counter_branch.name.to_owned(),
+        ident: Ident::new(format!("n_{}", field_name(&counter_branch.name))),
+        parser,
+    }))
+}
+
+/// The branches of `tree` matching any of the `globs` in the order
+/// of the globs. It is an error if a glob matches no branch
+pub(crate) fn select_branches<'t>(tree: &'t Tree, globs: &[&str]) -> Result<Vec<&'t TBranch>, Error> {
+    let all_branches = tree.branches();
+    let mut branches: Vec<&TBranch> = vec![];
+    for glob in globs {
+        let pattern: Vec<_> = glob.chars().collect();
+        let matched: Vec<_> = all_branches.iter()
+            .filter(|b| glob_match(&pattern, &b.name.chars().collect::<Vec<_>>()))
+            .collect();
+        if matched.is_empty() {
+            return Err(format_err!("No branch matches `{}`", glob));
+        }
+        for b in matched {
+            if !branches.iter().any(|known| known.name == b.name) {
+                branches.push(b);
+            }
+        }
+    }
+    Ok(branches)
+}
+
+/// Generate a model struct holding one entry of the given branches
+/// and an iterator over the entries of `tree`. Branches matching any
+/// of the `globs` are selected. Branches which cannot be read (yet)
+/// are skipped and listed in a comment at the top of the output
+pub(crate) fn model_as_rust(tree: &Tree, name: &str, globs: &[&str], s: &mut dyn fmt::Write)
+                            -> Result<(), Error> {
+    let branches = select_branches(tree, globs)?;
+    let mut columns = vec![];
+    let mut counters: Vec<Counter> = vec![];
+    let mut skipped = vec![];
+    for br in branches {
+        // Branches with several leaves are not supported
+        let elem = if br.leaves().len() == 1 {
+            br.leaves()[0].element_type_and_parser()
+        } else {
+            None
+        };
+        match (elem, counter(tree, br)) {
+            (Some((elem_type, parser)), Some(c)) => {
+                let counter = c.map(|c| {
+                    let ident = c.ident.clone();
+                    if !counters.iter().any(|known| known.branch == c.branch) {
+                        counters.push(c);
+                    }
+                    ident
+                });
+                // Branch names may only differ in case
+                let mut field = field_name(&br.name);
+                while columns.iter().any(|c: &Column| c.field.as_ref() == field) {
+                    field.push('_');
+                }
+                columns.push(Column {
+                    branch: br.name.to_owned(),
+                    field: Ident::new(field),
+                    elem_type,
+                    parser,
+                    counter,
+                });
+            },
+            _ => skipped.push(br.name.to_owned()),
+        }
+    }
+    if columns.is_empty() {
+        return Err(format_err!("None of the selected branches can be read"));
+    }
+    for br in &skipped {
+        writeln!(s, "// Skipped branch `{}`: its type is not supported", br)?;
+    }
+
+    let model = Ident::new(name);
+    let iter = Ident::new(format!("{}IntoIter", name));
+    let model_doc = format!("One entry of the tree `{}`", tree.name());
+    let iter_doc = format!("Iterator over the entries of a tree as `{}`s", name);
+    let fields: &Vec<_> = &columns.iter().map(|c| c.field.clone()).collect();
+    let docs: Vec<_> = columns.iter().map(|c| format!("Branch `{}`", c.branch)).collect();
+    let types: Vec<_> = columns.iter()
+        .map(|c| {
+            let t = &c.elem_type;
+            match c.counter {
+                Some(_) => quote!(Vec<#t>),
+                None => quote!(#t),
+            }
+        })
+        .collect();
+    let column_types: Vec<_> = columns.iter()
+        .map(|c| {
+            let t = &c.elem_type;
+            match c.counter {
+                Some(_) => quote!(ColumnVarIntoIter<#t>),
+                None => quote!(ColumnFixedIntoIter<#t>),
+            }
+        })
+        .collect();
+    let constructors: Vec<_> = columns.iter()
+        .map(|c| {
+            let (branch, p) = (&c.branch, &c.parser);
+            match c.counter {
+                Some(ref n) => quote!(ColumnVarIntoIter::new(t, #branch, #p, &#n)?),
+                None => quote!(ColumnFixedIntoIter::new(t, #branch, #p)?),
+            }
+        })
+        .collect();
+    let nexts: Vec<_> = fields.iter().map(|f| quote!(#f: self.#f.next()?)).collect();
+    let counter_idents: Vec<_> = counters.iter().map(|c| &c.ident).collect();
+    let counter_branches: Vec<_> = counters.iter().map(|c| &c.branch).collect();
+    let counter_parsers: Vec<_> = counters.iter().map(|c| &c.parser).collect();
+
+    let code = quote! {
+        #[allow(unused_imports)]
+        use failure::Error;
+        #[allow(unused_imports)]
+        use nom::*;
+        #[allow(unused_imports)]
+        use root_io::core::parsers::*;
+        #[allow(unused_imports)]
+        use root_io::tree_reader::{ColumnFixedIntoIter, ColumnVarIntoIter, Tree};
+
+        #[doc = #model_doc]
+        #[derive(Debug)]
+        pub struct #model {
+            #(#[doc = #docs] pub #fields: #types,)*
+        }
+
+        #[doc = #iter_doc]
+        pub struct #iter {
+            #(#fields: #column_types,)*
+        }
+
+        impl #iter {
+            /// Create a new iterator over the entries of the given `Tree`
+            pub fn new(t: &Tree) -> Result<#iter, Error> {
+                #(let #counter_idents: Vec<u32> =
+                  ColumnFixedIntoIter::new(t, #counter_branches, #counter_parsers)?.collect();)*
+                Ok(#iter {
+                    #(#fields: #constructors,)*
+                })
+            }
+        }
+
+        impl Iterator for #iter {
+            type Item = #model;
+            fn next(&mut self) -> Option<Self::Item> {
+                Some(#model {
+                    #(#nexts,)*
+                })
+            }
+        }
+    };
+    writeln!(s, "{}", code)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        let m = |p: &str, n: &str| glob_match(&p.chars().collect::<Vec<_>>(),
+                                              &n.chars().collect::<Vec<_>>());
+        assert!(m("Tracks.f*", "Tracks.fAlpha"));
+        assert!(m("*", "Tracks.fP[5]"));
+        assert!(m("Tracks.fP[?]", "Tracks.fP[5]"));
+        assert!(m("PrimaryVertex.*", "PrimaryVertex.AliVertex.fPosition[3]"));
+        assert!(!m("Tracks.f*", "Tracks"));
+        assert!(!m("one", "one_two"));
+    }
+}
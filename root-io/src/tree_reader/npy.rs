@@ -0,0 +1,149 @@
+use std::io::Write;
+use failure::Error;
+use npy_writer::{Element, NpzWriter};
+
+use tree_reader::column_value_into_iter::{ColumnValueIntoIter, Value};
+use tree_reader::tree::Tree;
+
+/// Conversion of a `Value` of a known type to a NumPy element
+trait FromValue: Element {
+    fn from_value(v: &Value) -> Self;
+}
+
+macro_rules! impl_from_value {
+    ($t:ty, $variant:ident) => {
+        impl FromValue for $t {
+            fn from_value(v: &Value) -> $t {
+                match *v {
+                    Value::$variant(x) => x as $t,
+                    _ => unreachable!("Values of a branch have a single type"),
+                }
+            }
+        }
+    };
+}
+
+impl_from_value!(i8, Int);
+impl_from_value!(u8, UInt);
+impl_from_value!(i16, Int);
+impl_from_value!(u16, UInt);
+impl_from_value!(i32, Int);
+impl_from_value!(u32, UInt);
+impl_from_value!(i64, Int);
+impl_from_value!(u64, UInt);
+impl_from_value!(f32, Float);
+impl_from_value!(f64, Double);
+
+impl FromValue for bool {
+    fn from_value(v: &Value) -> bool {
+        match *v {
+            Value::Bool(b) => b,
+            _ => unreachable!("Values of a branch have a single type"),
+        }
+    }
+}
+
+/// Add `values` of the given Rust type as the array `name`
+fn add_values<W: Write>(npz: &mut NpzWriter<W>, name: &str, rust_type: &str,
+                        shape: &[usize], values: &[Value]) -> Result<(), Error> {
+    fn add<W: Write, T: FromValue>(npz: &mut NpzWriter<W>, name: &str,
+                                   shape: &[usize], values: &[Value]) -> Result<(), Error> {
+        let data: Vec<T> = values.iter().map(T::from_value).collect();
+        npz.add_array(name, shape, &data)
+    }
+    match rust_type {
+        "bool" => add::<W, bool>(npz, name, shape, values),
+        "i8" => add::<W, i8>(npz, name, shape, values),
+        "u8" => add::<W, u8>(npz, name, shape, values),
+        "i16" => add::<W, i16>(npz, name, shape, values),
+        "u16" => add::<W, u16>(npz, name, shape, values),
+        "i32" => add::<W, i32>(npz, name, shape, values),
+        "u32" => add::<W, u32>(npz, name, shape, values),
+        "i64" => add::<W, i64>(npz, name, shape, values),
+        "u64" => add::<W, u64>(npz, name, shape, values),
+        "f32" => add::<W, f32>(npz, name, shape, values),
+        "f64" => add::<W, f64>(npz, name, shape, values),
+        other => Err(format_err!("Values of type `{}` cannot be exported to NumPy", other)),
+    }
+}
+
+/// Write the branches of `tree` matching any of the `globs` (which may
+/// contain `*` and `?`) as a NumPy `.npz` archive to `w`. Each branch
+/// with one element per entry becomes an array named after the
+/// branch; fixed size arrays add a second dimension. Branches with a
+/// variable number of elements per entry are stored as the array of
+/// all elements and an array `<branch>_offsets` of `entries + 1`
+/// indices, such that the elements of entry `i` are
+/// `values[offsets[i]:offsets[i + 1]]`. Returns the names of the
+/// selected branches which were skipped because they contain strings,
+/// several leaves or unsupported types.
+pub fn write_npz<W: Write>(tree: &Tree, globs: &[&str], w: W) -> Result<Vec<String>, Error> {
+    let schema = tree.schema();
+    let mut npz = NpzWriter::new(w);
+    let mut skipped = vec![];
+    for name in tree.select_branch_names(globs)? {
+        let leaf = match schema.branch(&name) {
+            Some(b) if b.leaves.len() == 1 => b.leaves[0].clone(),
+            _ => {
+                skipped.push(name);
+                continue;
+            },
+        };
+        let rust_type = match leaf.rust_type {
+            Some(ref t) if t != "String" => t.to_owned(),
+            _ => {
+                skipped.push(name);
+                continue;
+            },
+        };
+        let jagged = leaf.counter.is_some();
+        let mut values = vec![];
+        let mut offsets = vec![0i64];
+        for entry in ColumnValueIntoIter::new(tree, &name)? {
+            let elements = match entry {
+                Value::Array(elements) if jagged => elements,
+                element => vec![element],
+            };
+            offsets.push(offsets[offsets.len() - 1] + elements.len() as i64);
+            for element in elements {
+                match element {
+                    Value::Array(ref vals) if leaf.array_len > 1 => values.extend(vals.iter().cloned()),
+                    v => values.push(v),
+                }
+            }
+        }
+        let shape = if leaf.array_len > 1 {
+            vec![values.len() / leaf.array_len, leaf.array_len]
+        } else {
+            vec![values.len()]
+        };
+        add_values(&mut npz, &name, &rust_type, &shape, &values)?;
+        if jagged {
+            npz.add_array(&format!("{}_offsets", name), &[offsets.len()], &offsets)?;
+        }
+    }
+    npz.finish()?;
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use core::RootFile;
+    use super::*;
+
+    #[test]
+    fn export_branches() {
+        let path = PathBuf::from("./src/test_data/sample-6.10.05-zlib.root");
+        let tree = RootFile::new_from_file(&path).unwrap().tree("sample").unwrap();
+        let mut buf = vec![];
+        let skipped = write_npz(&tree, &["n", "af4", "Ai8", "str"], &mut buf).unwrap();
+        assert_eq!(skipped, vec!["str".to_string()]);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("n.npy"));
+        assert!(s.contains("'descr': '<i4', 'fortran_order': False, 'shape': (30,), }"));
+        assert!(s.contains("'descr': '<f4', 'fortran_order': False, 'shape': (30, 3), }"));
+        assert!(s.contains("Ai8_offsets.npy"));
+        assert!(s.contains("'descr': '<i8', 'fortran_order': False, 'shape': (31,), }"));
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt;
+use failure::Error;
+
+use tree_reader::branch::TBranch;
+use tree_reader::leafs::TLeaf;
+use tree_reader::tree::Tree;
+
+/// Description of the branch structure of a `Tree`. It may be
+/// written as JSON or as an Apache Arrow schema (in Arrow's JSON
+/// representation) in order to validate inputs before reading them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeSchema {
+    /// Name of the tree
+    pub name: String,
+    /// Number of entries in the tree
+    pub entries: i64,
+    /// The top-level branches
+    pub branches: Vec<BranchSchema>,
+}
+
+/// Description of a branch and its sub-branches
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchSchema {
+    /// Full name of the branch such as `Tracks.fP[5]`
+    pub name: String,
+    /// The leaves of this branch
+    pub leaves: Vec<LeafSchema>,
+    /// Nested branches; empty if this branch is an endpoint
+    pub branches: Vec<BranchSchema>,
+}
+
+/// Description of the values of a leaf in each entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeafSchema {
+    /// Name of the leaf
+    pub name: String,
+    /// C++ type of a single value (e.g. `Float_t`); `None` for
+    /// streamed objects
+    pub cpp_type: Option<String>,
+    /// Rust type of a single value (e.g. `f32`); `None` if the
+    /// values cannot be read (yet)
+    pub rust_type: Option<String>,
+    /// Number of values per element; elements are fixed size
+    /// arrays if this is larger than 1
+    pub array_len: usize,
+    /// Name of the leaf holding the number of elements in each
+    /// entry; `None` if there is exactly one element per entry
+    pub counter: Option<String>,
+}
+
+/// Minimal JSON value used to write the schemas
+enum Json {
+    Null,
+    Bool(bool),
+    Num(i64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn str(s: &str) -> Json {
+        Json::Str(s.to_string())
+    }
+
+    fn opt_str(s: &Option<String>) -> Json {
+        s.as_ref().map_or(Json::Null, |s| Json::str(s))
+    }
+
+    fn write(&self, s: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        let pad = |n| " ".repeat(n);
+        match *self {
+            Json::Null => write!(s, "null"),
+            Json::Bool(b) => write!(s, "{}", b),
+            Json::Num(n) => write!(s, "{}", n),
+            Json::Str(ref v) => write_json_string(s, v),
+            Json::Arr(ref vals) if vals.is_empty() => write!(s, "[]"),
+            Json::Arr(ref vals) => {
+                writeln!(s, "[")?;
+                for (i, v) in vals.iter().enumerate() {
+                    write!(s, "{}", pad(indent + 2))?;
+                    v.write(s, indent + 2)?;
+                    writeln!(s, "{}", if i + 1 < vals.len() { "," } else { "" })?;
+                }
+                write!(s, "{}]", pad(indent))
+            },
+            Json::Obj(ref pairs) => {
+                writeln!(s, "{{")?;
+                for (i, &(k, ref v)) in pairs.iter().enumerate() {
+                    write!(s, "{}\"{}\": ", pad(indent + 2), k)?;
+                    v.write(s, indent + 2)?;
+                    writeln!(s, "{}", if i + 1 < pairs.len() { "," } else { "" })?;
+                }
+                write!(s, "{}}}", pad(indent))
+            },
+        }
+    }
+}
+
+/// Write `v` as a quoted and escaped JSON string
+pub fn write_json_string(s: &mut dyn fmt::Write, v: &str) -> fmt::Result {
+    write!(s, "\"")?;
+    for c in v.chars() {
+        match c {
+            '"' => write!(s, "\\\"")?,
+            '\\' => write!(s, "\\\\")?,
+            '\n' => write!(s, "\\n")?,
+            '\t' => write!(s, "\\t")?,
+            c if (c as u32) < 0x20 => write!(s, "\\u{:04x}", c as u32)?,
+            c => write!(s, "{}", c)?,
+        }
+    }
+    write!(s, "\"")
+}
+
+/// Arrow type of a single value of the given Rust type. Values
+/// which cannot be read are exposed as binary blobs
+fn arrow_type(rust_type: &Option<String>) -> Json {
+    let int = |bits, signed| Json::Obj(vec![("name", Json::str("int")),
+                                            ("bitWidth", Json::Num(bits)),
+                                            ("isSigned", Json::Bool(signed))]);
+    let float = |precision| Json::Obj(vec![("name", Json::str("floatingpoint")),
+                                           ("precision", Json::str(precision))]);
+    let simple = |name| Json::Obj(vec![("name", Json::str(name))]);
+    match rust_type.as_ref().map(|t| t.as_str()) {
+        Some("bool") => simple("bool"),
+        Some("i8") => int(8, true),
+        Some("u8") => int(8, false),
+        Some("i16") => int(16, true),
+        Some("u16") => int(16, false),
+        Some("i32") => int(32, true),
+        Some("u32") => int(32, false),
+        Some("i64") => int(64, true),
+        Some("u64") => int(64, false),
+        Some("f32") => float("SINGLE"),
+        Some("f64") => float("DOUBLE"),
+        Some("String") => simple("utf8"),
+        _ => simple("binary"),
+    }
+}
+
+/// An Arrow field in Arrow's JSON representation
+fn arrow_field(name: &str, data_type: Json, children: Vec<Json>) -> Json {
+    Json::Obj(vec![("name", Json::str(name)),
+                   ("nullable", Json::Bool(false)),
+                   ("type", data_type),
+                   ("children", Json::Arr(children))])
+}
+
+impl LeafSchema {
+    fn new(leaf: &TLeaf) -> LeafSchema {
+        LeafSchema {
+            name: leaf.name().to_string(),
+            cpp_type: leaf.cpp_type().map(|t| t.to_string()),
+            rust_type: leaf.primitive_type().map(|t| t.to_string()),
+            array_len: leaf.array_len(),
+            counter: leaf.counter().map(|c| c.name().to_string()),
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        Json::Obj(vec![("name", Json::str(&self.name)),
+                       ("cpp_type", Json::opt_str(&self.cpp_type)),
+                       ("rust_type", Json::opt_str(&self.rust_type)),
+                       ("array_len", Json::Num(self.array_len as i64)),
+                       ("counter", Json::opt_str(&self.counter))])
+    }
+
+    /// Arrow field named `name` describing one entry of this leaf
+    fn to_arrow(&self, name: &str) -> Json {
+        let mut data_type = arrow_type(&self.rust_type);
+        let mut children = vec![];
+        if self.array_len > 1 {
+            children = vec![arrow_field("item", data_type, children)];
+            data_type = Json::Obj(vec![("name", Json::str("fixedsizelist")),
+                                       ("listSize", Json::Num(self.array_len as i64))]);
+        }
+        if self.counter.is_some() {
+            children = vec![arrow_field("item", data_type, children)];
+            data_type = Json::Obj(vec![("name", Json::str("list"))]);
+        }
+        arrow_field(name, data_type, children)
+    }
+}
+
+impl BranchSchema {
+    fn new(branch: &TBranch) -> BranchSchema {
+        BranchSchema {
+            name: branch.name.to_owned(),
+            leaves: branch.leaves().iter().map(LeafSchema::new).collect(),
+            branches: branch.sub_branches().iter().map(BranchSchema::new).collect(),
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        Json::Obj(vec![("name", Json::str(&self.name)),
+                       ("leaves", Json::Arr(self.leaves.iter().map(|l| l.to_json()).collect())),
+                       ("branches", Json::Arr(self.branches.iter().map(|b| b.to_json()).collect()))])
+    }
+
+    /// Endpoints with a single leaf are fields of that leaf's type;
+    /// everything else is a struct of its leaves and sub-branches
+    fn to_arrow(&self) -> Json {
+        if self.branches.is_empty() && self.leaves.len() == 1 {
+            return self.leaves[0].to_arrow(&self.name);
+        }
+        let children = self.leaves.iter()
+            .map(|l| l.to_arrow(&l.name))
+            .chain(self.branches.iter().map(|b| b.to_arrow()))
+            .collect();
+        arrow_field(&self.name, Json::Obj(vec![("name", Json::str("struct"))]), children)
+    }
+}
+
+impl TreeSchema {
+    pub(crate) fn new(tree: &Tree) -> TreeSchema {
+        TreeSchema {
+            name: tree.name().to_string(),
+            entries: tree.entries(),
+            branches: tree.fbranches.iter().map(BranchSchema::new).collect(),
+        }
+    }
+
+    /// The branch called `name` at any level of nesting
+    pub fn branch(&self, name: &str) -> Option<&BranchSchema> {
+        fn find<'a>(branches: &'a [BranchSchema], name: &str) -> Option<&'a BranchSchema> {
+            branches.iter()
+                .filter_map(|b| if b.name == name { Some(b) } else { find(&b.branches, name) })
+                .next()
+        }
+        find(&self.branches, name)
+    }
+
+    /// Write this schema as JSON
+    pub fn to_json(&self, s: &mut dyn fmt::Write) -> Result<(), Error> {
+        let json = Json::Obj(vec![
+            ("name", Json::str(&self.name)),
+            ("entries", Json::Num(self.entries)),
+            ("branches", Json::Arr(self.branches.iter().map(|b| b.to_json()).collect())),
+        ]);
+        json.write(s, 0)?;
+        writeln!(s)?;
+        Ok(())
+    }
+
+    /// Write this schema as an Apache Arrow schema in Arrow's JSON
+    /// representation. Branches with a counter become lists, fixed
+    /// size arrays become fixed size lists, and branches with
+    /// several leaves or sub-branches become structs
+    pub fn to_arrow_json(&self, s: &mut dyn fmt::Write) -> Result<(), Error> {
+        let json = Json::Obj(vec![
+            ("fields", Json::Arr(self.branches.iter().map(|b| b.to_arrow()).collect())),
+        ]);
+        json.write(s, 0)?;
+        writeln!(s)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use core::RootFile;
+
+    #[test]
+    fn simple_schema() {
+        let path = PathBuf::from("./src/test_data/simple.root");
+        let f = RootFile::new_from_file(&path).unwrap();
+        let schema = f.items()[0].as_tree().unwrap().schema();
+        assert_eq!(schema.name, "tree");
+        assert_eq!(schema.entries, 4);
+        let names: Vec<_> = schema.branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["one", "two", "three"]);
+        let one = &schema.branches[0].leaves[0];
+        assert_eq!(one.cpp_type, Some("Int_t".to_string()));
+        assert_eq!(one.rust_type, Some("i32".to_string()));
+        assert_eq!((one.array_len, one.counter.clone()), (1, None));
+
+        let mut s = String::new();
+        schema.to_json(&mut s).unwrap();
+        assert!(s.contains("\"cpp_type\": \"Float_t\""));
+        let mut s = String::new();
+        schema.to_arrow_json(&mut s).unwrap();
+        assert!(s.contains("\"name\": \"floatingpoint\""));
+        assert!(s.contains("\"name\": \"utf8\""));
+    }
+
+    #[test]
+    fn jagged_schema() {
+        let path = PathBuf::from("./src/test_data/HZZ.root");
+        let schema = RootFile::new_from_file(&path).unwrap().tree("events").unwrap().schema();
+        let px = schema.branches.iter().find(|b| b.name == "Jet_Px").unwrap();
+        assert_eq!(px.leaves[0].counter, Some("NJet".to_string()));
+        assert_eq!(px.leaves[0].cpp_type, Some("Float_t".to_string()));
+    }
+}
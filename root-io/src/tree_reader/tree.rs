@@ -1,14 +1,18 @@
 use std::fmt;
 use std::ops::Deref;
+use failure::Error;
 use nom::*;
 
 use core::parsers::*;
 use core::types::*;
 
 use tree_reader::branch::TBranch;
+use tree_reader::column_fixed_into_iter::ColumnFixedIntoIter;
 use tree_reader::branch::tbranch_hdr;
 use tree_reader::leafs::TLeaf;
 use tree_reader::leafs::tleaf;
+use tree_reader::model::select_branches;
+use tree_reader::schema::TreeSchema;
 
 /// `TTree` potentially has members with very large `Vec<u8>` buffers
 /// The `Pointer` type is used to overwrite the default `Debug` trait
@@ -84,6 +88,46 @@ pub struct Tree {
 }
 
 impl<'s> Tree {
+    /// The name of this tree
+    pub(crate) fn name(&self) -> &str {
+        &self.tnamed.name
+    }
+
+    /// Number of entries in this tree
+    pub fn entries(&self) -> i64 {
+        self.fentries
+    }
+
+    /// Structure of the branches of this tree including their
+    /// leaves' types and shapes
+    pub fn schema(&self) -> TreeSchema {
+        TreeSchema::new(self)
+    }
+
+    /// Names of the branches (including nested ones) matching any of
+    /// the `globs`, which may contain `*` and `?`. Fails if a glob
+    /// does not match any branch
+    pub fn select_branch_names(&self, globs: &[&str]) -> Result<Vec<String>, Error> {
+        Ok(select_branches(self, globs)?.into_iter().map(|b| b.name.to_owned()).collect())
+    }
+
+    /// Number of elements in each entry as given by the leaf
+    /// `counter` of a branch with a variable number of elements
+    pub(crate) fn counts(&self, counter: &str) -> Result<Vec<u32>, Error> {
+        let (br, leaf) = self.branches().into_iter()
+            .filter_map(|b| b.leaves().iter().find(|l| l.name() == counter).map(|l| (b, l)))
+            .next()
+            .ok_or_else(|| format_err!("Counter `{}` not found", counter))?;
+        let name = br.name.as_str();
+        Ok(match leaf.primitive_type() {
+            Some("i32") | Some("u32") => ColumnFixedIntoIter::new(self, name, be_u32)?.collect(),
+            Some("i16") | Some("u16") => ColumnFixedIntoIter::new(self, name, be_u16)?.map(u32::from).collect(),
+            Some("i8") | Some("u8") => ColumnFixedIntoIter::new(self, name, be_u8)?.map(u32::from).collect(),
+            Some("i64") | Some("u64") => ColumnFixedIntoIter::new(self, name, be_u64)?.map(|v| v as u32).collect(),
+            _ => return Err(format_err!("Counter `{}` is not an integer", counter)),
+        })
+    }
+
     /// Get all branches of a tree (including nested ones)
     pub(crate) fn branches(&self) -> Vec<(&TBranch)> {
         self.fbranches.iter()
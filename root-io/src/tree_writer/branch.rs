@@ -0,0 +1,304 @@
+use failure::Error;
+
+use core::*;
+use tree_writer::leaf::LeafType;
+
+/// Length of the `TBasket` specific part of a basket's key
+const BASKET_HEADER_LEN: i16 = 2 + 4 + 4 + 4 + 4 + 1;
+/// Length of the table of entry offsets which ROOT allocates by default
+const DEFAULT_ENTRY_OFFSET_LEN: i32 = 1000;
+
+/// Number of elements of a branch in each entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Shape {
+    /// One element per entry
+    Scalar,
+    /// A fixed number of elements per entry
+    Array(usize),
+    /// A variable number of elements per entry given by the value of
+    /// the (scalar) branch with this index
+    Jagged(usize),
+}
+
+/// A branch with a single leaf which is being written. The data is
+/// collected in a basket which is written to the file once it is full.
+#[derive(Debug)]
+pub(crate) struct BranchWriter {
+    pub(crate) name: String,
+    /// The `TLeaf` class of this branch's leaf
+    leaf_class: &'static str,
+    type_code: char,
+    type_size: i32,
+    unsigned: bool,
+    pub(crate) shape: Shape,
+    /// Elements set for the current entry and their number
+    pending: Option<(Vec<u8>, usize)>,
+    /// Value of the current entry if it can be used as a counter
+    pending_count: Option<usize>,
+    /// Largest value filled so far if this branch is a counter
+    max_count: usize,
+    /// Uncompressed content of the current basket
+    basket: Vec<u8>,
+    /// Start of each entry within the current basket
+    entry_offsets: Vec<u32>,
+    /// Total number of entries filled
+    entries: i64,
+    /// First entry of the current basket
+    basket_first_entry: i64,
+    basket_bytes: Vec<i32>,
+    basket_entry: Vec<i64>,
+    basket_seek: Vec<i64>,
+    tot_bytes: i64,
+    zip_bytes: i64,
+}
+
+impl BranchWriter {
+    pub(crate) fn new<T: LeafType>(name: &str, shape: Shape) -> BranchWriter {
+        BranchWriter {
+            name: name.to_string(),
+            leaf_class: T::leaf_class(),
+            type_code: T::type_code(),
+            type_size: T::size(),
+            unsigned: T::is_unsigned(),
+            shape,
+            pending: None,
+            pending_count: None,
+            max_count: 0,
+            basket: vec![],
+            entry_offsets: vec![],
+            entries: 0,
+            basket_first_entry: 0,
+            basket_bytes: vec![],
+            basket_entry: vec![],
+            basket_seek: vec![],
+            tot_bytes: 0,
+            zip_bytes: 0,
+        }
+    }
+
+    /// Can this branch be used as the counter of a jagged branch?
+    pub(crate) fn is_counter_candidate(&self) -> bool {
+        self.shape == Shape::Scalar && "BbSsIiLl".contains(self.type_code)
+    }
+
+    /// Set the elements of this branch for the current entry
+    pub(crate) fn set<T: LeafType>(&mut self, vals: &[T]) -> Result<(), Error> {
+        match self.shape {
+            Shape::Scalar if vals.len() != 1 => {
+                return Err(format_err!("Branch {} expects a single value per entry", self.name));
+            },
+            Shape::Array(n) if vals.len() != n => {
+                return Err(format_err!("Branch {} expects {} values per entry but got {}",
+                                       self.name, n, vals.len()));
+            },
+            _ => {},
+        }
+        let mut buf = WBuffer::new(0);
+        for v in vals {
+            v.put(&mut buf);
+        }
+        self.pending_count = if self.shape == Shape::Scalar { vals[0].as_count() } else { None };
+        self.pending = Some((buf.into_inner(), vals.len()));
+        Ok(())
+    }
+
+    /// Number of elements set for the current entry, if any
+    pub(crate) fn pending_len(&self) -> Option<usize> {
+        self.pending.as_ref().map(|&(_, n)| n)
+    }
+
+    /// Value set for the current entry if it is a valid count
+    pub(crate) fn pending_count(&self) -> Option<usize> {
+        self.pending_count
+    }
+
+    /// Move the elements of the current entry into the basket
+    pub(crate) fn commit(&mut self) {
+        if let Some((data, _)) = self.pending.take() {
+            self.entry_offsets.push(self.basket.len() as u32);
+            self.basket.extend_from_slice(&data);
+            self.entries += 1;
+        }
+        if let Some(count) = self.pending_count.take() {
+            self.max_count = ::std::cmp::max(self.max_count, count);
+        }
+    }
+
+    /// Size of the current basket in bytes
+    pub(crate) fn basket_len(&self) -> usize {
+        self.basket.len()
+    }
+
+    /// Total number of bytes written before and after compression
+    pub(crate) fn n_bytes(&self) -> (i64, i64) {
+        (self.tot_bytes, self.zip_bytes)
+    }
+
+    /// Write the current basket to the file. Baskets are keys with
+    /// some additional header information. Branches with a variable
+    /// number of elements per entry store the position of each entry
+    /// after the data.
+    pub(crate) fn flush(&mut self, f: &mut RootFileWriter, tree_name: &str) -> Result<(), Error> {
+        let n_entries = self.entry_offsets.len();
+        if n_entries == 0 {
+            return Ok(());
+        }
+        let mut hdr = f.new_key_header("TBasket", &self.name, tree_name, 1);
+        hdr.key_len += BASKET_HEADER_LEN;
+        let key_len = hdr.key_len as u32;
+        let last = key_len + self.basket.len() as u32;
+
+        let mut payload = WBuffer::new(0);
+        payload.put_bytes(&self.basket);
+        let nev_buf_size = match self.shape {
+            Shape::Jagged(_) => {
+                payload.put_i32(n_entries as i32);
+                for offset in &self.entry_offsets {
+                    payload.put_u32(key_len + offset);
+                }
+                DEFAULT_ENTRY_OFFSET_LEN
+            },
+            _ => self.entry_size() as i32,
+        };
+        let payload = payload.into_inner();
+        let comp = f.compress(&payload)?;
+        let data = comp.as_ref().unwrap_or(&payload);
+        hdr.set_sizes(data.len(), payload.len());
+
+        let mut key = WBuffer::new(0);
+        hdr.stream(&mut key);
+        key.put_u16(3);
+        // Size of the buffer in memory
+        key.put_i32((key_len as usize + payload.len()) as i32);
+        key.put_i32(nev_buf_size);
+        key.put_i32(n_entries as i32);
+        key.put_u32(last);
+        // Only the header is part of the key; the data follows
+        key.put_i8(0);
+        key.put_bytes(data);
+
+        let seek = match hdr.seek_key {
+            ::std::io::SeekFrom::Start(s) => s as i64,
+            _ => unreachable!(),
+        };
+        f.append(key.as_slice())?;
+        self.basket_bytes.push(hdr.total_size as i32);
+        self.basket_entry.push(self.basket_first_entry);
+        self.basket_seek.push(seek);
+        self.tot_bytes += i64::from(key_len) + payload.len() as i64;
+        self.zip_bytes += i64::from(hdr.total_size);
+
+        self.basket_first_entry = self.entries;
+        self.basket.clear();
+        self.entry_offsets.clear();
+        Ok(())
+    }
+
+    /// Size of one entry in bytes if it is fixed
+    fn entry_size(&self) -> usize {
+        let n = match self.shape {
+            Shape::Array(n) => n,
+            _ => 1,
+        };
+        n * self.type_size as usize
+    }
+
+    /// Title of this branch's leaf, e.g. `px[ntracks]`
+    fn leaf_title(&self, branches: &[BranchWriter]) -> String {
+        match self.shape {
+            Shape::Scalar => self.name.clone(),
+            Shape::Array(n) => format!("{}[{}]", self.name, n),
+            Shape::Jagged(counter) => format!("{}[{}]", self.name, branches[counter].name),
+        }
+    }
+
+    /// Write the `TLeaf` of this branch. The counter leaf of jagged
+    /// branches is written as a reference if it was already written to `buf`.
+    pub(crate) fn put_leaf(&self, buf: &mut WBuffer, branches: &[BranchWriter]) {
+        let len = match self.shape {
+            Shape::Array(n) => n as i32,
+            _ => 1,
+        };
+        let named = TNamed {name: self.name.clone(), title: self.leaf_title(branches)};
+        buf.put_byte_count(|w| {
+            w.put_class_tag(self.leaf_class);
+            w.put_version(1, |w| {
+                w.put_version(2, |w| {
+                    named.stream(w);
+                    w.put_i32(len);
+                    w.put_i32(self.type_size);
+                    // Offset
+                    w.put_i32(0);
+                    // Is range
+                    w.put_u8(0);
+                    w.put_u8(self.unsigned as u8);
+                    match self.shape {
+                        Shape::Jagged(counter) => {
+                            w.put_object_ref(counter, |w| branches[counter].put_leaf(w, branches))
+                        },
+                        _ => w.put_null(),
+                    }
+                });
+                // Minimum and maximum; the maximum of counters is
+                // used by ROOT to allocate the arrays they describe
+                let max = self.max_count as u64;
+                for v in &[0, max] {
+                    for i in (0..self.type_size).rev() {
+                        w.put_u8((v >> (8 * i)) as u8);
+                    }
+                }
+            })
+        });
+    }
+
+    /// Write this branch as a `TBranch`. Counterpart to the `tbranch` parser
+    pub(crate) fn put_branch(&self, buf: &mut WBuffer, branches: &[BranchWriter], idx: usize,
+                             compress: i32, basket_size: usize) {
+        let title = format!("{}/{}", self.leaf_title(branches), self.type_code);
+        let entry_offset_len = match self.shape {
+            Shape::Jagged(_) => DEFAULT_ENTRY_OFFSET_LEN,
+            _ => 0,
+        };
+        // The arrays describing the baskets have one more element
+        // than there are baskets written to disk
+        let max_baskets = self.basket_bytes.len() + 1;
+        buf.put_any("TBranch", |w| w.put_version(12, |w| {
+            TNamed {name: self.name.clone(), title}.stream(w);
+            put_tattfill(w);
+            w.put_i32(compress);
+            w.put_i32(basket_size as i32);
+            w.put_i32(entry_offset_len);
+            // Number of baskets written
+            w.put_i32(self.basket_bytes.len() as i32);
+            w.put_i64(self.entries);
+            // Offset
+            w.put_i32(0);
+            w.put_i32(max_baskets as i32);
+            // Split level
+            w.put_i32(0);
+            w.put_i64(self.entries);
+            // First entry
+            w.put_i64(0);
+            w.put_i64(self.tot_bytes);
+            w.put_i64(self.zip_bytes);
+            // Sub-branches, leaves, and baskets kept in memory
+            put_tobjarray(w, 0, |_, _| {});
+            put_tobjarray(w, 1, |w, _| w.put_object_ref(idx, |w| self.put_leaf(w, branches)));
+            put_tobjarray(w, 0, |_, _| {});
+            w.put_u8(1);
+            for b in self.basket_bytes.iter().chain(Some(&0)) {
+                w.put_i32(*b);
+            }
+            w.put_u8(1);
+            for e in self.basket_entry.iter().chain(Some(&self.entries)) {
+                w.put_i64(*e);
+            }
+            w.put_u8(1);
+            for s in self.basket_seek.iter().chain(Some(&0)) {
+                w.put_i64(*s);
+            }
+            // File name; empty if the baskets are in the same file as the tree
+            w.put_string("");
+        }));
+    }
+}
@@ -0,0 +1,55 @@
+use core::WBuffer;
+
+/// Primitive types which can be stored in the branches of a
+/// `TreeWriter`. Each type corresponds to one of ROOT's `TLeaf`
+/// classes.
+pub trait LeafType: Copy + 'static {
+    /// Name of the `TLeaf` subclass describing this type, e.g. `TLeafF`
+    fn leaf_class() -> &'static str;
+    /// Character identifying this type in the title of a branch, e.g. `F`
+    fn type_code() -> char;
+    /// Size of this type in bytes
+    fn size() -> i32;
+    /// Is this an unsigned integer type?
+    fn is_unsigned() -> bool;
+    /// Write this value to the given buffer
+    fn put(self, buf: &mut WBuffer);
+    /// The value as a number of elements if this is an integer type
+    /// and may thus be used as the counter of jagged branches
+    fn as_count(self) -> Option<usize>;
+}
+
+macro_rules! impl_leaf_type {
+    ($t:ty, $class:expr, $code:expr, $size:expr, $unsigned:expr, $put:ident, $as_count:expr) => {
+        impl LeafType for $t {
+            fn leaf_class() -> &'static str { $class }
+            fn type_code() -> char { $code }
+            fn size() -> i32 { $size }
+            fn is_unsigned() -> bool { $unsigned }
+            fn put(self, buf: &mut WBuffer) { buf.$put(self) }
+            fn as_count(self) -> Option<usize> { $as_count(self) }
+        }
+    }
+}
+
+fn int_count<T: Into<i64>>(v: T) -> Option<usize> {
+    let v = v.into();
+    if v >= 0 { Some(v as usize) } else { None }
+}
+
+fn no_count<T>(_v: T) -> Option<usize> {
+    None
+}
+
+impl_leaf_type!(i8, "TLeafB", 'B', 1, false, put_i8, int_count);
+impl_leaf_type!(u8, "TLeafB", 'b', 1, true, put_u8, int_count);
+impl_leaf_type!(i16, "TLeafS", 'S', 2, false, put_i16, int_count);
+impl_leaf_type!(u16, "TLeafS", 's', 2, true, put_u16, int_count);
+impl_leaf_type!(i32, "TLeafI", 'I', 4, false, put_i32, int_count);
+impl_leaf_type!(u32, "TLeafI", 'i', 4, true, put_u32, int_count);
+impl_leaf_type!(i64, "TLeafL", 'L', 8, false, put_i64, int_count);
+impl_leaf_type!(u64, "TLeafL", 'l', 8, true, put_u64,
+                |v: u64| if v <= i64::MAX as u64 { Some(v as usize) } else { None });
+impl_leaf_type!(f32, "TLeafF", 'F', 4, false, put_f32, no_count);
+impl_leaf_type!(f64, "TLeafD", 'D', 8, false, put_f64, no_count);
+impl_leaf_type!(bool, "TLeafO", 'O', 1, false, put_bool, no_count);
@@ -0,0 +1,16 @@
+//! Write `TTree`s with branches of primitive types to a ROOT file.
+//!
+//! Each branch holds either a single element, a fixed number of
+//! elements, or a variable number of elements per entry. In the latter
+//! case, the number of elements is given by a counter branch of the
+//! same tree. This is, for example, enough to write a skimmed subset
+//! of events read from one of the ALICE files back to disk such that
+//! it can again be read with `ColumnFixedIntoIter` and
+//! `ColumnVarIntoIter`.
+
+mod branch;
+mod leaf;
+mod tree;
+
+pub use self::leaf::LeafType;
+pub use self::tree::{Branch, TreeWriter};
@@ -0,0 +1,235 @@
+use std::marker::PhantomData;
+
+use failure::Error;
+
+use core::*;
+use tree_writer::branch::{BranchWriter, Shape};
+use tree_writer::leaf::LeafType;
+
+/// Default size of the baskets of each branch in bytes
+const DEFAULT_BASKET_SIZE: usize = 32_000;
+
+/// Handle to a branch of a `TreeWriter` holding elements of type `T`
+#[derive(Debug)]
+pub struct Branch<T> {
+    idx: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for Branch<T> {
+    fn clone(&self) -> Branch<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Branch<T> {}
+
+/// Write a `TTree` to a `RootFileWriter`. Branches are added to the
+/// tree before the first entry is filled. An entry is filled by first
+/// setting the value of every branch and then calling `fill`. Full
+/// baskets are written to the file while filling; the tree itself is
+/// written with `write`.
+///
+/// ```no_run
+/// # extern crate root_io;
+/// # use root_io::RootFileWriter;
+/// # use root_io::tree_writer::TreeWriter;
+/// # fn main() {
+/// let mut f = RootFileWriter::create("tracks.root").unwrap();
+/// let mut tree = TreeWriter::new("events", "A few events");
+/// let ntracks = tree.add_branch::<i32>("ntracks").unwrap();
+/// let pt = tree.add_jagged_branch::<f32, _>("pt", ntracks).unwrap();
+/// let vertex = tree.add_array_branch::<f32>("vertex", 3).unwrap();
+/// for i in 0..10 {
+///     let pts: Vec<f32> = (0..i).map(|j| j as f32).collect();
+///     tree.set(ntracks, i).unwrap();
+///     tree.set_slice(pt, &pts).unwrap();
+///     tree.set_slice(vertex, &[0.0, 0.0, i as f32]).unwrap();
+///     tree.fill(&mut f).unwrap();
+/// }
+/// tree.write(&mut f).unwrap();
+/// f.close().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TreeWriter {
+    name: String,
+    title: String,
+    branches: Vec<BranchWriter>,
+    entries: i64,
+    basket_size: usize,
+}
+
+impl TreeWriter {
+    /// A new tree without any branches
+    pub fn new(name: &str, title: &str) -> TreeWriter {
+        TreeWriter {
+            name: name.to_string(),
+            title: title.to_string(),
+            branches: vec![],
+            entries: 0,
+            basket_size: DEFAULT_BASKET_SIZE,
+        }
+    }
+
+    /// Size in bytes (before compression) at which the basket of a
+    /// branch is written to the file
+    pub fn set_basket_size(&mut self, basket_size: usize) {
+        self.basket_size = basket_size;
+    }
+
+    /// Number of entries filled so far
+    pub fn entries(&self) -> i64 {
+        self.entries
+    }
+
+    /// Add a branch with a single element per entry
+    pub fn add_branch<T: LeafType>(&mut self, name: &str) -> Result<Branch<T>, Error> {
+        self.push_branch(BranchWriter::new::<T>(name, Shape::Scalar))
+    }
+
+    /// Add a branch with `len` elements per entry
+    pub fn add_array_branch<T: LeafType>(&mut self, name: &str, len: usize) -> Result<Branch<T>, Error> {
+        self.push_branch(BranchWriter::new::<T>(name, Shape::Array(len)))
+    }
+
+    /// Add a branch with a variable number of elements per entry. The
+    /// number of elements is given by the value of the integer branch
+    /// `counter` in the same entry.
+    pub fn add_jagged_branch<T: LeafType, C: LeafType>(&mut self, name: &str, counter: Branch<C>)
+                                                       -> Result<Branch<T>, Error> {
+        if !self.branches[counter.idx].is_counter_candidate() {
+            return Err(format_err!("Branch {} cannot be used as a counter",
+                                   self.branches[counter.idx].name));
+        }
+        self.push_branch(BranchWriter::new::<T>(name, Shape::Jagged(counter.idx)))
+    }
+
+    fn push_branch<T>(&mut self, branch: BranchWriter) -> Result<Branch<T>, Error> {
+        if self.entries > 0 {
+            return Err(format_err!("Cannot add branch {} after entries were filled", branch.name));
+        }
+        if self.branches.iter().any(|b| b.name == branch.name) {
+            return Err(format_err!("Branch {} already exists", branch.name));
+        }
+        self.branches.push(branch);
+        Ok(Branch {idx: self.branches.len() - 1, phantom: PhantomData})
+    }
+
+    /// Set the value of a branch with a single element per entry for
+    /// the current entry
+    pub fn set<T: LeafType>(&mut self, branch: Branch<T>, value: T) -> Result<(), Error> {
+        self.branches[branch.idx].set(&[value])
+    }
+
+    /// Set the elements of an array or jagged branch for the current entry
+    pub fn set_slice<T: LeafType>(&mut self, branch: Branch<T>, values: &[T]) -> Result<(), Error> {
+        self.branches[branch.idx].set(values)
+    }
+
+    /// Fill the current entry. Every branch must have been set and the
+    /// number of elements of jagged branches must match their
+    /// counter. Full baskets are written to the given file.
+    pub fn fill(&mut self, f: &mut RootFileWriter) -> Result<(), Error> {
+        for b in &self.branches {
+            let len = b.pending_len()
+                .ok_or_else(|| format_err!("Branch {} was not set for entry {}", b.name, self.entries))?;
+            if let Shape::Jagged(counter) = b.shape {
+                let count = self.branches[counter].pending_count()
+                    .ok_or_else(|| format_err!("Counter of branch {} is not a valid count", b.name))?;
+                if count != len {
+                    return Err(format_err!("Branch {} has {} elements but its counter {} is {}",
+                                           b.name, len, self.branches[counter].name, count));
+                }
+            }
+        }
+        for b in &mut self.branches {
+            b.commit();
+            if b.basket_len() >= self.basket_size {
+                b.flush(f, &self.name)?;
+            }
+        }
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Write the remaining baskets and the tree itself to the given file
+    pub fn write(mut self, f: &mut RootFileWriter) -> Result<(), Error> {
+        for b in &mut self.branches {
+            b.flush(f, &self.name)?;
+        }
+        let compress = f.compression_setting();
+        let (name, title) = (self.name.clone(), self.title.clone());
+        f.write_object(&name, &title, &TTree {tree: &self, compress})
+    }
+}
+
+/// The `TTree` of a `TreeWriter` as it is written to the file
+struct TTree<'a> {
+    tree: &'a TreeWriter,
+    compress: i32,
+}
+
+impl<'a> Streamable for TTree<'a> {
+    fn class_name(&self) -> &str {
+        "TTree"
+    }
+
+    /// Counterpart to the `ttree` parser
+    fn stream(&self, buf: &mut WBuffer) {
+        let tree = self.tree;
+        let branches = &tree.branches;
+        let (tot_bytes, zip_bytes) = branches.iter()
+            .map(|b| b.n_bytes())
+            .fold((0, 0), |(t, z), (bt, bz)| (t + bt, z + bz));
+        buf.put_version(19, |w| {
+            TNamed {name: tree.name.clone(), title: tree.title.clone()}.stream(w);
+            put_tattline(w);
+            put_tattfill(w);
+            put_tattmarker(w);
+            w.put_i64(tree.entries);
+            w.put_i64(tot_bytes);
+            w.put_i64(zip_bytes);
+            // Saved and flushed bytes
+            w.put_i64(0);
+            w.put_i64(0);
+            // Weight
+            w.put_f64(1.0);
+            // Timer interval, scan field, update
+            w.put_i32(0);
+            w.put_i32(25);
+            w.put_i32(0);
+            // Default entry offset len
+            w.put_i32(1000);
+            // Number of cluster ranges
+            w.put_i32(0);
+            // Max entries, max entry loop
+            w.put_i64(1_000_000_000_000);
+            w.put_i64(1_000_000_000_000);
+            // Max virtual size, auto save, auto flush
+            w.put_i64(0);
+            w.put_i64(-300_000_000);
+            w.put_i64(-30_000_000);
+            // Estimate
+            w.put_i64(1_000_000);
+            // Cluster range end and cluster size are null pointers
+            w.put_u8(0);
+            w.put_u8(0);
+            put_tobjarray(w, branches.len(), |w, i| {
+                branches[i].put_branch(w, branches, i, self.compress, tree.basket_size)
+            });
+            put_tobjarray(w, branches.len(), |w, i| {
+                w.put_object_ref(i, |w| branches[i].put_leaf(w, branches))
+            });
+            // Aliases
+            w.put_null();
+            // Index values and index
+            put_tarrayd(w, &[]);
+            w.put_i32(0);
+            // Tree index, friends, user info, branch ref
+            for _ in 0..4 {
+                w.put_null();
+            }
+        });
+    }
+}
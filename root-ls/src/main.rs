@@ -6,12 +6,14 @@ extern crate rustfmt;
 extern crate failure;
 
 use std::env;
+use std::fmt;
 use std::path::PathBuf;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{self, BufWriter, Read, Write};
 use clap::{Arg, ArgMatches, App, AppSettings, SubCommand};
 use failure::Error;
-use root_io::RootFile;
+use root_io::{FileItem, RootFile, RootObject};
+use root_io::tree_reader::{write_json_string, ColumnValueIntoIter, Value};
 
 fn main() {
     let matches = App::new("Inspect root files")
@@ -36,6 +38,27 @@ fn main() {
                     .args_from_usage(
                         "--output=[OUTPUT] 'Output is written to this file'
                          --rustfmt 'Format the output with `Rustfmt` (slow!)'"))
+        .subcommand(SubCommand::with_name("read-rules")
+                    .about("List the schema evolution rules stored in the StreamerInfo"))
+        .subcommand(SubCommand::with_name("schema")
+                    .about("Describe the branches of a tree as JSON")
+                    .args_from_usage(
+                        "--tree=<TREE> 'Name of the tree'
+                         --arrow 'Write an Apache Arrow schema (JSON representation) instead'"))
+        .subcommand(SubCommand::with_name("dump")
+                    .about("Stream the selected branches of a tree as newline delimited JSON or CSV")
+                    .args_from_usage(
+                        "--tree=<TREE> 'Name of the tree'
+                         --branches=[BRANCHES] 'Comma separated branch names; may contain `*` and `?` (default: all)'
+                         --range=[RANGE] 'Entries `START..END` to write; either end may be omitted'
+                         --limit=[N] 'Write at most `N` entries'
+                         --format=[FORMAT] 'Either `ndjson` or `csv` (default: `ndjson`)'"))
+        .subcommand(SubCommand::with_name("print-model")
+                    .about("Generate a typed model and iterator for the selected branches of a tree")
+                    .args_from_usage(
+                        "--tree=<TREE> 'Name of the tree'
+                         --branches=<BRANCHES> 'Comma separated branch names; may contain `*` and `?`'
+                         --name=[NAME] 'Name of the generated struct (default: `Model`)'"))
         .get_matches();
     let in_path = PathBuf::from(matches.value_of("INPUT").unwrap());
     let f = root_io::RootFile::new_from_file(&in_path).expect("Failed to open file");
@@ -46,6 +69,14 @@ fn main() {
         sinfo_to_yaml(&f);
     } else if let Some(matches) = matches.subcommand_matches("to-rust") {
         to_rust(&f, matches).unwrap();
+    } else if matches.subcommand_matches("read-rules").is_some() {
+        print_read_rules(&f).unwrap();
+    } else if let Some(matches) = matches.subcommand_matches("schema") {
+        print_schema(&f, matches).unwrap();
+    } else if let Some(matches) = matches.subcommand_matches("dump") {
+        dump(&f, matches).unwrap();
+    } else if let Some(matches) = matches.subcommand_matches("print-model") {
+        print_model(&f, matches).unwrap();
     } else {
         // Write help if no sub command is given
         println!("{}", matches.usage());
@@ -53,25 +84,61 @@ fn main() {
 }
 
 fn inspect_file(f: &RootFile, sub_matches: &ArgMatches) {
+    let verbose = sub_matches.is_present("v");
     if sub_matches.is_present("item-pos") {
         let idx = value_t!(sub_matches.value_of("item-pos"), usize).unwrap();
-        // FIXME: This should not be specific for TTrees!
-        let tree = f.items()[idx].as_tree().unwrap();
-        if sub_matches.is_present("v") {
-            println!("{:#?}", tree);
-        } else {
-            for &(ref name, ref types) in &tree.branch_names_and_types() {
-                println!("{}: {:#?}", name, types);
-            }
+        let obj = f.items()[idx].object().unwrap();
+        if verbose {
+            println!("{:#?}", obj);
+            return;
+        }
+        match obj {
+            RootObject::Tree(tree) => {
+                for (name, types) in &tree.branch_names_and_types() {
+                    println!("{}: {:#?}", name, types);
+                }
+            },
+            RootObject::Directory(items) => {
+                println!("Items in directory:");
+                print_items(&items, verbose);
+            },
+            RootObject::Histogram(h) => {
+                for ax in h.axes() {
+                    println!("{} ({}): {} bins in [{}, {}]", ax.name(), ax.title(), ax.nbins(),
+                             ax.edges()[0], ax.edges()[ax.nbins()]);
+                }
+                println!("entries: {}", h.entries());
+            },
+            RootObject::Profile(p) => {
+                println!("means: {:?}", p.means());
+                println!("errors: {:?}", p.errors());
+            },
+            RootObject::Graph(g) => {
+                println!("{}: {:?}", g.x_title(), g.x());
+                println!("{}: {:?}", g.y_title(), g.y());
+            },
+            RootObject::ObjString(s) => println!("{}", s),
+            RootObject::List(objs) => println!("List of {} objects", objs.len()),
+            RootObject::Named(n) => println!("{}: {}", n.name, n.title),
+            RootObject::Unknown {class_name, data, members} => {
+                println!("Object of unsupported class `{}` ({} bytes)", class_name, data.len());
+                for (member, source) in members {
+                    println!("{}: {:?}", member, source);
+                }
+            },
         }
     } else {
         println!("Items in file:");
-        for (i, item) in f.items().iter().enumerate() {
-            if sub_matches.is_present("v") {
-                println!("{}: {}", i, item.verbose_info());
-            } else {
-                println!("{}: {}", i, item.name());
-            }
+        print_items(f.items(), verbose);
+    }
+}
+
+fn print_items(items: &[FileItem], verbose: bool) {
+    for (i, item) in items.iter().enumerate() {
+        if verbose {
+            println!("{}: {}", i, item.verbose_info());
+        } else {
+            println!("{}: {}", i, item.name());
         }
     }
 }
@@ -108,3 +175,185 @@ fn to_rust(f: &RootFile, sub_matches: &ArgMatches) -> Result<(), Error> {
     println!("{}", s);
     Ok(())
 }
+
+fn print_read_rules(f: &RootFile) -> Result<(), Error> {
+    let rules = f.read_rules()?;
+    if rules.is_empty() {
+        println!("No read rules in file");
+    }
+    for rule in rules {
+        let versions: Vec<_> = rule.versions().iter()
+            .map(|&(lo, hi)| match (lo, hi) {
+                (Some(lo), Some(hi)) if lo == hi => lo.to_string(),
+                _ => format!("{}-{}",
+                             lo.map_or(String::new(), |v| v.to_string()),
+                             hi.map_or(String::new(), |v| v.to_string())),
+            })
+            .collect();
+        println!("{} -> {}", rule.source_class(), rule.target_class());
+        if !versions.is_empty() {
+            println!("  versions: [{}]", versions.join(","));
+        }
+        if !rule.checksums().is_empty() {
+            println!("  checksums: {:?}", rule.checksums());
+        }
+        if !rule.source().is_empty() {
+            let source: Vec<_> = rule.source().iter()
+                .map(|(t, n)| format!("{} {}", t, n).trim().to_string())
+                .collect();
+            println!("  source: {}", source.join("; "));
+        }
+        println!("  target: {}", rule.target().join(", "));
+        if !rule.include().is_empty() {
+            println!("  include: {}", rule.include().join(", "));
+        }
+        println!("  code: {}", rule.code());
+    }
+    Ok(())
+}
+
+fn print_schema(f: &RootFile, sub_matches: &ArgMatches) -> Result<(), Error> {
+    let schema = f.tree(sub_matches.value_of("tree").unwrap())?.schema();
+    let mut s = String::new();
+    if sub_matches.is_present("arrow") {
+        schema.to_arrow_json(&mut s)?;
+    } else {
+        schema.to_json(&mut s)?;
+    }
+    print!("{}", s);
+    Ok(())
+}
+
+/// Parse a range of entries such as `10..20`, `10..` or `..20`
+fn parse_range(range: &str) -> Result<(usize, Option<usize>), Error> {
+    let mut bounds = range.splitn(2, "..");
+    let start = bounds.next().unwrap_or("");
+    let end = bounds.next()
+        .ok_or_else(|| format_err!("Invalid range `{}`; expected `START..END`", range))?;
+    let start = if start.is_empty() { 0 } else { start.parse()? };
+    let end = if end.is_empty() { None } else { Some(end.parse()?) };
+    Ok((start, end))
+}
+
+/// Write `v` as JSON; non-finite floats become `null`
+fn write_json_value(s: &mut String, v: &Value) -> fmt::Result {
+    match *v {
+        Value::Bool(b) => s.push_str(&b.to_string()),
+        Value::Int(i) => s.push_str(&i.to_string()),
+        Value::UInt(u) => s.push_str(&u.to_string()),
+        Value::Float(f) if f.is_finite() => s.push_str(&format!("{:?}", f)),
+        Value::Double(f) if f.is_finite() => s.push_str(&format!("{:?}", f)),
+        Value::Float(_) | Value::Double(_) => s.push_str("null"),
+        Value::String(ref v) => write_json_string(s, v)?,
+        Value::Array(ref vals) => {
+            s.push('[');
+            for (i, v) in vals.iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                write_json_value(s, v)?;
+            }
+            s.push(']');
+        },
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if needed
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write `v` as a CSV field; arrays are written as JSON
+fn write_csv_value(s: &mut String, v: &Value) -> fmt::Result {
+    match *v {
+        Value::String(ref v) => s.push_str(&csv_field(v)),
+        Value::Float(f) => s.push_str(&f.to_string()),
+        Value::Double(f) => s.push_str(&f.to_string()),
+        Value::Array(_) => {
+            let mut json = String::new();
+            write_json_value(&mut json, v)?;
+            s.push_str(&csv_field(&json));
+        },
+        _ => write_json_value(s, v)?,
+    }
+    Ok(())
+}
+
+fn dump(f: &RootFile, sub_matches: &ArgMatches) -> Result<(), Error> {
+    let tree = f.tree(sub_matches.value_of("tree").unwrap())?;
+    let globs: Vec<_> = sub_matches.value_of("branches").unwrap_or("*").split(',').collect();
+    let (start, end) = match sub_matches.value_of("range") {
+        Some(range) => parse_range(range)?,
+        None => (0, None),
+    };
+    let mut n_entries = end.unwrap_or(tree.entries() as usize).saturating_sub(start);
+    if let Some(limit) = sub_matches.value_of("limit") {
+        n_entries = n_entries.min(limit.parse()?);
+    }
+    let csv = match sub_matches.value_of("format").unwrap_or("ndjson") {
+        "ndjson" => false,
+        "csv" => true,
+        other => return Err(format_err!("Unknown format `{}`", other)),
+    };
+
+    let mut names = vec![];
+    let mut columns = vec![];
+    for name in tree.select_branch_names(&globs)? {
+        match ColumnValueIntoIter::new_from_entry(&tree, &name, start) {
+            Ok(col) => {
+                columns.push(col);
+                names.push(name);
+            },
+            Err(e) => eprintln!("Skipped branch `{}`: {}", name, e),
+        }
+    }
+    if columns.is_empty() {
+        return Err(format_err!("None of the selected branches can be read"));
+    }
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if csv {
+        let header: Vec<_> = names.iter().map(|n| csv_field(n)).collect();
+        writeln!(out, "{}", header.join(","))?;
+    }
+    let mut line = String::new();
+    for _ in 0..n_entries {
+        line.clear();
+        line.push_str(if csv { "" } else { "{" });
+        for (i, (name, col)) in names.iter().zip(columns.iter_mut()).enumerate() {
+            let value = match col.next() {
+                Some(v) => v,
+                None => return Ok(()),
+            };
+            if i > 0 {
+                line.push(',');
+            }
+            if csv {
+                write_csv_value(&mut line, &value)?;
+            } else {
+                write_json_string(&mut line, name)?;
+                line.push(':');
+                write_json_value(&mut line, &value)?;
+            }
+        }
+        line.push_str(if csv { "\n" } else { "}\n" });
+        out.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn print_model(f: &RootFile, sub_matches: &ArgMatches) -> Result<(), Error> {
+    let tree = sub_matches.value_of("tree").unwrap();
+    let branches: Vec<_> = sub_matches.value_of("branches").unwrap().split(',').collect();
+    let name = sub_matches.value_of("name").unwrap_or("Model");
+    let mut s = String::new();
+    f.tree_model_as_rust(tree, &branches, name, &mut s)?;
+    println!("{}", s);
+    Ok(())
+}
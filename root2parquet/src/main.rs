@@ -0,0 +1,107 @@
+extern crate arrow_array;
+extern crate clap;
+#[macro_use]
+extern crate failure;
+extern crate parquet;
+extern crate root_io;
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process;
+use arrow_array::RecordBatchReader;
+use clap::{App, ArgMatches};
+use failure::Error;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use root_io::RootFile;
+use root_io::tree_reader::RecordBatchIntoIter;
+
+fn main() {
+    let matches = App::new("Convert trees in root files to Parquet")
+        .version("0.1.0")
+        .args_from_usage(
+            "<OUTPUT> 'Output .parquet file'
+             <INPUT>... 'Input .root files; their trees must have the same layout'
+             --tree=<TREE> 'Name of the tree'
+             --branches=[BRANCHES] 'Comma separated branch names; may contain `*` and `?` (default: all)'
+             --row-group-size=[N] 'Maximum number of entries per row group (default: 1048576)'
+             --compression=[ALGO] 'One of `none`, `snappy`, `gzip`, or `zstd` (default: `snappy`)'")
+        .get_matches();
+    if let Err(e) = convert(&matches) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn writer_properties(matches: &ArgMatches) -> Result<WriterProperties, Error> {
+    let compression = match matches.value_of("compression").unwrap_or("snappy") {
+        "none" => Compression::UNCOMPRESSED,
+        "snappy" => Compression::SNAPPY,
+        "gzip" => Compression::GZIP(GzipLevel::default()),
+        "zstd" => Compression::ZSTD(ZstdLevel::default()),
+        other => return Err(format_err!("Unknown compression `{}`", other)),
+    };
+    let mut props = WriterProperties::builder().set_compression(compression);
+    if let Some(n) = matches.value_of("row-group-size") {
+        props = props.set_max_row_group_size(n.parse()?);
+    }
+    Ok(props.build())
+}
+
+fn convert(matches: &ArgMatches) -> Result<(), Error> {
+    let tree_name = matches.value_of("tree").unwrap();
+    let branches: Vec<_> = matches.value_of("branches").unwrap_or("*").split(',').collect();
+    let props = writer_properties(matches)?;
+    let out_path = PathBuf::from(matches.value_of("OUTPUT").unwrap());
+
+    // Check the layout of every input before creating the output
+    let mut inputs = vec![];
+    for in_path in matches.values_of("INPUT").unwrap() {
+        let tree = RootFile::new_from_file(&PathBuf::from(in_path))?.tree(tree_name)?;
+        let batches = RecordBatchIntoIter::new(&tree, &branches)?;
+        for name in batches.skipped() {
+            eprintln!("{}: Skipped branch `{}`: its type is not supported", in_path, name);
+        }
+        if let Some((_, first)) = inputs.first() {
+            if first != &batches.schema() {
+                return Err(format_err!("The layout of `{}` in {} differs from the previous files",
+                                       tree_name, in_path));
+            }
+        }
+        inputs.push((tree, batches.schema()));
+    }
+    let schema = match inputs.first() {
+        Some((_, schema)) => schema.clone(),
+        None => return Err(format_err!("No input files")),
+    };
+
+    let f = File::create(&out_path)?;
+    let mut writer = ArrowWriter::try_new(f, schema, Some(props))?;
+    let mut write = || -> Result<usize, Error> {
+        let mut entries = 0;
+        for (tree, _) in &inputs {
+            for batch in RecordBatchIntoIter::new(tree, &branches)? {
+                let batch = batch?;
+                entries += batch.num_rows();
+                writer.write(&batch)?;
+            }
+        }
+        Ok(entries)
+    };
+    let written = write().and_then(|entries| {
+        writer.close()?;
+        Ok(entries)
+    });
+    match written {
+        Ok(entries) => {
+            println!("Wrote {} entries to {}", entries, out_path.display());
+            Ok(())
+        },
+        Err(e) => {
+            // Do not leave a half written file behind
+            let _ = fs::remove_file(&out_path);
+            Err(e)
+        }
+    }
+}